@@ -0,0 +1,65 @@
+use anyhow::Context;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Passed by the autostart entry, the window starts minimized
+pub const MINIMIZED_ARG: &str = "--minimized";
+
+/// The desktop entry installed by `script/share-rs.desktop`, used as the template of the
+/// autostart entry
+const DESKTOP_ENTRY_TEMPLATE: &str = include_str!("../script/share-rs.desktop");
+
+/// The autostart entry, see the XDG Autostart specification
+fn autostart_file() -> PathBuf {
+    dirs::config_dir()
+        .unwrap()
+        .join("autostart")
+        .join("share-rs.desktop")
+}
+
+pub fn is_autostart_enabled() -> bool {
+    fs::exists(autostart_file()).unwrap_or(false)
+}
+
+/// Create or remove the autostart entry, the entry launches the current executable
+pub fn set_autostart(enabled: bool) -> anyhow::Result<()> {
+    let file = autostart_file();
+    if !enabled {
+        if fs::exists(&file)? {
+            fs::remove_file(&file).context("Failed to remove autostart entry")?;
+        }
+        return Ok(());
+    }
+
+    let executable = std::env::current_exe().context("Failed to get current executable")?;
+    fs::create_dir_all(file.parent().unwrap())?;
+    fs::write(&file, autostart_entry(&executable)).context("Failed to write autostart entry")?;
+    Ok(())
+}
+
+/// Replace the `Exec` of the template, no files are opened at login
+fn autostart_entry(executable: &Path) -> String {
+    let exec = format!("Exec=\"{}\" {}", executable.display(), MINIMIZED_ARG);
+    let mut lines: Vec<String> = DESKTOP_ENTRY_TEMPLATE
+        .lines()
+        .map(|line| {
+            if line.starts_with("Exec=") {
+                exec.clone()
+            } else {
+                line.to_string()
+            }
+        })
+        .collect();
+    lines.push("X-GNOME-Autostart-enabled=true".to_string());
+    lines.join("\n") + "\n"
+}
+
+#[test]
+fn test_autostart_entry() {
+    let entry = autostart_entry(Path::new("/opt/share rs/share-rs"));
+    assert!(entry.starts_with("[Desktop Entry]\n"));
+    assert!(entry.contains("\nExec=\"/opt/share rs/share-rs\" --minimized\n"));
+    assert_eq!(1, entry.matches("Exec=").count());
+    assert!(entry.contains("\nName=Share\n"));
+    assert!(entry.ends_with("X-GNOME-Autostart-enabled=true\n"));
+}
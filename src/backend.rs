@@ -1,3 +1,4 @@
 pub mod db;
 pub mod entities;
+pub mod watcher;
 pub mod web;
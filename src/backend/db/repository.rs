@@ -1,10 +1,29 @@
-use crate::backend::entities::prelude::{Chunks, Uploads};
-use crate::backend::entities::{chunks, uploads};
+use crate::backend::entities::prelude::{
+    BlockedRequests, Chunks, CollectionShares, Collections, Devices, Shares, Transfers, Uploads,
+};
+use crate::backend::entities::{
+    blocked_requests, chunks, collection_shares, collections, devices, shares, transfers, uploads,
+};
+use crate::backend::watcher::ShareStatus;
+use crate::mimes::{MimeCategory, detect_mime_type};
+use crate::util::{generate_access_token, hash_pin, read_file_metadata};
 use anyhow::Context;
+pub use blocked_requests::Model as BlockedRequest;
+use chrono::{Local, NaiveDateTime};
 pub use chunks::Model as Chunk;
-use sea_orm::DatabaseConnection;
-use sea_orm::{ActiveModelTrait, QueryFilter};
+pub use collections::Model as Collection;
+pub use devices::Model as Device;
+use log::info;
+use sea_orm::sea_query::{Expr, LikeExpr, Query, SelectStatement};
+use sea_orm::strum::{Display as StrumDisplay, EnumString};
+use sea_orm::{ActiveModelTrait, IntoActiveModel, QueryFilter, QueryOrder, Set};
 use sea_orm::{ColumnTrait, EntityTrait};
+use sea_orm::{Condition, DatabaseConnection};
+use sea_orm::{Order, PaginatorTrait, QuerySelect, Select};
+pub use shares::ActiveModel as ShareActiveModel;
+pub use shares::Model as Share;
+use std::path::Path;
+pub use transfers::Model as Transfer;
 pub use uploads::ActiveModel as UploadItemActiveModel;
 pub use uploads::Model as UploadItem;
 
@@ -108,3 +127,775 @@ pub async fn delete_chunk_by_id(
         .context(format!("Failed to delete chunk {}", chunk_id))?;
     Ok(())
 }
+
+/// 插入一条被拦截的请求记录
+pub async fn insert_blocked_request(
+    connection: &DatabaseConnection,
+    blocked_request: BlockedRequest,
+) -> anyhow::Result<BlockedRequest> {
+    let mut blocked_request: blocked_requests::ActiveModel = blocked_request.into();
+    blocked_request.not_set(blocked_requests::Column::Id);
+    let result = blocked_request
+        .insert(connection)
+        .await
+        .context("Failed to insert blocked request")?;
+    Ok(result)
+}
+
+/// 获取所有被拦截的请求记录，最新的在前
+pub async fn get_blocked_requests(
+    connection: &DatabaseConnection,
+) -> anyhow::Result<Vec<BlockedRequest>> {
+    let result = BlockedRequests::find()
+        .order_by_desc(blocked_requests::Column::CreatedAt)
+        .all(connection)
+        .await
+        .context("Failed to query blocked requests")?;
+    Ok(result)
+}
+
+/// 清空被拦截的请求记录
+pub async fn delete_all_blocked_requests(connection: &DatabaseConnection) -> anyhow::Result<()> {
+    BlockedRequests::delete_many()
+        .exec(connection)
+        .await
+        .context("Failed to delete blocked requests")?;
+    Ok(())
+}
+
+/// 查询传输记录的过滤条件
+#[derive(Debug, Default, Clone)]
+pub struct TransferFilter {
+    /// 传输方向，为None时不过滤
+    pub direction: Option<String>,
+    /// 匹配文件名、客户端IP或者设备名称
+    pub keyword: Option<String>,
+}
+
+/// 插入一条传输记录
+pub async fn insert_transfer(
+    connection: &DatabaseConnection,
+    transfer: Transfer,
+) -> anyhow::Result<Transfer> {
+    let mut transfer: transfers::ActiveModel = transfer.into();
+    transfer.not_set(transfers::Column::Id);
+    let result = transfer
+        .insert(connection)
+        .await
+        .context("Failed to insert transfer")?;
+    Ok(result)
+}
+
+/// 增加传输记录中已传输的字节数
+pub async fn add_transfer_bytes(
+    connection: &DatabaseConnection,
+    transfer_id: i32,
+    bytes: i64,
+) -> anyhow::Result<()> {
+    Transfers::update_many()
+        .col_expr(
+            transfers::Column::Bytes,
+            Expr::col(transfers::Column::Bytes).add(bytes),
+        )
+        .filter(transfers::Column::Id.eq(transfer_id))
+        .exec(connection)
+        .await
+        .context(format!(
+            "Failed to update bytes of transfer {}",
+            transfer_id
+        ))?;
+    Ok(())
+}
+
+/// 结束一条传输记录，`bytes`为None时保留已记录的字节数
+pub async fn finish_transfer(
+    connection: &DatabaseConnection,
+    transfer_id: i32,
+    bytes: Option<i64>,
+    result: &str,
+) -> anyhow::Result<()> {
+    let mut update = Transfers::update_many()
+        .col_expr(
+            transfers::Column::FinishedAt,
+            Expr::value(Local::now().naive_local()),
+        )
+        .col_expr(transfers::Column::Result, Expr::value(result));
+    if let Some(bytes) = bytes {
+        update = update.col_expr(transfers::Column::Bytes, Expr::value(bytes));
+    }
+    update
+        .filter(transfers::Column::Id.eq(transfer_id))
+        .exec(connection)
+        .await
+        .context(format!("Failed to finish transfer {}", transfer_id))?;
+    Ok(())
+}
+
+/// 获取上传项最新的一条传输记录
+pub async fn get_latest_upload_transfer(
+    connection: &DatabaseConnection,
+    upload_id: &str,
+) -> anyhow::Result<Option<Transfer>> {
+    let result = Transfers::find()
+        .filter(transfers::Column::UploadId.eq(upload_id))
+        .order_by_desc(transfers::Column::Id)
+        .one(connection)
+        .await
+        .context(format!(
+            "Failed to query transfers with upload_id {}",
+            upload_id
+        ))?;
+    Ok(result)
+}
+
+/// 模糊查询包含关键字的内容，关键字中的`%`、`_`按普通字符匹配
+fn contains_keyword(keyword: &str) -> LikeExpr {
+    let mut pattern = String::with_capacity(keyword.len() + 2);
+    pattern.push('%');
+    for c in keyword.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+    LikeExpr::new(pattern).escape('\\')
+}
+
+/// 根据过滤条件获取传输记录，最新的在前
+pub async fn get_transfers(
+    connection: &DatabaseConnection,
+    filter: &TransferFilter,
+) -> anyhow::Result<Vec<Transfer>> {
+    let mut query = Transfers::find();
+    if let Some(direction) = &filter.direction {
+        query = query.filter(transfers::Column::Direction.eq(direction));
+    }
+    if let Some(keyword) = filter.keyword.as_deref().filter(|k| !k.is_empty()) {
+        query = query.filter(
+            Condition::any()
+                .add(transfers::Column::FileName.like(contains_keyword(keyword)))
+                .add(transfers::Column::ClientIp.like(contains_keyword(keyword)))
+                .add(
+                    transfers::Column::DeviceId.in_subquery(
+                        Query::select()
+                            .column(devices::Column::Id)
+                            .from(Devices)
+                            .and_where(devices::Column::Name.like(contains_keyword(keyword)))
+                            .to_owned(),
+                    ),
+                ),
+        );
+    }
+    let result = query
+        .order_by_desc(transfers::Column::StartedAt)
+        .all(connection)
+        .await
+        .context("Failed to query transfers")?;
+    Ok(result)
+}
+
+/// 保存设备信息，设备已存在时更新设备名称
+pub async fn save_device(
+    connection: &DatabaseConnection,
+    device: Device,
+) -> anyhow::Result<Device> {
+    let existing = Devices::find_by_id(device.id.clone())
+        .one(connection)
+        .await
+        .context(format!("Failed to query device {}", device.id))?;
+    let result = match existing {
+        Some(existing) => {
+            let mut active_model = existing.into_active_model();
+            active_model.name = Set(device.name);
+            active_model.client_ip = Set(device.client_ip);
+            active_model.user_agent = Set(device.user_agent);
+            active_model.updated_at = Set(device.updated_at);
+            active_model
+                .update(connection)
+                .await
+                .context("Failed to update device")?
+        }
+        None => {
+            let active_model: devices::ActiveModel = device.into();
+            active_model
+                .insert(connection)
+                .await
+                .context("Failed to insert device")?
+        }
+    };
+    Ok(result)
+}
+
+/// 获取所有的设备
+pub async fn get_devices(connection: &DatabaseConnection) -> anyhow::Result<Vec<Device>> {
+    let result = Devices::find()
+        .all(connection)
+        .await
+        .context("Failed to query devices")?;
+    Ok(result)
+}
+
+/// 将文件添加到分享列表，哈希值在第一次获取分享详情时才计算
+pub async fn insert_share<P: AsRef<Path>>(
+    connection: &DatabaseConnection,
+    share_id: i64,
+    file_path: P,
+) -> anyhow::Result<Share> {
+    let file_path = file_path.as_ref();
+    let (file_size, modified_at) = read_file_metadata(file_path).await?;
+    let file_name = file_path
+        .file_name()
+        .map(|file_name| file_name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let file_path = file_path.to_string_lossy().into_owned();
+    let share: shares::ActiveModel = Share {
+        id: share_id,
+        file_name,
+        mime_type: detect_mime_type(&file_path),
+        file_path,
+        file_size,
+        modified_at: Some(modified_at),
+        sha256: None,
+        status: ShareStatus::Available.to_string(),
+        access_token: generate_access_token(),
+        expires_at: None,
+    }
+    .into();
+    let result = share
+        .insert(connection)
+        .await
+        .context("Failed to insert share")?;
+    Ok(result)
+}
+
+/// 获取分享项
+pub async fn get_share(
+    connection: &DatabaseConnection,
+    share_id: i64,
+) -> anyhow::Result<Option<Share>> {
+    let result = Shares::find_by_id(share_id)
+        .one(connection)
+        .await
+        .context(format!("Failed to query share {}", share_id))?;
+    Ok(result)
+}
+
+/// 更新分享项
+pub async fn update_share(
+    connection: &DatabaseConnection,
+    share: shares::ActiveModel,
+) -> anyhow::Result<Share> {
+    let result = share
+        .update(connection)
+        .await
+        .context("Failed to update share")?;
+    Ok(result)
+}
+
+/// 根据访问令牌获取分享项
+pub async fn get_share_by_token(
+    connection: &DatabaseConnection,
+    access_token: &str,
+) -> anyhow::Result<Option<Share>> {
+    let result = Shares::find()
+        .filter(shares::Column::AccessToken.eq(access_token))
+        .filter(not_expired())
+        .one(connection)
+        .await
+        .context("Failed to query share by access token")?;
+    Ok(result)
+}
+
+/// 重新生成分享项的访问令牌，旧的下载链接随之失效
+pub async fn regenerate_share_token(
+    connection: &DatabaseConnection,
+    share_id: i64,
+) -> anyhow::Result<Share> {
+    let share = get_share(connection, share_id)
+        .await?
+        .context(format!("Share {} not found", share_id))?;
+    let mut share = share.into_active_model();
+    share.access_token = Set(generate_access_token());
+    update_share(connection, share).await
+}
+
+/// 根据文件路径获取分享项
+pub async fn get_share_by_path(
+    connection: &DatabaseConnection,
+    file_path: &str,
+) -> anyhow::Result<Option<Share>> {
+    let result = Shares::find()
+        .filter(shares::Column::FilePath.eq(file_path))
+        .one(connection)
+        .await
+        .context(format!("Failed to query share of {:?}", file_path))?;
+    Ok(result)
+}
+
+/// 获取所有的分享项
+pub async fn get_all_shares(connection: &DatabaseConnection) -> anyhow::Result<Vec<Share>> {
+    let result = Shares::find()
+        .all(connection)
+        .await
+        .context("Failed to query shares")?;
+    Ok(result)
+}
+
+/// 分享列表的排序字段
+#[derive(EnumString, StrumDisplay, Debug, Default, PartialEq, Clone, Copy)]
+#[strum(serialize_all = "lowercase")]
+pub enum ShareSortBy {
+    /// 按添加的顺序，分享项的id是按时间递增的雪花id
+    #[default]
+    Added,
+    Name,
+    Size,
+    /// 按文件的修改时间
+    Date,
+}
+
+/// 查询分享列表的过滤条件
+#[derive(Debug, Default, Clone)]
+pub struct ShareFilter {
+    /// 匹配文件名
+    pub keyword: Option<String>,
+    /// 文件类型，为None时不过滤
+    pub category: Option<MimeCategory>,
+    pub sort_by: ShareSortBy,
+    pub descending: bool,
+    /// 只包含公开的分享项，网页端的分享列表使用。属于设置了PIN的分享集合的分享项不公开，
+    /// 只能通过集合访问
+    pub public_only: bool,
+}
+
+/// 文件类型对应的查询条件
+fn mime_category_condition(category: MimeCategory) -> Condition {
+    if category == MimeCategory::Other {
+        // 不属于其他任何类型
+        return [
+            MimeCategory::Image,
+            MimeCategory::Video,
+            MimeCategory::Audio,
+            MimeCategory::Text,
+            MimeCategory::Document,
+            MimeCategory::Archive,
+        ]
+        .into_iter()
+        .fold(Condition::all(), |condition, category| {
+            condition.add(mime_category_condition(category).not())
+        });
+    }
+    let mut condition = Condition::any();
+    if let Some(prefix) = category.mime_prefix() {
+        condition = condition.add(shares::Column::MimeType.starts_with(prefix));
+    }
+    if !category.mime_types().is_empty() {
+        condition = condition.add(shares::Column::MimeType.is_in(category.mime_types().to_vec()));
+    }
+    condition
+}
+
+/// 没有设置过期时间或者还没有过期的分享项
+fn not_expired() -> Condition {
+    Condition::any()
+        .add(shares::Column::ExpiresAt.is_null())
+        .add(shares::Column::ExpiresAt.gt(Local::now().naive_local()))
+}
+
+/// 属于设置了PIN的分享集合的分享项
+fn pin_protected_share_ids() -> SelectStatement {
+    Query::select()
+        .column((CollectionShares, collection_shares::Column::ShareId))
+        .from(CollectionShares)
+        .inner_join(
+            Collections,
+            Expr::col((Collections, collections::Column::Id))
+                .equals((CollectionShares, collection_shares::Column::CollectionId)),
+        )
+        .and_where(Expr::col((Collections, collections::Column::Pin)).is_not_null())
+        .to_owned()
+}
+
+fn share_query(filter: &ShareFilter) -> Select<Shares> {
+    let mut query = Shares::find().filter(not_expired());
+    if filter.public_only {
+        query = query.filter(shares::Column::Id.not_in_subquery(pin_protected_share_ids()));
+    }
+    if let Some(keyword) = filter.keyword.as_deref().filter(|k| !k.is_empty()) {
+        query = query.filter(shares::Column::FileName.like(contains_keyword(keyword)));
+    }
+    if let Some(category) = filter.category {
+        query = query.filter(mime_category_condition(category));
+    }
+    let order = if filter.descending {
+        Order::Desc
+    } else {
+        Order::Asc
+    };
+    let column = match filter.sort_by {
+        ShareSortBy::Added => shares::Column::Id,
+        ShareSortBy::Name => shares::Column::FileName,
+        ShareSortBy::Size => shares::Column::FileSize,
+        ShareSortBy::Date => shares::Column::ModifiedAt,
+    };
+    // 排序字段相同时按添加顺序，保证分页结果稳定
+    query
+        .order_by(column, order.clone())
+        .order_by(shares::Column::Id, order)
+}
+
+/// 根据过滤条件获取分享列表
+pub async fn get_shares(
+    connection: &DatabaseConnection,
+    filter: &ShareFilter,
+) -> anyhow::Result<Vec<Share>> {
+    let result = share_query(filter)
+        .all(connection)
+        .await
+        .context("Failed to query shares")?;
+    Ok(result)
+}
+
+/// 根据过滤条件分页获取分享列表，`page`从0开始，同时返回符合条件的总数
+pub async fn get_shares_page(
+    connection: &DatabaseConnection,
+    filter: &ShareFilter,
+    page: u64,
+    page_size: u64,
+) -> anyhow::Result<(Vec<Share>, u64)> {
+    let paginator = share_query(filter).paginate(connection, page_size);
+    let total = paginator
+        .num_items()
+        .await
+        .context("Failed to count shares")?;
+    let result = paginator
+        .fetch_page(page)
+        .await
+        .context("Failed to query shares")?;
+    Ok((result, total))
+}
+
+/// 批量删除分享项
+pub async fn delete_shares(
+    connection: &DatabaseConnection,
+    share_ids: &[i64],
+) -> anyhow::Result<()> {
+    Shares::delete_many()
+        .filter(shares::Column::Id.is_in(share_ids.to_vec()))
+        .exec(connection)
+        .await
+        .context("Failed to delete shares")?;
+    Ok(())
+}
+
+/// 批量设置分享项的过期时间，`expires_at`为None表示永不过期
+pub async fn set_shares_expiry(
+    connection: &DatabaseConnection,
+    share_ids: &[i64],
+    expires_at: Option<NaiveDateTime>,
+) -> anyhow::Result<()> {
+    Shares::update_many()
+        .col_expr(shares::Column::ExpiresAt, Expr::value(expires_at))
+        .filter(shares::Column::Id.is_in(share_ids.to_vec()))
+        .exec(connection)
+        .await
+        .context("Failed to set expiry of shares")?;
+    Ok(())
+}
+
+/// 删除已经过期的分享项
+pub async fn delete_expired_shares(connection: &DatabaseConnection) -> anyhow::Result<()> {
+    let result = Shares::delete_many()
+        .filter(shares::Column::ExpiresAt.lte(Local::now().naive_local()))
+        .exec(connection)
+        .await
+        .context("Failed to delete expired shares")?;
+    if result.rows_affected > 0 {
+        info!("Deleted {} expired shares", result.rows_affected);
+    }
+    Ok(())
+}
+
+/// 将分享项指向新的文件，重新读取文件信息
+pub async fn relocate_share<P: AsRef<Path>>(
+    connection: &DatabaseConnection,
+    share_id: i64,
+    file_path: P,
+) -> anyhow::Result<Share> {
+    let file_path = file_path.as_ref();
+    let share = get_share(connection, share_id)
+        .await?
+        .context(format!("Share {} not found", share_id))?;
+    let (file_size, modified_at) = read_file_metadata(file_path).await?;
+    let file_path = file_path.to_string_lossy().into_owned();
+
+    let mut share = share.into_active_model();
+    if let Some(file_name) = Path::new(&file_path).file_name() {
+        share.file_name = Set(file_name.to_string_lossy().into_owned());
+    }
+    share.mime_type = Set(detect_mime_type(&file_path));
+    share.file_path = Set(file_path);
+    share.file_size = Set(file_size);
+    share.modified_at = Set(Some(modified_at));
+    share.sha256 = Set(None);
+    share.status = Set(ShareStatus::Available.to_string());
+    update_share(connection, share).await
+}
+
+/// 创建分享集合，`pin`为None时不需要PIN即可访问，PIN只保存哈希值
+pub async fn insert_collection(
+    connection: &DatabaseConnection,
+    collection_id: i64,
+    name: String,
+    pin: Option<String>,
+) -> anyhow::Result<Collection> {
+    let pin_hash = match pin {
+        Some(pin) => Some(
+            tokio::task::spawn_blocking(move || hash_pin(&pin))
+                .await
+                .context("Failed to hash PIN")?,
+        ),
+        None => None,
+    };
+    let collection: collections::ActiveModel = Collection {
+        id: collection_id,
+        name,
+        pin: pin_hash,
+        created_at: Local::now().naive_local(),
+        access_token: generate_access_token(),
+    }
+    .into();
+    let result = collection
+        .insert(connection)
+        .await
+        .context("Failed to insert collection")?;
+    Ok(result)
+}
+
+/// 获取分享集合
+pub async fn get_collection(
+    connection: &DatabaseConnection,
+    collection_id: i64,
+) -> anyhow::Result<Option<Collection>> {
+    let result = Collections::find_by_id(collection_id)
+        .one(connection)
+        .await
+        .context(format!("Failed to query collection {}", collection_id))?;
+    Ok(result)
+}
+
+/// 根据访问令牌获取分享集合
+pub async fn get_collection_by_token(
+    connection: &DatabaseConnection,
+    access_token: &str,
+) -> anyhow::Result<Option<Collection>> {
+    let result = Collections::find()
+        .filter(collections::Column::AccessToken.eq(access_token))
+        .one(connection)
+        .await
+        .context("Failed to query collection by access token")?;
+    Ok(result)
+}
+
+/// 重新生成分享集合的访问令牌，旧的集合链接随之失效
+pub async fn regenerate_collection_token(
+    connection: &DatabaseConnection,
+    collection_id: i64,
+) -> anyhow::Result<Collection> {
+    let collection = get_collection(connection, collection_id)
+        .await?
+        .context(format!("Collection {} not found", collection_id))?;
+    let mut collection = collection.into_active_model();
+    collection.access_token = Set(generate_access_token());
+    let result = collection
+        .update(connection)
+        .await
+        .context("Failed to update collection")?;
+    Ok(result)
+}
+
+/// 按创建的顺序获取所有的分享集合
+pub async fn get_collections(connection: &DatabaseConnection) -> anyhow::Result<Vec<Collection>> {
+    let result = Collections::find()
+        .order_by_asc(collections::Column::Id)
+        .all(connection)
+        .await
+        .context("Failed to query collections")?;
+    Ok(result)
+}
+
+/// 删除分享集合，集合中的分享项不会被删除
+pub async fn delete_collection(
+    connection: &DatabaseConnection,
+    collection_id: i64,
+) -> anyhow::Result<()> {
+    Collections::delete_by_id(collection_id)
+        .exec(connection)
+        .await
+        .context(format!("Failed to delete collection {}", collection_id))?;
+    Ok(())
+}
+
+/// 将分享项添加到集合中，已经在集合中的分享项会被忽略
+pub async fn add_shares_to_collection(
+    connection: &DatabaseConnection,
+    collection_id: i64,
+    share_ids: &[i64],
+) -> anyhow::Result<()> {
+    for &share_id in share_ids {
+        let existing = CollectionShares::find_by_id((collection_id, share_id))
+            .one(connection)
+            .await
+            .context("Failed to query collection share")?;
+        if existing.is_some() {
+            continue;
+        }
+        let collection_share = collection_shares::ActiveModel {
+            collection_id: Set(collection_id),
+            share_id: Set(share_id),
+        };
+        collection_share.insert(connection).await.context(format!(
+            "Failed to add share {} to collection {}",
+            share_id, collection_id
+        ))?;
+    }
+    Ok(())
+}
+
+/// 将分享项从集合中移除，分享项本身不会被删除
+pub async fn remove_share_from_collection(
+    connection: &DatabaseConnection,
+    collection_id: i64,
+    share_id: i64,
+) -> anyhow::Result<()> {
+    CollectionShares::delete_by_id((collection_id, share_id))
+        .exec(connection)
+        .await
+        .context(format!(
+            "Failed to remove share {} from collection {}",
+            share_id, collection_id
+        ))?;
+    Ok(())
+}
+
+fn collection_share_query(collection_id: i64) -> Select<Shares> {
+    Shares::find()
+        .inner_join(CollectionShares)
+        .filter(collection_shares::Column::CollectionId.eq(collection_id))
+        .filter(not_expired())
+}
+
+/// 按添加的顺序获取集合中的分享项
+pub async fn get_collection_shares(
+    connection: &DatabaseConnection,
+    collection_id: i64,
+) -> anyhow::Result<Vec<Share>> {
+    let result = collection_share_query(collection_id)
+        .order_by_asc(shares::Column::Id)
+        .all(connection)
+        .await
+        .context(format!(
+            "Failed to query shares of collection {}",
+            collection_id
+        ))?;
+    Ok(result)
+}
+
+/// 获取集合中的分享项，分享项不在集合中时返回None
+pub async fn get_collection_share(
+    connection: &DatabaseConnection,
+    collection_id: i64,
+    share_id: i64,
+) -> anyhow::Result<Option<Share>> {
+    let result = collection_share_query(collection_id)
+        .filter(shares::Column::Id.eq(share_id))
+        .one(connection)
+        .await
+        .context(format!(
+            "Failed to query share {} of collection {}",
+            share_id, collection_id
+        ))?;
+    Ok(result)
+}
+
+#[test]
+fn test_contains_keyword_escapes_wildcards() {
+    use sea_orm::{DbBackend, QueryTrait};
+
+    let filter = ShareFilter {
+        keyword: Some("50%_off\\".to_string()),
+        ..Default::default()
+    };
+    let sql = share_query(&filter).build(DbBackend::Sqlite).to_string();
+    assert!(sql.contains(r"LIKE '%50\%\_off\\%' ESCAPE '\'"), "{sql}");
+}
+
+#[test]
+fn test_public_shares_exclude_pin_protected_collections() {
+    use crate::migrator::Migrator;
+    use sea_orm::Database;
+    use sea_orm_migration::MigratorTrait;
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+
+    let project_dir = env!("CARGO_MANIFEST_DIR");
+    rt.block_on(async {
+        let db = Database::connect(format!(
+            "sqlite:{}/target/debug/test_public_shares.db?mode=rwc",
+            project_dir
+        ))
+        .await
+        .unwrap();
+        Migrator::refresh(&db).await.unwrap();
+
+        for id in 1..=3 {
+            let share: shares::ActiveModel = Share {
+                id,
+                file_name: format!("{id}.txt"),
+                file_path: format!("/tmp/{id}.txt"),
+                mime_type: "text/plain".to_string(),
+                file_size: 0,
+                modified_at: None,
+                sha256: None,
+                status: ShareStatus::Available.to_string(),
+                access_token: generate_access_token(),
+                expires_at: None,
+            }
+            .into();
+            share.insert(&db).await.unwrap();
+        }
+        insert_collection(&db, 1, "Private".to_string(), Some("1234".to_string()))
+            .await
+            .unwrap();
+        insert_collection(&db, 2, "Public".to_string(), None)
+            .await
+            .unwrap();
+        add_shares_to_collection(&db, 1, &[1]).await.unwrap();
+        add_shares_to_collection(&db, 2, &[2]).await.unwrap();
+
+        let filter = ShareFilter {
+            public_only: true,
+            ..Default::default()
+        };
+        let ids: Vec<i64> = get_shares(&db, &filter)
+            .await
+            .unwrap()
+            .iter()
+            .map(|share| share.id)
+            .collect();
+        assert_eq!(vec![2, 3], ids);
+        let (_, total) = get_shares_page(&db, &filter, 0, 10).await.unwrap();
+        assert_eq!(2, total);
+        assert_eq!(
+            3,
+            get_shares(&db, &ShareFilter::default())
+                .await
+                .unwrap()
+                .len()
+        );
+    });
+}
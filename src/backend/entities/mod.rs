@@ -2,6 +2,11 @@
 
 pub mod prelude;
 
+pub mod blocked_requests;
 pub mod chunks;
+pub mod collection_shares;
+pub mod collections;
+pub mod devices;
 pub mod shares;
+pub mod transfers;
 pub mod uploads;
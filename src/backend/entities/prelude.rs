@@ -1,5 +1,10 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.15
 
+pub use super::blocked_requests::Entity as BlockedRequests;
 pub use super::chunks::Entity as Chunks;
+pub use super::collection_shares::Entity as CollectionShares;
+pub use super::collections::Entity as Collections;
+pub use super::devices::Entity as Devices;
 pub use super::shares::Entity as Shares;
+pub use super::transfers::Entity as Transfers;
 pub use super::uploads::Entity as Uploads;
@@ -10,9 +10,34 @@ pub struct Model {
     pub file_name: String,
     pub file_path: String,
     pub mime_type: String,
+    pub file_size: i64,
+    pub modified_at: Option<DateTime>,
+    pub sha256: Option<String>,
+    pub status: String,
+    #[sea_orm(unique)]
+    pub access_token: String,
+    pub expires_at: Option<DateTime>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
-pub enum Relation {}
+pub enum Relation {
+    #[sea_orm(has_many = "super::collection_shares::Entity")]
+    CollectionShares,
+}
+
+impl Related<super::collection_shares::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::CollectionShares.def()
+    }
+}
+
+impl Related<super::collections::Entity> for Entity {
+    fn to() -> RelationDef {
+        super::collection_shares::Relation::Collections.def()
+    }
+    fn via() -> Option<RelationDef> {
+        Some(super::collection_shares::Relation::Shares.def().rev())
+    }
+}
 
 impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,201 @@
+use crate::backend::db::repository::{Share, get_all_shares, update_share};
+use crate::util::read_file_metadata;
+use chrono::NaiveDateTime;
+use futures::channel::mpsc::{UnboundedReceiver, unbounded};
+use gpui::Global;
+use log::{error, info};
+use notify::event::ModifyKind;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use sea_orm::strum::{Display as StrumDisplay, EnumString};
+use sea_orm::{DatabaseConnection, IntoActiveModel, Set};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// 分享的文件的状态
+#[derive(EnumString, StrumDisplay, Debug, PartialEq, Clone, Copy)]
+pub enum ShareStatus {
+    Available,
+    /// 文件被删除或者移动了
+    Missing,
+    /// 文件在添加到分享列表之后被修改过
+    Changed,
+}
+
+/// 根据文件当前的大小和修改时间计算分享的状态，`metadata`为None表示文件不存在
+pub fn get_share_status(share: &Share, metadata: Option<(i64, NaiveDateTime)>) -> ShareStatus {
+    match metadata {
+        None => ShareStatus::Missing,
+        Some((file_size, modified_at))
+            if share.file_size != file_size || share.modified_at != Some(modified_at) =>
+        {
+            ShareStatus::Changed
+        }
+        Some(_) => ShareStatus::Available,
+    }
+}
+
+/// 检查所有分享的文件是否被删除或者修改过，更新分享的状态
+pub async fn check_shares(connection: &DatabaseConnection) -> anyhow::Result<()> {
+    for share in get_all_shares(connection).await? {
+        let metadata = read_file_metadata(&share.file_path).await.ok();
+        // 旧版本添加的分享没有记录文件信息，补充记录即可
+        if share.modified_at.is_none()
+            && let Some((file_size, modified_at)) = metadata
+        {
+            let mut share = share.into_active_model();
+            share.file_size = Set(file_size);
+            share.modified_at = Set(Some(modified_at));
+            share.status = Set(ShareStatus::Available.to_string());
+            update_share(connection, share).await?;
+            continue;
+        }
+
+        let status = get_share_status(&share, metadata);
+        if share.status != status.to_string() {
+            info!("Share {:?} is {}", share.file_path, status);
+            let mut share = share.into_active_model();
+            share.status = Set(status.to_string());
+            update_share(connection, share).await?;
+        }
+    }
+    Ok(())
+}
+
+/// 监听所有分享的文件，文件被删除、移动或者修改时发出通知。
+/// 监听的是文件所在的目录，这样文件被删除后重新创建也能收到通知。
+/// 所在的目录本身被删除或者重命名时同样会发出通知，但是更上层的目录被重命名时收不到通知，
+/// 只能等到下次启动时检查。
+pub struct ShareWatcher {
+    watcher: Mutex<RecommendedWatcher>,
+    watched_paths: Arc<Mutex<WatchedPaths>>,
+    watched_dirs: Mutex<HashSet<PathBuf>>,
+}
+
+/// 监听回调中需要用到的路径
+#[derive(Default)]
+struct WatchedPaths {
+    files: HashSet<PathBuf>,
+    dirs: HashSet<PathBuf>,
+    /// 被删除或者重命名的目录，监听已经失效，需要重新监听
+    lost_dirs: HashSet<PathBuf>,
+}
+
+impl ShareWatcher {
+    /// 返回监听器以及接收文件变化通知的通道
+    pub fn new() -> anyhow::Result<(Self, UnboundedReceiver<()>)> {
+        let (sender, receiver) = unbounded();
+        let watched_paths: Arc<Mutex<WatchedPaths>> = Arc::default();
+        let paths = watched_paths.clone();
+        let watcher = notify::recommended_watcher(move |result: notify::Result<Event>| {
+            let event = match result {
+                Ok(event) => event,
+                Err(e) => {
+                    error!("Failed to watch shared files, {e}");
+                    return;
+                }
+            };
+            // 下载文件时会产生访问事件，忽略
+            if matches!(event.kind, EventKind::Access(_)) {
+                return;
+            }
+            let mut paths = paths.lock().unwrap();
+            let dir_lost = matches!(
+                event.kind,
+                EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(_))
+            );
+            let mut changed = false;
+            for path in &event.paths {
+                if paths.files.contains(path) {
+                    changed = true;
+                } else if dir_lost && paths.dirs.contains(path) {
+                    paths.lost_dirs.insert(path.clone());
+                    changed = true;
+                }
+            }
+            if changed {
+                let _ = sender.unbounded_send(());
+            }
+        })?;
+        Ok((
+            Self {
+                watcher: Mutex::new(watcher),
+                watched_paths,
+                watched_dirs: Mutex::default(),
+            },
+            receiver,
+        ))
+    }
+
+    /// 更新需要监听的文件列表
+    pub fn watch<'a>(&self, file_paths: impl IntoIterator<Item = &'a Path>) {
+        let files: HashSet<PathBuf> = file_paths
+            .into_iter()
+            .map(|path| path.to_path_buf())
+            .collect();
+        let dirs: HashSet<PathBuf> = files
+            .iter()
+            .filter_map(|path| path.parent())
+            .map(|dir| dir.to_path_buf())
+            .collect();
+        let lost_dirs = {
+            let mut paths = self.watched_paths.lock().unwrap();
+            paths.files = files;
+            paths.dirs = dirs.clone();
+            std::mem::take(&mut paths.lost_dirs)
+        };
+
+        let mut watcher = self.watcher.lock().unwrap();
+        let mut watched_dirs = self.watched_dirs.lock().unwrap();
+        for dir in &lost_dirs {
+            let _ = watcher.unwatch(dir);
+            watched_dirs.remove(dir);
+        }
+        for dir in watched_dirs.difference(&dirs) {
+            if let Err(e) = watcher.unwatch(dir) {
+                error!("Failed to unwatch {:?}, {e}", dir);
+            }
+        }
+        // 目录不存在时监听会失败，下次更新时再重试
+        watched_dirs.retain(|dir| dirs.contains(dir));
+        for dir in dirs {
+            if watched_dirs.contains(&dir) {
+                continue;
+            }
+            match watcher.watch(&dir, RecursiveMode::NonRecursive) {
+                Ok(_) => {
+                    watched_dirs.insert(dir);
+                }
+                Err(e) => error!("Failed to watch {:?}, {e}", dir),
+            }
+        }
+    }
+}
+
+impl Global for ShareWatcher {}
+
+#[test]
+fn test_get_share_status() {
+    let modified_at = chrono::Local::now().naive_local();
+    let share = Share {
+        id: 1,
+        file_name: "a.txt".to_string(),
+        file_path: "/tmp/a.txt".to_string(),
+        mime_type: "text/plain".to_string(),
+        file_size: 42,
+        modified_at: Some(modified_at),
+        sha256: None,
+        status: ShareStatus::Available.to_string(),
+        access_token: "0123456789abcdef0123456789abcdef".to_string(),
+        expires_at: None,
+    };
+    assert_eq!(ShareStatus::Missing, get_share_status(&share, None));
+    assert_eq!(
+        ShareStatus::Available,
+        get_share_status(&share, Some((42, modified_at)))
+    );
+    assert_eq!(
+        ShareStatus::Changed,
+        get_share_status(&share, Some((43, modified_at)))
+    );
+}
@@ -1,2 +1,7 @@
+pub mod access_control;
+pub mod error;
 pub mod handlers;
+pub mod openapi;
 pub mod server;
+pub mod throttle;
+pub mod transfers;
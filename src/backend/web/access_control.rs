@@ -0,0 +1,393 @@
+use crate::backend::db::repository::{BlockedRequest, insert_blocked_request};
+use crate::backend::web::error::{ApiError, ErrorCode};
+use crate::setting::Settings;
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::{Error, ResponseError, web};
+use anyhow::anyhow;
+use chrono::Local;
+use log::{error, warn};
+use sea_orm::DatabaseConnection;
+use sea_orm::strum::{Display as StrumDisplay, EnumString};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::net::IpAddr;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(60);
+
+/// 限流表超过该大小时清理过期的记录
+const RATE_LIMITER_CLEANUP_THRESHOLD: usize = 1024;
+
+/// CIDR表示的网段，例如 `192.168.1.0/24`、`fe80::/10`，不带前缀长度时表示单个地址
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IpNetwork {
+    addr: IpAddr,
+    prefix_len: u8,
+}
+
+impl IpNetwork {
+    /// 只包含指定地址的网段
+    pub fn host(addr: IpAddr) -> Self {
+        let addr = addr.to_canonical();
+        let prefix_len = match addr {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+        Self { addr, prefix_len }
+    }
+
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        match (self.addr, addr.to_canonical()) {
+            (IpAddr::V4(network), IpAddr::V4(addr)) => {
+                let mask = u32::MAX
+                    .checked_shl(32 - self.prefix_len as u32)
+                    .unwrap_or(0);
+                u32::from(network) & mask == u32::from(addr) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(addr)) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - self.prefix_len as u32)
+                    .unwrap_or(0);
+                u128::from(network) & mask == u128::from(addr) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for IpNetwork {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (addr, prefix_len) = match s.split_once('/') {
+            Some((addr, prefix_len)) => (addr, Some(prefix_len)),
+            None => (s, None),
+        };
+        let addr = addr
+            .parse::<IpAddr>()
+            .map_err(|e| anyhow!("Invalid address {addr}, {e}"))?;
+        let network = Self::host(addr);
+        match prefix_len {
+            Some(prefix_len) => {
+                let prefix_len = prefix_len
+                    .parse::<u8>()
+                    .map_err(|e| anyhow!("Invalid prefix length {prefix_len}, {e}"))?;
+                if prefix_len > network.prefix_len {
+                    return Err(anyhow!(
+                        "Prefix length {prefix_len} is too large for {addr}"
+                    ));
+                }
+                Ok(Self {
+                    addr: network.addr,
+                    prefix_len,
+                })
+            }
+            None => Ok(network),
+        }
+    }
+}
+
+impl Display for IpNetwork {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix_len)
+    }
+}
+
+/// 请求被拦截的原因
+#[derive(EnumString, StrumDisplay, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum BlockReason {
+    /// 命中了黑名单
+    Denied,
+    /// 设置了白名单，但是不在白名单中
+    NotAllowed,
+    /// 请求过于频繁
+    RateLimited,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum AccessDecision {
+    Allowed,
+    /// `record`为false时表示同一个限流周期内已经记录过，不需要再次记录
+    Blocked {
+        reason: BlockReason,
+        record: bool,
+    },
+}
+
+#[derive(Default)]
+struct AccessRules {
+    allow_list: Vec<IpNetwork>,
+    deny_list: Vec<IpNetwork>,
+    /// 每个客户端每分钟最多的请求数，0表示不限制
+    max_requests_per_minute: u32,
+}
+
+struct RateWindow {
+    started_at: Instant,
+    requests: u32,
+    reported: bool,
+}
+
+/// 按客户端地址进行访问控制，规则可以在服务器运行时重新加载
+#[derive(Default)]
+pub struct AccessControl {
+    rules: RwLock<AccessRules>,
+    rate_limiter: Mutex<HashMap<IpAddr, RateWindow>>,
+    /// 黑白名单拦截的请求上次被记录的时间，同一个客户端每个周期只记录一次
+    reported: Mutex<HashMap<(IpAddr, BlockReason), Instant>>,
+}
+
+/// 解析网段列表，忽略无效的条目
+pub fn parse_ip_networks(networks: &[String]) -> Vec<IpNetwork> {
+    networks
+        .iter()
+        .filter_map(|network| match IpNetwork::from_str(network) {
+            Ok(network) => Some(network),
+            Err(e) => {
+                warn!("Ignore invalid network {network}, {e}");
+                None
+            }
+        })
+        .collect()
+}
+
+impl AccessControl {
+    pub fn reload(&self, settings: &Settings) {
+        self.set_rules(AccessRules {
+            allow_list: parse_ip_networks(&settings.allow_list),
+            deny_list: parse_ip_networks(&settings.deny_list),
+            max_requests_per_minute: settings.max_requests_per_minute,
+        });
+    }
+
+    fn set_rules(&self, rules: AccessRules) {
+        *self.rules.write().unwrap() = rules;
+        self.rate_limiter.lock().unwrap().clear();
+        self.reported.lock().unwrap().clear();
+    }
+
+    /// 黑白名单拦截的请求不经过限流，同样按周期去重，避免被拦截的客户端刷满数据库和日志
+    fn blocked(&self, addr: IpAddr, reason: BlockReason) -> AccessDecision {
+        let now = Instant::now();
+        let mut reported = self.reported.lock().unwrap();
+        if reported.len() > RATE_LIMITER_CLEANUP_THRESHOLD {
+            reported.retain(|_, reported_at| now - *reported_at < RATE_LIMIT_WINDOW);
+        }
+        let record = match reported.get(&(addr, reason)) {
+            Some(reported_at) => now - *reported_at >= RATE_LIMIT_WINDOW,
+            None => true,
+        };
+        if record {
+            reported.insert((addr, reason), now);
+        }
+        AccessDecision::Blocked { reason, record }
+    }
+
+    /// `rate_limited`为false时只检查黑白名单，请求不计入限流
+    pub fn check(&self, addr: IpAddr, rate_limited: bool) -> AccessDecision {
+        let addr = addr.to_canonical();
+        let max_requests_per_minute = {
+            let rules = self.rules.read().unwrap();
+            if rules.deny_list.iter().any(|network| network.contains(addr)) {
+                return self.blocked(addr, BlockReason::Denied);
+            }
+            if !rules.allow_list.is_empty()
+                && !rules
+                    .allow_list
+                    .iter()
+                    .any(|network| network.contains(addr))
+            {
+                return self.blocked(addr, BlockReason::NotAllowed);
+            }
+            rules.max_requests_per_minute
+        };
+        if !rate_limited || max_requests_per_minute == 0 {
+            return AccessDecision::Allowed;
+        }
+
+        let now = Instant::now();
+        let mut rate_limiter = self.rate_limiter.lock().unwrap();
+        if rate_limiter.len() > RATE_LIMITER_CLEANUP_THRESHOLD {
+            rate_limiter.retain(|_, window| now - window.started_at < RATE_LIMIT_WINDOW);
+        }
+        let window = rate_limiter.entry(addr).or_insert(RateWindow {
+            started_at: now,
+            requests: 0,
+            reported: false,
+        });
+        if now - window.started_at >= RATE_LIMIT_WINDOW {
+            *window = RateWindow {
+                started_at: now,
+                requests: 0,
+                reported: false,
+            };
+        }
+        window.requests += 1;
+        if window.requests <= max_requests_per_minute {
+            return AccessDecision::Allowed;
+        }
+
+        let record = !window.reported;
+        window.reported = true;
+        AccessDecision::Blocked {
+            reason: BlockReason::RateLimited,
+            record,
+        }
+    }
+}
+
+/// 网页端的静态资源不计入限流，否则打开一次页面就会消耗大量的请求次数。
+/// 挂载在根路径下的旧版本接口别名和`/api`下的接口一样计入限流
+fn is_rate_limited(path: &str) -> bool {
+    !path.starts_with("/web/")
+}
+
+/// 访问控制中间件，拦截黑名单、白名单之外以及请求接口过于频繁的客户端
+pub async fn access_control_middleware<B: MessageBody>(
+    req: ServiceRequest,
+    next: Next<B>,
+) -> Result<ServiceResponse<EitherBody<B>>, Error> {
+    let access_control = req.app_data::<web::Data<Arc<AccessControl>>>().cloned();
+    let (Some(access_control), Some(peer_addr)) = (access_control, req.peer_addr()) else {
+        return Ok(next.call(req).await?.map_into_left_body());
+    };
+
+    let client_ip = peer_addr.ip().to_canonical();
+    match access_control.check(client_ip, is_rate_limited(req.path())) {
+        AccessDecision::Allowed => Ok(next.call(req).await?.map_into_left_body()),
+        AccessDecision::Blocked { reason, record } => {
+            if record {
+                warn!(
+                    "Blocked request from {}: {} {}, reason: {}",
+                    client_ip,
+                    req.method(),
+                    req.path(),
+                    reason
+                );
+                record_blocked_request(&req, client_ip, reason);
+            }
+            let response = match reason {
+                BlockReason::RateLimited => {
+                    ApiError::new(ErrorCode::TooManyRequests, "Too many requests").error_response()
+                }
+                BlockReason::Denied | BlockReason::NotAllowed => {
+                    ApiError::new(ErrorCode::Forbidden, "Access denied").error_response()
+                }
+            };
+            Ok(req.into_response(response).map_into_right_body())
+        }
+    }
+}
+
+/// 异步保存被拦截的请求，不阻塞响应
+fn record_blocked_request(req: &ServiceRequest, client_ip: IpAddr, reason: BlockReason) {
+    let Some(connection) = req.app_data::<web::Data<DatabaseConnection>>().cloned() else {
+        return;
+    };
+    let blocked_request = BlockedRequest {
+        id: 0,
+        client_ip: client_ip.to_string(),
+        path: req.path().to_string(),
+        reason: reason.to_string(),
+        created_at: Local::now().naive_local(),
+    };
+    actix_web::rt::spawn(async move {
+        if let Err(e) = insert_blocked_request(connection.get_ref(), blocked_request).await {
+            error!("Failed to record blocked request, {e}");
+        }
+    });
+}
+
+#[test]
+fn test_ip_network_contains() {
+    let network = IpNetwork::from_str("192.168.1.0/24").unwrap();
+    assert!(network.contains("192.168.1.42".parse().unwrap()));
+    assert!(!network.contains("192.168.2.1".parse().unwrap()));
+    // IPv4 clients connected to a dual stack socket show up as IPv4-mapped IPv6 addresses
+    assert!(network.contains("::ffff:192.168.1.42".parse().unwrap()));
+
+    let host = IpNetwork::from_str("10.0.0.5").unwrap();
+    assert!(host.contains("10.0.0.5".parse().unwrap()));
+    assert!(!host.contains("10.0.0.6".parse().unwrap()));
+
+    let all = IpNetwork::from_str("0.0.0.0/0").unwrap();
+    assert!(all.contains("8.8.8.8".parse().unwrap()));
+
+    let link_local = IpNetwork::from_str("fe80::/10").unwrap();
+    assert!(link_local.contains("fe80::1".parse().unwrap()));
+    assert!(!link_local.contains("2001:db8::1".parse().unwrap()));
+
+    assert!(IpNetwork::from_str("10.0.0.0/33").is_err());
+    assert!(IpNetwork::from_str("foo").is_err());
+}
+
+#[test]
+fn test_access_control_check() {
+    let access_control = AccessControl::default();
+    access_control.set_rules(AccessRules {
+        allow_list: parse_ip_networks(&["192.168.1.0/24".to_string()]),
+        deny_list: parse_ip_networks(&["192.168.1.13".to_string()]),
+        max_requests_per_minute: 2,
+    });
+
+    let client = "192.168.1.2".parse().unwrap();
+    assert_eq!(AccessDecision::Allowed, access_control.check(client, true));
+    assert_eq!(AccessDecision::Allowed, access_control.check(client, true));
+    assert_eq!(
+        AccessDecision::Blocked {
+            reason: BlockReason::RateLimited,
+            record: true
+        },
+        access_control.check(client, true)
+    );
+    assert_eq!(
+        AccessDecision::Blocked {
+            reason: BlockReason::RateLimited,
+            record: false
+        },
+        access_control.check(client, true)
+    );
+    assert_eq!(AccessDecision::Allowed, access_control.check(client, false));
+    assert_eq!(
+        AccessDecision::Blocked {
+            reason: BlockReason::Denied,
+            record: true
+        },
+        access_control.check("192.168.1.13".parse().unwrap(), false)
+    );
+    assert_eq!(
+        AccessDecision::Blocked {
+            reason: BlockReason::Denied,
+            record: false
+        },
+        access_control.check("192.168.1.13".parse().unwrap(), false)
+    );
+    assert_eq!(
+        AccessDecision::Blocked {
+            reason: BlockReason::NotAllowed,
+            record: true
+        },
+        access_control.check("10.0.0.1".parse().unwrap(), true)
+    );
+    assert_eq!(
+        AccessDecision::Blocked {
+            reason: BlockReason::NotAllowed,
+            record: false
+        },
+        access_control.check("10.0.0.1".parse().unwrap(), true)
+    );
+}
+
+#[test]
+fn test_is_rate_limited() {
+    assert!(is_rate_limited("/api/v1/shares"));
+    assert!(is_rate_limited("/api/shares"));
+    assert!(is_rate_limited("/stream/abc"));
+    assert!(!is_rate_limited("/web/index.html"));
+    assert!(!is_rate_limited("/web/assets/app.js"));
+}
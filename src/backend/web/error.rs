@@ -0,0 +1,163 @@
+use actix_multipart::form::MultipartFormConfig;
+use actix_web::error::BlockingError;
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, ResponseError, web};
+use log::error;
+use sea_orm::strum::Display as StrumDisplay;
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt::{Display, Formatter};
+use utoipa::ToSchema;
+
+/// 错误码，客户端根据错误码而不是错误信息判断错误类型
+#[derive(StrumDisplay, Serialize, ToSchema, Debug, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum ErrorCode {
+    /// 请求参数错误
+    InvalidRequest,
+    NotFound,
+    /// 访问分享集合需要PIN，或者PIN不正确
+    PinRequired,
+    /// 客户端被黑名单或者白名单拦截
+    Forbidden,
+    /// 客户端请求过于频繁
+    TooManyRequests,
+    /// 文件已经上传完成
+    UploadCompleted,
+    /// 分片的哈希值校验失败
+    ChunkHashMismatch,
+    InternalError,
+}
+
+impl ErrorCode {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ErrorCode::InvalidRequest
+            | ErrorCode::UploadCompleted
+            | ErrorCode::ChunkHashMismatch => StatusCode::BAD_REQUEST,
+            ErrorCode::NotFound => StatusCode::NOT_FOUND,
+            ErrorCode::PinRequired => StatusCode::UNAUTHORIZED,
+            ErrorCode::Forbidden => StatusCode::FORBIDDEN,
+            ErrorCode::TooManyRequests => StatusCode::TOO_MANY_REQUESTS,
+            ErrorCode::InternalError => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// 所有接口统一的错误响应，序列化为`{"code": ..., "message": ..., "details": ...}`
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiError {
+    pub code: ErrorCode,
+    pub message: String,
+    /// 错误的详细信息，例如anyhow错误的原因链
+    #[schema(value_type = Option<Object>)]
+    pub details: Option<Value>,
+}
+
+pub type ApiResult<T> = Result<T, ApiError>;
+
+impl ApiError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    pub fn invalid_request(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::InvalidRequest, message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::NotFound, message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::InternalError, message)
+    }
+
+    pub fn with_details(mut self, details: Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+}
+
+impl Display for ApiError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.code, self.message)
+    }
+}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        self.code.status_code()
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        if self.code == ErrorCode::InternalError {
+            error!("{}, details: {:?}", self, self.details);
+        }
+        HttpResponse::build(self.status_code()).json(self)
+    }
+}
+
+/// anyhow错误作为服务器内部错误，最外层的上下文作为错误信息，原因链作为详细信息
+impl From<anyhow::Error> for ApiError {
+    fn from(e: anyhow::Error) -> Self {
+        let causes: Vec<Value> = e
+            .chain()
+            .skip(1)
+            .map(|cause| Value::String(cause.to_string()))
+            .collect();
+        let error = ApiError::internal(e.to_string());
+        if causes.is_empty() {
+            error
+        } else {
+            error.with_details(Value::Array(causes))
+        }
+    }
+}
+
+impl From<BlockingError> for ApiError {
+    fn from(e: BlockingError) -> Self {
+        ApiError::internal(e.to_string())
+    }
+}
+
+/// 请求体、查询参数以及路径参数解析失败时也返回统一的错误格式
+pub fn configure_extractors(cfg: &mut web::ServiceConfig) {
+    cfg.app_data(
+        web::JsonConfig::default()
+            .error_handler(|e, _req| ApiError::invalid_request(e.to_string()).into()),
+    )
+    .app_data(
+        web::QueryConfig::default()
+            .error_handler(|e, _req| ApiError::invalid_request(e.to_string()).into()),
+    )
+    .app_data(
+        web::PathConfig::default()
+            .error_handler(|e, _req| ApiError::invalid_request(e.to_string()).into()),
+    )
+    .app_data(
+        MultipartFormConfig::default()
+            .error_handler(|e, _req| ApiError::invalid_request(e.to_string()).into()),
+    );
+}
+
+#[test]
+fn test_api_error_from_anyhow() {
+    use anyhow::Context;
+
+    let e = std::fs::read("/nonexistent/file")
+        .context("Failed to read chunk")
+        .unwrap_err();
+    let api_error = ApiError::from(e);
+    assert_eq!(ErrorCode::InternalError, api_error.code);
+    assert_eq!("Failed to read chunk", api_error.message);
+
+    let json = serde_json::to_value(&api_error).unwrap();
+    assert_eq!("internal_error", json["code"]);
+    assert!(json["details"].is_array());
+}
@@ -1,2 +1,7 @@
+pub mod collections;
+pub mod devices;
 pub mod downloads;
+pub mod previews;
+pub mod shares;
+pub mod thumbnails;
 pub mod uploads;
@@ -0,0 +1,347 @@
+use crate::backend::db::repository::{
+    Collection, get_collection_by_token, get_collection_share, get_collection_shares,
+};
+use crate::backend::web::error::{ApiError, ApiResult, ErrorCode};
+use crate::backend::web::handlers::downloads::{DownloadOption, stream_share};
+use crate::backend::web::handlers::shares::ShareItem;
+use crate::backend::web::throttle::BandwidthLimiter;
+use crate::util::verify_pin_hash;
+use actix_web::{HttpRequest, HttpResponse, Responder, get, web};
+use log::{info, warn};
+use sea_orm::DatabaseConnection;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use utoipa::ToSchema;
+
+/// 网页端通过请求头携带分享集合的PIN
+pub const COLLECTION_PIN_HEADER: &str = "X-Collection-Pin";
+
+/// 网页端同时把PIN保存在cookie中，下载等无法设置请求头的请求使用
+pub fn collection_pin_cookie(collection_id: i64) -> String {
+    format!("share_rs_collection_pin_{collection_id}")
+}
+
+/// 从请求头或者cookie中获取分享集合的PIN
+fn get_collection_pin(req: &HttpRequest, collection_id: i64) -> Option<String> {
+    req.headers()
+        .get(COLLECTION_PIN_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string())
+        .or_else(|| {
+            req.cookie(&collection_pin_cookie(collection_id))
+                .map(|cookie| cookie.value().to_string())
+        })
+}
+
+/// 没有设置PIN的集合不需要校验，数据库中保存的是PIN的哈希值
+fn verify_pin(collection: &Collection, pin: Option<&str>) -> bool {
+    match (collection.pin.as_deref(), pin) {
+        (None, _) => true,
+        (Some(pin_hash), Some(pin)) => verify_pin_hash(pin, pin_hash),
+        (Some(_), None) => false,
+    }
+}
+
+/// 连续输错PIN的次数达到该值后，暂时禁止客户端访问该集合
+const MAX_PIN_FAILURES: u32 = 5;
+
+/// 输错PIN的次数在该时间内累计，达到上限后禁止访问同样长的时间
+const PIN_LOCKOUT: Duration = Duration::from_secs(5 * 60);
+
+/// 记录表超过该大小时清理过期的记录
+const PIN_LIMITER_CLEANUP_THRESHOLD: usize = 1024;
+
+struct PinFailures {
+    count: u32,
+    last_failed_at: Instant,
+    locked_until: Option<Instant>,
+}
+
+/// 按客户端地址和分享集合记录输错PIN的次数，防止暴力破解PIN
+#[derive(Default)]
+pub struct PinLimiter {
+    failures: Mutex<HashMap<(IpAddr, i64), PinFailures>>,
+}
+
+impl PinLimiter {
+    /// 客户端被禁止访问集合时返回剩余的时间
+    fn locked(&self, addr: IpAddr, collection_id: i64, now: Instant) -> Option<Duration> {
+        let failures = self.failures.lock().unwrap();
+        let locked_until = failures.get(&(addr, collection_id))?.locked_until?;
+        (locked_until > now).then(|| locked_until - now)
+    }
+
+    fn record_failure(&self, addr: IpAddr, collection_id: i64, now: Instant) {
+        let mut failures = self.failures.lock().unwrap();
+        if failures.len() > PIN_LIMITER_CLEANUP_THRESHOLD {
+            failures.retain(|_, failure| {
+                now - failure.last_failed_at < PIN_LOCKOUT
+                    || failure.locked_until.is_some_and(|until| until > now)
+            });
+        }
+        let failure = failures
+            .entry((addr, collection_id))
+            .or_insert(PinFailures {
+                count: 0,
+                last_failed_at: now,
+                locked_until: None,
+            });
+        if now - failure.last_failed_at >= PIN_LOCKOUT {
+            failure.count = 0;
+        }
+        failure.count += 1;
+        failure.last_failed_at = now;
+        if failure.count >= MAX_PIN_FAILURES {
+            failure.count = 0;
+            failure.locked_until = Some(now + PIN_LOCKOUT);
+        }
+    }
+
+    fn reset(&self, addr: IpAddr, collection_id: i64) {
+        self.failures.lock().unwrap().remove(&(addr, collection_id));
+    }
+}
+
+/// 获取分享集合并校验请求携带的PIN，输错PIN的次数过多时暂时拒绝该客户端的请求
+async fn find_collection(
+    req: &HttpRequest,
+    connection: &DatabaseConnection,
+    pin_limiter: &PinLimiter,
+    access_token: &str,
+) -> ApiResult<Collection> {
+    let collection = get_collection_by_token(connection, access_token)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Collection not found"))?;
+    if collection.pin.is_none() {
+        return Ok(collection);
+    }
+
+    let client_ip = req.peer_addr().map(|addr| addr.ip().to_canonical());
+    let now = Instant::now();
+    if let Some(client_ip) = client_ip
+        && let Some(remaining) = pin_limiter.locked(client_ip, collection.id, now)
+    {
+        return Err(ApiError::new(
+            ErrorCode::TooManyRequests,
+            format!(
+                "Too many wrong PINs, try again in {} seconds",
+                remaining.as_secs() + 1
+            ),
+        ));
+    }
+
+    let pin = get_collection_pin(req, collection.id);
+    let pin_provided = pin.is_some();
+    // 计算PIN的哈希值比较耗时，放到线程池中执行
+    let (collection, verified) = web::block(move || {
+        let verified = verify_pin(&collection, pin.as_deref());
+        (collection, verified)
+    })
+    .await?;
+    if verified {
+        if let Some(client_ip) = client_ip {
+            pin_limiter.reset(client_ip, collection.id);
+        }
+        return Ok(collection);
+    }
+    if !pin_provided {
+        return Err(ApiError::new(ErrorCode::PinRequired, "PIN required"));
+    }
+    if let Some(client_ip) = client_ip {
+        warn!(
+            "Wrong PIN of collection {} from {}",
+            collection.id, client_ip
+        );
+        pin_limiter.record_failure(client_ip, collection.id, now);
+    }
+    Err(ApiError::new(ErrorCode::PinRequired, "Invalid PIN"))
+}
+
+/// 分享集合的基本信息，不需要PIN即可获取
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CollectionInfo {
+    /// 网页端按集合id把PIN保存在cookie中
+    id: String,
+    name: String,
+    /// 为true时需要PIN才能获取集合中的分享项
+    pin_required: bool,
+}
+
+/// 获取分享集合的名称以及是否需要PIN
+#[utoipa::path(
+    get,
+    path = "/collections/{access_token}",
+    tag = "collections",
+    params(("access_token" = String, Path, description = "分享集合的访问令牌")),
+    responses(
+        (status = 200, description = "分享集合的基本信息", body = CollectionInfo),
+        (status = 404, description = "分享集合不存在", body = ApiError),
+    )
+)]
+#[get("/collections/{access_token}")]
+pub async fn get_collection_info(
+    path: web::Path<String>,
+    connection: web::Data<DatabaseConnection>,
+) -> ApiResult<impl Responder> {
+    let access_token = path.into_inner();
+    info!("Accept request: GET /collections/{}", access_token);
+
+    let collection = get_collection_by_token(connection.get_ref(), &access_token)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Collection not found"))?;
+    Ok(web::Json(CollectionInfo {
+        id: collection.id.to_string(),
+        name: collection.name,
+        pin_required: collection.pin.is_some(),
+    }))
+}
+
+/// 获取分享集合中的分享项，集合设置了PIN时需要通过请求头或者cookie携带PIN
+#[utoipa::path(
+    get,
+    path = "/collections/{access_token}/shares",
+    tag = "collections",
+    params(
+        ("access_token" = String, Path, description = "分享集合的访问令牌"),
+        ("X-Collection-Pin" = Option<String>, Header, description = "分享集合的PIN"),
+    ),
+    responses(
+        (status = 200, description = "集合中的分享项", body = Vec<ShareItem>),
+        (status = 401, description = "缺少PIN或者PIN不正确", body = ApiError),
+        (status = 404, description = "分享集合不存在", body = ApiError),
+        (status = 429, description = "输错PIN的次数过多，暂时禁止访问", body = ApiError),
+    )
+)]
+#[get("/collections/{access_token}/shares")]
+pub async fn list_collection_shares(
+    req: HttpRequest,
+    path: web::Path<String>,
+    connection: web::Data<DatabaseConnection>,
+    pin_limiter: web::Data<PinLimiter>,
+) -> ApiResult<impl Responder> {
+    let access_token = path.into_inner();
+    info!("Accept request: GET /collections/{}/shares", access_token);
+
+    let collection = find_collection(
+        &req,
+        connection.get_ref(),
+        pin_limiter.get_ref(),
+        &access_token,
+    )
+    .await?;
+    let share_list = get_collection_shares(connection.get_ref(), collection.id).await?;
+    let share_list: Vec<ShareItem> = share_list.iter().map(ShareItem::from).collect();
+    Ok(web::Json(share_list))
+}
+
+/// 下载分享集合中的文件，只能下载集合中的分享项
+#[utoipa::path(
+    get,
+    path = "/collections/{access_token}/stream/{share_id}",
+    tag = "collections",
+    params(
+        ("access_token" = String, Path, description = "分享集合的访问令牌"),
+        ("share_id" = i64, Path, description = "分享项id"),
+        ("X-Collection-Pin" = Option<String>, Header, description = "分享集合的PIN"),
+        DownloadOption,
+    ),
+    responses(
+        (status = 200, description = "文件内容"),
+        (status = 206, description = "范围请求的部分文件内容"),
+        (status = 401, description = "缺少PIN或者PIN不正确", body = ApiError),
+        (status = 404, description = "分享集合、分享项或者文件不存在", body = ApiError),
+        (status = 429, description = "输错PIN的次数过多，暂时禁止访问", body = ApiError),
+    )
+)]
+#[get("/collections/{access_token}/stream/{share_id}")]
+pub async fn stream_collection_download(
+    req: HttpRequest,
+    path: web::Path<(String, i64)>,
+    connection: web::Data<DatabaseConnection>,
+    bandwidth_limiter: web::Data<Arc<BandwidthLimiter>>,
+    pin_limiter: web::Data<PinLimiter>,
+    query: web::Query<DownloadOption>,
+) -> ApiResult<HttpResponse> {
+    let (access_token, share_id) = path.into_inner();
+    let collection = find_collection(
+        &req,
+        connection.get_ref(),
+        pin_limiter.get_ref(),
+        &access_token,
+    )
+    .await?;
+    let share = get_collection_share(connection.get_ref(), collection.id, share_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("File not found"))?;
+    stream_share(
+        &req,
+        share,
+        connection.get_ref(),
+        bandwidth_limiter.get_ref().clone(),
+        query.force_download,
+    )
+    .await
+}
+
+#[test]
+fn test_verify_pin() {
+    let mut collection = Collection {
+        id: 1,
+        name: "Trip photos".to_string(),
+        pin: None,
+        created_at: chrono::Local::now().naive_local(),
+        access_token: "0123456789abcdef0123456789abcdef".to_string(),
+    };
+    assert!(verify_pin(&collection, None));
+    assert!(verify_pin(&collection, Some("1234")));
+
+    // `hash_pin`使用的迭代次数在调试构建中太慢，这里使用迭代1000次的哈希值
+    collection.pin = Some(
+        "pbkdf2-sha256$1000$00112233445566778899aabbccddeeff$\
+         181d3d346b16a08b15822de7be8972c1c68da2b1d6d1a7589d937321c25f0f29"
+            .to_string(),
+    );
+    assert!(verify_pin(&collection, Some("1234")));
+    assert!(!verify_pin(&collection, Some("4321")));
+    assert!(!verify_pin(&collection, None));
+}
+
+#[test]
+fn test_pin_limiter_lockout() {
+    let pin_limiter = PinLimiter::default();
+    let client: IpAddr = "192.168.1.2".parse().unwrap();
+    let other_client: IpAddr = "192.168.1.3".parse().unwrap();
+    let now = Instant::now();
+
+    for _ in 1..MAX_PIN_FAILURES {
+        pin_limiter.record_failure(client, 1, now);
+    }
+    assert_eq!(None, pin_limiter.locked(client, 1, now));
+    pin_limiter.record_failure(client, 1, now);
+    assert_eq!(Some(PIN_LOCKOUT), pin_limiter.locked(client, 1, now));
+    // 只禁止该客户端访问该集合
+    assert_eq!(None, pin_limiter.locked(client, 2, now));
+    assert_eq!(None, pin_limiter.locked(other_client, 1, now));
+    assert_eq!(None, pin_limiter.locked(client, 1, now + PIN_LOCKOUT));
+
+    // 间隔较长的失败不会累计
+    let later = now + PIN_LOCKOUT;
+    for i in 0..MAX_PIN_FAILURES {
+        pin_limiter.record_failure(other_client, 1, later + PIN_LOCKOUT * i);
+    }
+    assert_eq!(
+        None,
+        pin_limiter.locked(other_client, 1, later + PIN_LOCKOUT * MAX_PIN_FAILURES)
+    );
+
+    // 输入正确的PIN之后重新计数
+    for _ in 1..MAX_PIN_FAILURES {
+        pin_limiter.record_failure(other_client, 2, now);
+    }
+    pin_limiter.reset(other_client, 2);
+    pin_limiter.record_failure(other_client, 2, now);
+    assert_eq!(None, pin_limiter.locked(other_client, 2, now));
+}
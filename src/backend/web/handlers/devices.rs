@@ -0,0 +1,113 @@
+use crate::backend::db::repository::{Device, save_device};
+use crate::backend::web::error::{ApiError, ApiResult};
+use actix_web::http::header;
+use actix_web::{HttpRequest, post, web};
+use chrono::Local;
+use log::info;
+use sea_orm::DatabaseConnection;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// 网页端通过请求头携带设备id，上传请求使用
+pub const DEVICE_ID_HEADER: &str = "X-Device-Id";
+
+/// 网页端同时把设备id保存在cookie中，下载等无法设置请求头的请求使用
+pub const DEVICE_ID_COOKIE: &str = "share_rs_device_id";
+
+const MAX_DEVICE_ID_LENGTH: usize = 64;
+const MAX_DEVICE_NAME_LENGTH: usize = 64;
+
+/// 设备id由网页端随机生成，只允许字母、数字和`-`
+fn is_valid_device_id(device_id: &str) -> bool {
+    !device_id.is_empty()
+        && device_id.len() <= MAX_DEVICE_ID_LENGTH
+        && device_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+/// 从请求头或者cookie中获取设备id
+pub fn get_device_id(req: &HttpRequest) -> Option<String> {
+    let device_id = req
+        .headers()
+        .get(DEVICE_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string())
+        .or_else(|| {
+            req.cookie(DEVICE_ID_COOKIE)
+                .map(|cookie| cookie.value().to_string())
+        })?;
+    is_valid_device_id(&device_id).then_some(device_id)
+}
+
+/// 注册设备请求结构
+#[derive(Deserialize, ToSchema)]
+pub struct RegisterDeviceRequest {
+    device_id: String,
+    name: String,
+}
+
+/// 注册设备响应结构
+#[derive(Serialize, ToSchema)]
+pub struct RegisterDeviceResponse {
+    device_id: String,
+    name: String,
+}
+
+/// 注册设备或者修改设备名称端点
+#[utoipa::path(
+    post,
+    path = "/devices/register",
+    tag = "devices",
+    request_body = RegisterDeviceRequest,
+    responses(
+        (status = 200, description = "注册成功", body = RegisterDeviceResponse),
+        (status = 400, description = "设备id或者设备名称不合法", body = ApiError),
+    )
+)]
+#[post("/devices/register")]
+pub async fn register_device(
+    req: HttpRequest,
+    connection: web::Data<DatabaseConnection>,
+    info: web::Json<RegisterDeviceRequest>,
+) -> ApiResult<web::Json<RegisterDeviceResponse>> {
+    let device_id = info.device_id.trim();
+    let name = info.name.trim();
+    if !is_valid_device_id(device_id) {
+        return Err(ApiError::invalid_request("Invalid device id"));
+    }
+    if name.is_empty() || name.chars().count() > MAX_DEVICE_NAME_LENGTH {
+        return Err(ApiError::invalid_request("Invalid device name"));
+    }
+
+    let now = Local::now().naive_local();
+    let device = Device {
+        id: device_id.to_string(),
+        name: name.to_string(),
+        client_ip: req
+            .peer_addr()
+            .map(|addr| addr.ip().to_canonical().to_string())
+            .unwrap_or_default(),
+        user_agent: req
+            .headers()
+            .get(header::USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string()),
+        created_at: now,
+        updated_at: now,
+    };
+    let device = save_device(connection.get_ref(), device).await?;
+    info!("Registered device {} as {}", device.id, device.name);
+    Ok(web::Json(RegisterDeviceResponse {
+        device_id: device.id,
+        name: device.name,
+    }))
+}
+
+#[test]
+fn test_is_valid_device_id() {
+    assert!(is_valid_device_id("0b7c5a1e-3f2d-4c8e-9a6b-1d2e3f4a5b6c"));
+    assert!(!is_valid_device_id(""));
+    assert!(!is_valid_device_id("../etc/passwd"));
+    assert!(!is_valid_device_id(&"a".repeat(65)));
+}
@@ -1,79 +1,131 @@
 use crate::assets::Assets;
-use crate::backend::entities::shares;
+use crate::backend::db::repository::{Share, get_share_by_token};
+use crate::backend::web::error::{ApiError, ApiResult};
+use crate::backend::web::throttle::{BandwidthLimiter, throttle_response};
+use crate::backend::web::transfers::track_download;
 use crate::mimes::get_icon_for_mime;
 use actix_files::NamedFile;
 use actix_web::http::header::ContentType;
 use actix_web::mime::Mime;
-use actix_web::{Error, HttpResponse, get, mime, web};
+use actix_web::{HttpRequest, HttpResponse, get, mime, web};
 use log::error;
-use sea_orm::{DatabaseConnection, EntityTrait};
+use sea_orm::DatabaseConnection;
 use serde::Deserialize;
 use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::Arc;
+use utoipa::IntoParams;
 
-#[derive(Deserialize)]
-struct DownloadOption {
-    force_download: bool,
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(crate) struct DownloadOption {
+    /// 为true时以附件形式下载，否则由浏览器直接打开
+    pub(crate) force_download: bool,
 }
 
-#[get("/stream/{file_id}")]
+/// 下载分享的文件，支持范围请求。通过随机的访问令牌而不是分享项id访问，无法根据一个下载链接推测其他文件的链接
+#[utoipa::path(
+    get,
+    path = "/stream/{access_token}",
+    tag = "downloads",
+    params(
+        ("access_token" = String, Path, description = "分享项的访问令牌"),
+        DownloadOption
+    ),
+    responses(
+        (status = 200, description = "文件内容"),
+        (status = 206, description = "范围请求的部分文件内容"),
+        (status = 404, description = "分享项或者文件不存在", body = ApiError),
+    )
+)]
+#[get("/stream/{access_token}")]
 pub async fn stream_download(
-    path: web::Path<i64>,
+    req: HttpRequest,
+    path: web::Path<String>,
     connection: web::Data<DatabaseConnection>,
+    bandwidth_limiter: web::Data<Arc<BandwidthLimiter>>,
     query: web::Query<DownloadOption>,
-) -> Result<NamedFile, Error> {
-    let file_id = path.into_inner();
-    let force_download = query.force_download;
-    let result = shares::Entity::find_by_id(file_id)
-        .all(connection.get_ref())
-        .await;
-
-    if let Err(e) = result {
-        error!("Failed to find shares by id, {e}");
-        return Err(actix_web::error::ErrorNotFound("File not found"));
-    }
+) -> ApiResult<HttpResponse> {
+    let access_token = path.into_inner();
+    let share = match get_share_by_token(connection.get_ref(), &access_token).await {
+        Ok(Some(share)) => share,
+        Ok(None) => {
+            error!("Failed to find shares by access token, access_token={access_token}");
+            return Err(ApiError::not_found("File not found"));
+        }
+        Err(e) => {
+            error!("Failed to find shares by access token, {e}");
+            return Err(ApiError::not_found("File not found"));
+        }
+    };
+    stream_share(
+        &req,
+        share,
+        connection.get_ref(),
+        bandwidth_limiter.get_ref().clone(),
+        query.force_download,
+    )
+    .await
+}
 
-    let result = result.unwrap();
-    let share_info = result.first();
-    if share_info.is_none() {
-        error!("Failed to find shares by id, file_id={file_id}");
-        return Err(actix_web::error::ErrorNotFound("File not found"));
-    }
-    let file_path = share_info.unwrap().file_path.clone();
-    let file_path = PathBuf::from(file_path);
+/// 返回分享的文件内容，记录下载的传输记录并限制下载速度
+pub(crate) async fn stream_share(
+    req: &HttpRequest,
+    share: Share,
+    connection: &DatabaseConnection,
+    bandwidth_limiter: Arc<BandwidthLimiter>,
+    force_download: bool,
+) -> ApiResult<HttpResponse> {
+    let file_path = PathBuf::from(&share.file_path);
 
     // 使用 NamedFile 会自动处理范围请求、ETag 等
-    match NamedFile::open(file_path) {
+    let file = match NamedFile::open(file_path) {
         Ok(file) => {
             if force_download {
-                Ok(file
-                    .use_last_modified(true)
-                    .set_content_type(Mime::from_str("application/octet-stream").unwrap()))
+                file.use_last_modified(true)
+                    .set_content_type(Mime::from_str("application/octet-stream").unwrap())
             } else {
-                Ok(file.use_last_modified(true))
+                file.use_last_modified(true)
             }
         }
-        Err(_) => Err(actix_web::error::ErrorNotFound("File not found")),
-    }
+        Err(_) => return Err(ApiError::not_found("File not found")),
+    };
+    let response = file.into_response(req);
+    let response = track_download(req, response, connection, share).await;
+    Ok(throttle_response(response, bandwidth_limiter))
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 struct IconForMimeTypeQuery {
     mime_type: String,
 }
 
+/// 获取文件类型对应的svg图标
+#[utoipa::path(
+    get,
+    path = "/icons",
+    tag = "downloads",
+    params(IconForMimeTypeQuery),
+    responses(
+        (status = 200, description = "svg图标"),
+        (status = 404, description = "图标不存在", body = ApiError),
+    )
+)]
 #[get("/icons")]
-pub async fn get_icon_for_mime_type(query: web::Query<IconForMimeTypeQuery>) -> HttpResponse {
+pub async fn get_icon_for_mime_type(
+    query: web::Query<IconForMimeTypeQuery>,
+) -> ApiResult<HttpResponse> {
     let mime_type = &query.mime_type;
 
     let icon_path = get_icon_for_mime(mime_type);
     let data = Assets::get(&*icon_path);
     if data.is_none() {
-        return HttpResponse::NotFound().finish();
+        return Err(ApiError::not_found("Icon not found"));
     }
     let data = data.unwrap();
 
-    HttpResponse::Ok()
+    Ok(HttpResponse::Ok()
         .content_type(ContentType(mime::Mime::from_str("image/svg+xml").unwrap()))
-        .body(data.data)
+        .body(data.data))
 }
@@ -0,0 +1,305 @@
+use crate::backend::db::repository::get_share_by_token;
+use crate::backend::web::error::{ApiError, ApiResult};
+use actix_web::http::header;
+use actix_web::http::header::ContentType;
+use actix_web::{HttpResponse, get, web};
+use log::error;
+use pulldown_cmark::{CowStr, Event, Options, Parser, Tag};
+use sea_orm::DatabaseConnection;
+use std::path::Path;
+use std::sync::LazyLock;
+use syntect::highlighting::ThemeSet;
+use syntect::html::highlighted_html_for_string;
+use syntect::parsing::SyntaxSet;
+
+/// 超过该大小的文本文件不做预览，直接交给浏览器处理
+const MAX_TEXT_PREVIEW_SIZE: u64 = 1024 * 1024;
+
+const HIGHLIGHT_THEME: &str = "InspiredGitHub";
+
+static SYNTAX_SET: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
+
+static THEME_SET: LazyLock<ThemeSet> = LazyLock::new(ThemeSet::load_defaults);
+
+/// 预览方式
+#[derive(Debug, PartialEq)]
+enum PreviewKind {
+    Markdown,
+    /// 文本或者代码，进行语法高亮
+    Text,
+    Audio,
+    Video,
+    /// 图片、PDF等浏览器可以直接打开的文件
+    Raw,
+}
+
+/// 以文本形式预览的非`text/*`类型
+const TEXT_MIME_TYPES: [&str; 9] = [
+    "application/json",
+    "application/javascript",
+    "application/x-javascript",
+    "application/xml",
+    "application/x-sh",
+    "application/x-shellscript",
+    "application/toml",
+    "application/x-yaml",
+    "application/sql",
+];
+
+fn get_preview_kind(mime_type: &str, file_name: &str) -> PreviewKind {
+    let extension = Path::new(file_name)
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase());
+    if mime_type == "text/markdown" || matches!(extension.as_deref(), Some("md" | "markdown")) {
+        PreviewKind::Markdown
+    } else if mime_type.starts_with("text/") || TEXT_MIME_TYPES.contains(&mime_type) {
+        PreviewKind::Text
+    } else if mime_type.starts_with("audio/") {
+        PreviewKind::Audio
+    } else if mime_type.starts_with("video/") {
+        PreviewKind::Video
+    } else {
+        PreviewKind::Raw
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// 链接和图片允许的协议，不带协议的相对地址也是允许的
+const ALLOWED_URL_SCHEMES: [&str; 3] = ["http", "https", "mailto"];
+
+/// 判断链接地址是否可以放到预览页面中，`javascript:`、`data:`等协议可能执行脚本。
+/// 浏览器会忽略地址中的空白和控制字符，判断协议之前先去掉它们
+fn is_safe_url(url: &str) -> bool {
+    let url: String = url
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace() && !c.is_control())
+        .collect();
+    let scheme_end = url.find([':', '/', '?', '#']);
+    match scheme_end {
+        Some(end) if url[end..].starts_with(':') => ALLOWED_URL_SCHEMES
+            .iter()
+            .any(|scheme| url[..end].eq_ignore_ascii_case(scheme)),
+        _ => true,
+    }
+}
+
+fn sanitize_url(url: CowStr) -> CowStr {
+    if is_safe_url(&url) {
+        url
+    } else {
+        CowStr::Borrowed("#")
+    }
+}
+
+/// 将markdown渲染为HTML，markdown中内嵌的HTML会被转义，不安全的链接地址会被替换，
+/// 避免分享的文件在预览页面中执行脚本
+fn render_markdown(content: &str) -> String {
+    let parser = Parser::new_ext(content, Options::all()).map(|event| match event {
+        Event::Html(html) | Event::InlineHtml(html) => Event::Text(html),
+        Event::Start(Tag::Link {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => Event::Start(Tag::Link {
+            link_type,
+            dest_url: sanitize_url(dest_url),
+            title,
+            id,
+        }),
+        Event::Start(Tag::Image {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => Event::Start(Tag::Image {
+            link_type,
+            dest_url: sanitize_url(dest_url),
+            title,
+            id,
+        }),
+        event => event,
+    });
+    let mut html = String::new();
+    pulldown_cmark::html::push_html(&mut html, parser);
+    html
+}
+
+/// 根据文件扩展名或者首行选择语法进行高亮
+fn render_code(content: &str, file_name: &str) -> String {
+    let syntax = Path::new(file_name)
+        .extension()
+        .and_then(|extension| SYNTAX_SET.find_syntax_by_extension(&extension.to_string_lossy()))
+        .or_else(|| SYNTAX_SET.find_syntax_by_first_line(content))
+        .unwrap_or_else(|| SYNTAX_SET.find_syntax_plain_text());
+    let theme = &THEME_SET.themes[HIGHLIGHT_THEME];
+    match highlighted_html_for_string(content, &SYNTAX_SET, syntax, theme) {
+        Ok(html) => html,
+        Err(e) => {
+            error!("Failed to highlight {file_name}, {e}");
+            format!("<pre>{}</pre>", escape_html(content))
+        }
+    }
+}
+
+fn render_page(file_name: &str, stream_url: &str, body: &str) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{title}</title>
+<style>
+body {{ margin: 0; font-family: sans-serif; }}
+header {{ display: flex; justify-content: space-between; align-items: center; gap: 16px; padding: 8px 16px; border-bottom: 1px solid #ddd; }}
+header span {{ overflow: hidden; text-overflow: ellipsis; white-space: nowrap; }}
+main {{ padding: 16px; overflow-x: auto; }}
+main pre {{ margin: 0; padding: 8px; font-size: 14px; }}
+main img {{ max-width: 100%; }}
+audio, video {{ display: block; width: 100%; max-height: 80vh; }}
+</style>
+</head>
+<body>
+<header><span>{title}</span><a href="{stream_url}?force_download=true">Download</a></header>
+<main>{body}</main>
+</body>
+</html>"#,
+        title = escape_html(file_name),
+    )
+}
+
+/// 根据文件的mime类型在浏览器中预览文件，音视频通过`stream_download`播放以支持拖动进度条
+#[utoipa::path(
+    get,
+    path = "/preview/{access_token}",
+    tag = "downloads",
+    params(("access_token" = String, Path, description = "分享项的访问令牌")),
+    responses(
+        (status = 200, description = "预览页面的HTML"),
+        (status = 302, description = "浏览器可以直接打开的文件，重定向到下载地址"),
+        (status = 404, description = "分享项或者文件不存在", body = ApiError),
+    )
+)]
+#[get("/preview/{access_token}")]
+pub async fn preview(
+    path: web::Path<String>,
+    connection: web::Data<DatabaseConnection>,
+) -> ApiResult<HttpResponse> {
+    let access_token = path.into_inner();
+    let share_info = match get_share_by_token(connection.get_ref(), &access_token).await {
+        Ok(Some(share_info)) => share_info,
+        Ok(None) => return Err(ApiError::not_found("File not found")),
+        Err(e) => {
+            error!("Failed to find shares by access token, {e}");
+            return Err(ApiError::not_found("File not found"));
+        }
+    };
+
+    let stream_url = format!("/api/v1/stream/{}", access_token);
+    let raw_response = HttpResponse::Found()
+        .insert_header((
+            header::LOCATION,
+            format!("{}?force_download=false", stream_url),
+        ))
+        .finish();
+
+    let file_name = share_info.file_name;
+    let body = match get_preview_kind(&share_info.mime_type, &file_name) {
+        PreviewKind::Audio => format!(
+            r#"<audio controls preload="metadata" src="{}?force_download=false"></audio>"#,
+            stream_url
+        ),
+        PreviewKind::Video => format!(
+            r#"<video controls playsinline preload="metadata" src="{}?force_download=false"></video>"#,
+            stream_url
+        ),
+        PreviewKind::Raw => return Ok(raw_response),
+        kind @ (PreviewKind::Markdown | PreviewKind::Text) => {
+            let file_path = share_info.file_path;
+            match async_fs::metadata(&file_path).await {
+                Ok(metadata) if metadata.len() <= MAX_TEXT_PREVIEW_SIZE => {}
+                Ok(_) => return Ok(raw_response),
+                Err(_) => return Err(ApiError::not_found("File not found")),
+            }
+            let content = async_fs::read(&file_path)
+                .await
+                .map_err(|_| ApiError::not_found("File not found"))?;
+            let content = String::from_utf8_lossy(&content).into_owned();
+            let highlight_file_name = file_name.clone();
+            // 语法高亮比较耗时，放到线程池中执行
+            web::block(move || match kind {
+                PreviewKind::Markdown => render_markdown(&content),
+                _ => render_code(&content, &highlight_file_name),
+            })
+            .await?
+        }
+    };
+
+    Ok(HttpResponse::Ok()
+        .content_type(ContentType::html())
+        .body(render_page(&file_name, &stream_url, &body)))
+}
+
+#[test]
+fn test_get_preview_kind() {
+    assert_eq!(
+        PreviewKind::Markdown,
+        get_preview_kind("text/plain", "README.md")
+    );
+    assert_eq!(
+        PreviewKind::Text,
+        get_preview_kind("text/x-rust", "main.rs")
+    );
+    assert_eq!(
+        PreviewKind::Text,
+        get_preview_kind("application/json", "package.json")
+    );
+    assert_eq!(PreviewKind::Video, get_preview_kind("video/mp4", "a.mp4"));
+    assert_eq!(
+        PreviewKind::Raw,
+        get_preview_kind("application/pdf", "a.pdf")
+    );
+}
+
+#[test]
+fn test_render_markdown_escapes_html() {
+    let html = render_markdown("# Title\n\n<script>alert(1)</script>");
+    assert!(html.contains("<h1>Title</h1>"));
+    assert!(!html.contains("<script>"));
+}
+
+#[test]
+fn test_render_markdown_sanitizes_urls() {
+    let html = render_markdown(
+        "[a](javascript:alert(1)) [b](JavaScript:alert(1)) ![c](data:image/svg+xml,x)\n\n\
+         [d](https://example.com) [e](mailto:a@example.com) [f](docs/readme.md) \
+         [g](/web/index.html?a=b:c)",
+    );
+    assert!(!html.to_lowercase().contains("javascript:"));
+    assert!(!html.contains("data:"));
+    assert!(html.contains(r#"href="https://example.com""#));
+    assert!(html.contains(r#"href="mailto:a@example.com""#));
+    assert!(html.contains(r#"href="docs/readme.md""#));
+    assert!(html.contains(r#"href="/web/index.html?a=b:c""#));
+
+    assert!(!is_safe_url("javascript:alert(1)"));
+    assert!(!is_safe_url(" jav\nascript:alert(1)"));
+    assert!(!is_safe_url("vbscript:msgbox"));
+    assert!(is_safe_url("HTTP://example.com"));
+    assert!(is_safe_url("#section"));
+}
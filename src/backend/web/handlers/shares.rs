@@ -0,0 +1,242 @@
+use crate::backend::db::repository;
+use crate::backend::db::repository::{
+    Share, ShareFilter, ShareSortBy, get_share_by_token, get_shares_page, update_share,
+};
+use crate::backend::watcher::{ShareStatus, get_share_status};
+use crate::backend::web::error::{ApiError, ApiResult};
+use crate::mimes::MimeCategory;
+use crate::util::{compute_file_hash, read_file_metadata};
+use actix_web::{Responder, get, post, web};
+use anyhow::Context;
+use log::{error, info};
+use sea_orm::{DatabaseConnection, IntoActiveModel, Set};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use utoipa::{IntoParams, ToSchema};
+
+/// 分享项
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ShareItem {
+    id: String,
+    file_name: String,
+    mime_type: String,
+    file_size: i64,
+    modified_at: Option<String>,
+    /// 只有获取过分享详情之后才会有值
+    sha256: Option<String>,
+    /// 文件是否被删除或者修改过，参考`ShareStatus`
+    status: String,
+    /// 下载、预览以及获取缩略图时使用的访问令牌
+    access_token: String,
+    /// 过期之后分享项会被移除，为None时永不过期
+    expires_at: Option<String>,
+}
+
+impl From<&Share> for ShareItem {
+    fn from(share: &Share) -> Self {
+        Self {
+            id: share.id.to_string(),
+            file_name: share.file_name.clone(),
+            mime_type: share.mime_type.clone(),
+            file_size: share.file_size,
+            modified_at: share
+                .modified_at
+                .map(|modified_at| modified_at.format("%Y-%m-%dT%H:%M:%S").to_string()),
+            sha256: share.sha256.clone(),
+            status: share.status.clone(),
+            access_token: share.access_token.clone(),
+            expires_at: share
+                .expires_at
+                .map(|expires_at| expires_at.format("%Y-%m-%dT%H:%M:%S").to_string()),
+        }
+    }
+}
+
+/// 获取所有公开的分享项，兼容旧版本网页端，新的客户端请使用`GET /shares`
+#[utoipa::path(
+    post,
+    path = "/shares",
+    tag = "shares",
+    responses(
+        (status = 200, description = "所有的分享项", body = Vec<ShareItem>),
+        (status = 500, description = "查询失败", body = ApiError),
+    )
+)]
+#[post("/shares")]
+pub async fn get_shares(conn: web::Data<DatabaseConnection>) -> ApiResult<impl Responder> {
+    info!("Accept request: POST /shares");
+
+    let filter = ShareFilter {
+        public_only: true,
+        ..Default::default()
+    };
+    let share_list = repository::get_shares(conn.get_ref(), &filter).await?;
+    let share_list: Vec<ShareItem> = share_list.iter().map(ShareItem::from).collect();
+    Ok(web::Json(share_list))
+}
+
+const DEFAULT_PAGE_SIZE: u64 = 50;
+
+const MAX_PAGE_SIZE: u64 = 200;
+
+/// `GET /shares`的查询参数
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ShareListQuery {
+    /// 页码，从1开始
+    page: Option<u64>,
+    page_size: Option<u64>,
+    /// 按文件名搜索
+    keyword: Option<String>,
+    /// 文件类型，参考`MimeCategory`
+    category: Option<String>,
+    /// 排序字段：added、name、size或者date
+    sort: Option<String>,
+    /// asc或者desc
+    order: Option<String>,
+}
+
+impl ShareListQuery {
+    fn to_filter(&self) -> ApiResult<ShareFilter> {
+        let category =
+            match &self.category {
+                Some(category) => Some(MimeCategory::from_str(category).map_err(|_| {
+                    ApiError::invalid_request(format!("Unknown category: {category}"))
+                })?),
+                None => None,
+            };
+        let sort_by = match &self.sort {
+            Some(sort) => ShareSortBy::from_str(sort)
+                .map_err(|_| ApiError::invalid_request(format!("Unknown sort field: {sort}")))?,
+            None => ShareSortBy::default(),
+        };
+        let descending = match self.order.as_deref() {
+            None | Some("asc") => false,
+            Some("desc") => true,
+            Some(order) => {
+                return Err(ApiError::invalid_request(format!("Unknown order: {order}")));
+            }
+        };
+        Ok(ShareFilter {
+            keyword: self.keyword.clone(),
+            category,
+            sort_by,
+            descending,
+            public_only: true,
+        })
+    }
+}
+
+/// 分页查询的结果
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ShareListPage {
+    items: Vec<ShareItem>,
+    /// 符合条件的分享项总数
+    total: u64,
+    page: u64,
+    page_size: u64,
+}
+
+/// 分页获取公开的分享列表，支持按文件名搜索、按文件类型过滤以及排序
+#[utoipa::path(
+    get,
+    path = "/shares",
+    tag = "shares",
+    params(ShareListQuery),
+    responses(
+        (status = 200, description = "当前页的分享项", body = ShareListPage),
+        (status = 400, description = "查询参数错误", body = ApiError),
+    )
+)]
+#[get("/shares")]
+pub async fn list_shares(
+    query: web::Query<ShareListQuery>,
+    conn: web::Data<DatabaseConnection>,
+) -> ApiResult<impl Responder> {
+    info!("Accept request: GET /shares");
+
+    let filter = query.to_filter()?;
+    let page = query.page.unwrap_or(1).max(1);
+    let page_size = query
+        .page_size
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+    let (share_list, total) = get_shares_page(conn.get_ref(), &filter, page - 1, page_size).await?;
+    Ok(web::Json(ShareListPage {
+        items: share_list.iter().map(ShareItem::from).collect(),
+        total,
+        page,
+        page_size,
+    }))
+}
+
+/// 哈希值只在第一次获取详情时计算。文件被修改过时保留添加分享时记录的文件信息和哈希值，
+/// 只将状态标记为已修改，否则分享列表中的修改提示会被清除
+async fn refresh_share_metadata(
+    connection: &DatabaseConnection,
+    share: Share,
+) -> anyhow::Result<Share> {
+    let (file_size, modified_at) = read_file_metadata(&share.file_path).await?;
+    // 旧版本添加的分享没有记录文件信息，以当前的文件为准
+    let legacy = share.modified_at.is_none();
+    let status = if legacy {
+        ShareStatus::Available
+    } else {
+        get_share_status(&share, Some((file_size, modified_at)))
+    };
+    let needs_hash = status == ShareStatus::Available && share.sha256.is_none();
+    if !legacy && !needs_hash && share.status == status.to_string() {
+        return Ok(share);
+    }
+
+    let sha256 = if needs_hash {
+        let sha256 = compute_file_hash(&share.file_path)
+            .await
+            .context("Failed to compute file hash")?;
+        Some(sha256)
+    } else {
+        None
+    };
+    let mut share = share.into_active_model();
+    if legacy {
+        share.file_size = Set(file_size);
+        share.modified_at = Set(Some(modified_at));
+    }
+    if let Some(sha256) = sha256 {
+        share.sha256 = Set(Some(sha256));
+    }
+    share.status = Set(status.to_string());
+    update_share(connection, share).await
+}
+
+/// 获取分享详情，第一次获取时会计算文件的SHA-256
+#[utoipa::path(
+    get,
+    path = "/shares/{access_token}",
+    tag = "shares",
+    params(("access_token" = String, Path, description = "分享项的访问令牌")),
+    responses(
+        (status = 200, description = "分享详情", body = ShareItem),
+        (status = 404, description = "分享项或者文件不存在", body = ApiError),
+    )
+)]
+#[get("/shares/{access_token}")]
+pub async fn get_share_detail(
+    path: web::Path<String>,
+    conn: web::Data<DatabaseConnection>,
+) -> ApiResult<impl Responder> {
+    let access_token = path.into_inner();
+    info!("Accept request: GET /shares/{}", access_token);
+
+    let share = get_share_by_token(conn.get_ref(), &access_token)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Share not found"))?;
+    let share_id = share.id;
+    match refresh_share_metadata(conn.get_ref(), share).await {
+        Ok(share) => Ok(web::Json(ShareItem::from(&share))),
+        Err(e) => {
+            error!("Failed to refresh metadata of share {share_id}, {e:?}");
+            Err(ApiError::not_found("File not found"))
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use crate::backend::db::repository::get_share_by_token;
+use crate::backend::web::error::{ApiError, ApiResult};
+use crate::thumbnail::{get_or_create_thumbnail, is_thumbnail_supported, thumbnail_cache_dir};
+use actix_files::NamedFile;
+use actix_web::{HttpRequest, HttpResponse, get, web};
+use anyhow::Context;
+use log::error;
+use sea_orm::DatabaseConnection;
+use std::path::PathBuf;
+
+/// 获取图片分享的缩略图，缩略图在第一次请求时生成并缓存
+#[utoipa::path(
+    get,
+    path = "/thumbnails/{access_token}",
+    tag = "downloads",
+    params(("access_token" = String, Path, description = "分享项的访问令牌")),
+    responses(
+        (status = 200, description = "PNG格式的缩略图"),
+        (status = 404, description = "分享项不存在或者不支持生成缩略图", body = ApiError),
+    )
+)]
+#[get("/thumbnails/{access_token}")]
+pub async fn get_thumbnail(
+    req: HttpRequest,
+    path: web::Path<String>,
+    connection: web::Data<DatabaseConnection>,
+) -> ApiResult<HttpResponse> {
+    let access_token = path.into_inner();
+    let share_info = match get_share_by_token(connection.get_ref(), &access_token).await {
+        Ok(Some(share_info)) => share_info,
+        Ok(None) => return Err(ApiError::not_found("File not found")),
+        Err(e) => {
+            error!("Failed to find shares by access token, {e}");
+            return Err(ApiError::not_found("File not found"));
+        }
+    };
+    if !is_thumbnail_supported(&share_info.mime_type) {
+        return Err(ApiError::not_found("Thumbnail not supported"));
+    }
+
+    // 解码图片比较耗时，放到线程池中执行，避免阻塞其他请求
+    let file_path = PathBuf::from(share_info.file_path);
+    let thumbnail_path =
+        web::block(move || get_or_create_thumbnail(&thumbnail_cache_dir(), &file_path))
+            .await?
+            .map_err(|e| {
+                error!(
+                    "Failed to create thumbnail for {:?}, {e:?}",
+                    share_info.file_name
+                );
+                ApiError::not_found("Thumbnail not available")
+            })?;
+
+    let file = NamedFile::open(thumbnail_path).context("Failed to open thumbnail")?;
+    Ok(file.use_last_modified(true).into_response(&req))
+}
@@ -2,13 +2,18 @@ use crate::backend::db::repository::{
     Chunk, UploadItem, delete_chunk_by_id, delete_upload_item, get_chunk_by_number,
     get_upload_chunks, get_upload_item, insert_chunk, insert_upload_item, update_upload_item,
 };
+use crate::backend::web::error::{ApiError, ApiResult, ErrorCode};
+use crate::backend::web::transfers::{
+    ClientInfo, TransferResult, track_upload_chunk, track_upload_finished,
+};
+use crate::notification::{NotificationEvent, NotificationOptions, notify};
 use crate::setting::Settings;
 use crate::util;
 use crate::util::{
     check_file, check_file_hash, delete_file_if_exists, exists_file, get_available_filename,
 };
 use actix_multipart::form::{MultipartForm, json::Json, tempfile::TempFile};
-use actix_web::{HttpResponse, Responder, post, web};
+use actix_web::{HttpRequest, HttpResponse, post, web};
 use anyhow::{Context, anyhow};
 use chrono::Local;
 use futures::AsyncWriteExt;
@@ -17,22 +22,24 @@ use log::{error, info, warn};
 use sea_orm::strum::{Display as StrumDisplay, EnumString};
 use sea_orm::{DatabaseConnection, IntoActiveModel, Set};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::Arc;
+use utoipa::ToSchema;
 
 // 常量定义
 const CHUNK_FILE_PREFIX: &str = "chunk_";
 
 /// 上传状态枚举
-#[derive(EnumString, StrumDisplay, Debug, PartialEq, Serialize, Deserialize, Clone)]
+#[derive(EnumString, StrumDisplay, Debug, PartialEq, Serialize, Deserialize, ToSchema, Clone)]
 enum UploadStatus {
     Uploading,
     Completed,
 }
 
 /// 初始化上传请求结构
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct InitUploadRequest {
     file_name: String,
     file_size: i64,
@@ -40,7 +47,7 @@ pub struct InitUploadRequest {
 }
 
 /// 初始化上传响应结构
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct InitUploadResponse {
     file_id: String,
     status: UploadStatus,
@@ -49,57 +56,66 @@ pub struct InitUploadResponse {
 }
 
 /// 初始化上传端点
+#[utoipa::path(
+    post,
+    path = "/upload/init",
+    tag = "uploads",
+    request_body = InitUploadRequest,
+    responses(
+        (status = 200, description = "上传项的状态以及已经上传的分片，用于断点续传", body = InitUploadResponse),
+        (status = 500, description = "初始化失败", body = ApiError),
+    )
+)]
 #[post("/upload/init")]
 pub async fn init_upload(
+    req: HttpRequest,
     connection: web::Data<DatabaseConnection>,
     settings: web::Data<Arc<Settings>>,
+    notification_options: web::Data<Arc<NotificationOptions>>,
     info: web::Json<InitUploadRequest>,
-) -> impl Responder {
+) -> ApiResult<web::Json<InitUploadResponse>> {
     let file_hash = info.file_hash.clone();
     let storage_folder = &settings.get_ref().storage_folder;
     let connection_ref = connection.get_ref();
 
     // 查找文件元数据
-    match get_upload_item(connection_ref, &file_hash).await {
-        Ok(Some(upload_item)) => {
-            let status = match UploadStatus::from_str(&upload_item.status) {
-                Ok(status) => status,
-                Err(e) => {
-                    error!("Invalid upload status in database: {}", e);
-                    return HttpResponse::InternalServerError().body("Invalid upload status");
-                }
-            };
+    let response = match get_upload_item(connection_ref, &file_hash).await? {
+        Some(upload_item) => {
+            let status = UploadStatus::from_str(&upload_item.status).map_err(|e| {
+                ApiError::internal(format!("Invalid upload status in database: {}", e))
+            })?;
 
             match status {
                 UploadStatus::Completed => {
                     handle_completed_upload(upload_item, connection_ref, storage_folder, &info)
-                        .await
+                        .await?
                 }
                 UploadStatus::Uploading => {
-                    handle_uploading_upload(upload_item, connection_ref, storage_folder).await
+                    handle_uploading_upload(upload_item, connection_ref, storage_folder).await?
                 }
             }
         }
-        Ok(None) => {
+        None => {
             // 没有找到现有记录，创建新的上传项
-            match create_upload_item(connection_ref, storage_folder, &info).await {
-                Ok(upload_item) => HttpResponse::Ok().json(InitUploadResponse {
-                    file_id: upload_item.id,
-                    status: UploadStatus::Uploading,
-                    uploaded_chunks: vec![],
-                    uploaded_size: 0,
-                }),
-                Err(e) => {
-                    error!("Failed to create new upload item: {}", e);
-                    HttpResponse::InternalServerError().finish()
-                }
+            let upload_item = create_upload_item(connection_ref, storage_folder, &info)
+                .await
+                .context("Failed to create new upload item")?;
+            notify(
+                &notification_options,
+                NotificationEvent::UploadStarted,
+                &upload_item.file_name,
+                &ClientInfo::from_request(&req).client_ip,
+                None,
+            );
+            InitUploadResponse {
+                file_id: upload_item.id,
+                status: UploadStatus::Uploading,
+                uploaded_chunks: vec![],
+                uploaded_size: 0,
             }
         }
-        Err(e) => {
-            error!("Failed to query uploads: {}", e);
-            HttpResponse::InternalServerError().finish()
-        }
-    }
+    };
+    Ok(web::Json(response))
 }
 
 /// 创建上传项
@@ -163,41 +179,35 @@ async fn handle_completed_upload(
     connection: &DatabaseConnection,
     storage_folder: &str,
     info: &InitUploadRequest,
-) -> HttpResponse {
-    match check_file(upload_item.file_path, &upload_item.id).await {
-        Ok(util::CheckFileResult::Valid) => HttpResponse::Ok().json(InitUploadResponse {
+) -> anyhow::Result<InitUploadResponse> {
+    let check_result = check_file(upload_item.file_path, &upload_item.id)
+        .await
+        .context("Failed to check file status")?;
+    match check_result {
+        util::CheckFileResult::Valid => Ok(InitUploadResponse {
             file_id: upload_item.id,
             status: UploadStatus::Completed,
             uploaded_chunks: vec![],
             uploaded_size: upload_item.file_size,
         }),
-        Ok(util::CheckFileResult::Invalid(msg)) => {
+        util::CheckFileResult::Invalid(msg) => {
             // 文件完整性校验不通过，重置上传项
             info!(
                 "File {} is invalid since {}, resetting upload item",
                 upload_item.id, msg
             );
-            if let Err(e) = delete_upload_item(connection, &upload_item.id).await {
-                error!("Failed to delete invalid upload item: {}", e);
-                return HttpResponse::InternalServerError()
-                    .body("Failed to delete invalid upload item");
-            }
-            match create_upload_item(connection, Path::new(storage_folder), info).await {
-                Ok(new_upload_item) => HttpResponse::Ok().json(InitUploadResponse {
-                    file_id: new_upload_item.id,
-                    status: UploadStatus::Uploading,
-                    uploaded_chunks: vec![],
-                    uploaded_size: 0,
-                }),
-                Err(e) => {
-                    error!("Failed to reset upload item: {}", e);
-                    HttpResponse::InternalServerError().finish()
-                }
-            }
-        }
-        Err(e) => {
-            error!("Failed to check completed upload: {}", e);
-            HttpResponse::InternalServerError().body("Failed to check file status")
+            delete_upload_item(connection, &upload_item.id)
+                .await
+                .context("Failed to delete invalid upload item")?;
+            let new_upload_item = create_upload_item(connection, Path::new(storage_folder), info)
+                .await
+                .context("Failed to reset upload item")?;
+            Ok(InitUploadResponse {
+                file_id: new_upload_item.id,
+                status: UploadStatus::Uploading,
+                uploaded_chunks: vec![],
+                uploaded_size: 0,
+            })
         }
     }
 }
@@ -207,37 +217,28 @@ async fn handle_uploading_upload(
     upload_item: UploadItem,
     connection: &DatabaseConnection,
     storage_folder: &str,
-) -> HttpResponse {
+) -> anyhow::Result<InitUploadResponse> {
     let upload_id = upload_item.id;
     let upload_dir = Path::new(storage_folder).join(&upload_id);
 
-    match get_upload_chunks(connection, &upload_id).await {
-        Ok(chunks) => match check_and_clean_invalid_chunks(connection, upload_dir, chunks).await {
-            Ok(valid_chunks) => {
-                let uploaded_chunks: Vec<i32> =
-                    valid_chunks.iter().map(|c| c.chunk_number).collect();
-                let uploaded_size = valid_chunks.iter().map(|c| c.chunk_size).sum::<i64>();
-                HttpResponse::Ok().json(InitUploadResponse {
-                    file_id: upload_id,
-                    status: UploadStatus::Uploading,
-                    uploaded_chunks,
-                    uploaded_size,
-                })
-            }
-            Err(e) => {
-                error!("Failed to query chunks: {}", e);
-                HttpResponse::InternalServerError().body("Failed to check and clean invalid chunks")
-            }
-        },
-        Err(e) => {
-            error!("Failed to get upload chunks: {}", e);
-            HttpResponse::InternalServerError().body("Failed to query chunks")
-        }
-    }
+    let chunks = get_upload_chunks(connection, &upload_id)
+        .await
+        .context("Failed to query chunks")?;
+    let valid_chunks = check_and_clean_invalid_chunks(connection, upload_dir, chunks)
+        .await
+        .context("Failed to check and clean invalid chunks")?;
+    let uploaded_chunks: Vec<i32> = valid_chunks.iter().map(|c| c.chunk_number).collect();
+    let uploaded_size = valid_chunks.iter().map(|c| c.chunk_size).sum::<i64>();
+    Ok(InitUploadResponse {
+        file_id: upload_id,
+        status: UploadStatus::Uploading,
+        uploaded_chunks,
+        uploaded_size,
+    })
 }
 
 /// 上传元数据结构
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 struct Metadata {
     file_id: String,
     chunk_number: i32, // 切片序号，从1开始
@@ -252,13 +253,36 @@ struct UploadForm {
     json: Json<Metadata>,
 }
 
+/// 上传分片的表单，仅用于生成接口文档
+#[derive(ToSchema)]
+#[allow(dead_code)]
+struct UploadChunkForm {
+    /// 分片的内容
+    #[schema(value_type = String, format = Binary)]
+    file: Vec<u8>,
+    /// 分片的信息，content-type为application/json
+    json: Metadata,
+}
+
 /// 处理文件切片上传端点
+#[utoipa::path(
+    post,
+    path = "/upload/chunk",
+    tag = "uploads",
+    request_body(content = UploadChunkForm, content_type = "multipart/form-data"),
+    responses(
+        (status = 200, description = "分片上传成功或者分片已经上传过", body = String),
+        (status = 400, description = "参数错误、文件已经上传完成(upload_completed)或者分片哈希值校验失败(chunk_hash_mismatch)", body = ApiError),
+        (status = 404, description = "上传项不存在", body = ApiError),
+    )
+)]
 #[post("/upload/chunk")]
 pub async fn upload_chunk(
+    req: HttpRequest,
     connection: web::Data<DatabaseConnection>,
     settings: web::Data<Arc<Settings>>,
     MultipartForm(form): MultipartForm<UploadForm>,
-) -> impl Responder {
+) -> ApiResult<HttpResponse> {
     let metadata = form.json.0;
     let upload_id = metadata.file_id;
     let chunk_number = metadata.chunk_number;
@@ -269,43 +293,44 @@ pub async fn upload_chunk(
 
     // 验证输入参数
     if upload_id.is_empty() || chunk_number <= 0 {
-        return HttpResponse::BadRequest().body("Missing or invalid required fields");
+        return Err(ApiError::invalid_request(
+            "Missing or invalid required fields",
+        ));
     }
 
     // 检查上传项目是否存在
-    let upload_item = match get_upload_item(connection, &upload_id).await {
-        Ok(Some(item)) => item,
-        Ok(None) => {
-            return HttpResponse::NotFound().body("Upload item not found");
-        }
-        Err(e) => {
-            error!("Failed to query upload item: {}", e);
-            return HttpResponse::InternalServerError().body("Failed to query upload item");
-        }
-    };
+    let upload_item = get_upload_item(connection, &upload_id)
+        .await
+        .context("Failed to query upload item")?
+        .ok_or_else(|| ApiError::not_found("Upload item not found"))?;
 
     // 检查文件是否已完成上传
     if let Ok(UploadStatus::Completed) = UploadStatus::from_str(&upload_item.status) {
-        return HttpResponse::BadRequest().body("File already completed");
+        return Err(ApiError::new(
+            ErrorCode::UploadCompleted,
+            "File already completed",
+        ));
     }
 
     // 创建上传目录
     let upload_dir = Path::new(storage_folder).join(&upload_id);
-    if let Err(e) = async_fs::create_dir_all(&upload_dir).await {
-        error!("Failed to create directory: {}", e);
-        return HttpResponse::InternalServerError().body("Failed to create directory");
-    }
+    async_fs::create_dir_all(&upload_dir)
+        .await
+        .context("Failed to create directory")?;
 
     let chunk_path = get_chunk_file(&upload_dir, chunk_number);
 
     // 检查是否已存在相同切片
     match get_chunk_by_number(connection, &upload_id, chunk_number).await {
-        Ok(Some(chunk)) => match check_file(&chunk_path, &chunk.chunk_hash).await {
-            Ok(util::CheckFileResult::Valid) => {
+        Ok(Some(chunk)) => match check_file(&chunk_path, &chunk.chunk_hash)
+            .await
+            .context("Failed to check existing chunk")?
+        {
+            util::CheckFileResult::Valid => {
                 info!("Chunk {} already uploaded", chunk_number);
-                return HttpResponse::Ok().body("Chunk already uploaded");
+                return Ok(HttpResponse::Ok().body("Chunk already uploaded"));
             }
-            Ok(util::CheckFileResult::Invalid(msg)) => {
+            util::CheckFileResult::Invalid(msg) => {
                 info!(
                     "Existing chunk {} is invalid since {}, remove it",
                     chunk_number, msg
@@ -313,15 +338,9 @@ pub async fn upload_chunk(
                 if let Err(e) = delete_file_if_exists(&chunk_path).await {
                     warn!("Failed to delete invalid chunk file: {}", e);
                 }
-                if let Err(e) = delete_chunk_by_id(connection, chunk.id).await {
-                    error!("Failed to delete chunk record: {}", e);
-                    return HttpResponse::InternalServerError()
-                        .body("Failed to delete invalid chunk");
-                }
-            }
-            Err(e) => {
-                error!("Failed to check existing chunk: {}", e);
-                return HttpResponse::InternalServerError().body("Failed to check existing chunk");
+                delete_chunk_by_id(connection, chunk.id)
+                    .await
+                    .context("Failed to delete invalid chunk")?;
             }
         },
         Err(e) => {
@@ -336,10 +355,9 @@ pub async fn upload_chunk(
 
     // 保存切片文件
     let temp_file_path = chunk_file.file.path();
-    if let Err(e) = async_fs::copy(temp_file_path, &chunk_path).await {
-        error!("Failed to save chunk: {}", e);
-        return HttpResponse::InternalServerError().body("Failed to save chunk");
-    }
+    async_fs::copy(temp_file_path, &chunk_path)
+        .await
+        .context("Failed to save chunk")?;
 
     // 计算切片哈希并验证
     if let Err(e) = check_file_hash(&chunk_path, &chunk_hash).await {
@@ -348,7 +366,11 @@ pub async fn upload_chunk(
             warn!("Failed to delete chunk file: {}", e);
         }
         error!("Chunk hash verification failed: {}", e);
-        return HttpResponse::BadRequest().body("Chunk hash verification failed");
+        return Err(ApiError::new(
+            ErrorCode::ChunkHashMismatch,
+            "Chunk hash verification failed",
+        )
+        .with_details(Value::String(e.to_string())));
     }
 
     let chunk_size = chunk_file.size as i64;
@@ -364,12 +386,12 @@ pub async fn upload_chunk(
 
     info!("Saving chunk {:?} to db", chunk);
 
-    if let Err(e) = insert_chunk(connection, chunk).await {
-        error!("Failed to insert chunk: {}", e);
-        return HttpResponse::InternalServerError().body(format!("Failed to insert chunk: {}", e));
-    }
+    insert_chunk(connection, chunk)
+        .await
+        .context("Failed to insert chunk")?;
+    track_upload_chunk(&req, connection, &upload_item, chunk_size).await;
 
-    HttpResponse::Ok().body("Chunk uploaded successfully")
+    Ok(HttpResponse::Ok().body("Chunk uploaded successfully"))
 }
 
 /// 获取分片文件路径
@@ -380,31 +402,40 @@ fn get_chunk_file<P: AsRef<Path>>(upload_dir: P, chunk_number: i32) -> PathBuf {
 }
 
 /// 完成上传请求结构
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct CompleteUploadRequest {
     file_id: String,
 }
 
 /// 完成文件上传并验证完整性端点
+#[utoipa::path(
+    post,
+    path = "/upload/complete",
+    tag = "uploads",
+    request_body = CompleteUploadRequest,
+    responses(
+        (status = 200, description = "文件合并完成并且通过校验", body = String),
+        (status = 404, description = "上传项不存在", body = ApiError),
+        (status = 500, description = "合并分片失败", body = ApiError),
+    )
+)]
 #[post("/upload/complete")]
 pub async fn complete_upload(
+    req: HttpRequest,
     connection: web::Data<DatabaseConnection>,
     settings: web::Data<Arc<Settings>>,
+    notification_options: web::Data<Arc<NotificationOptions>>,
     info: web::Json<CompleteUploadRequest>,
-) -> impl Responder {
+) -> ApiResult<HttpResponse> {
     let upload_id = &info.file_id;
     let storage_folder = &settings.get_ref().storage_folder;
     let connection_ref = connection.get_ref();
 
     // 获取文件元数据
-    let mut upload_item = match get_upload_item(connection_ref, &upload_id).await {
-        Ok(Some(meta)) => meta,
-        Ok(None) => return HttpResponse::NotFound().body("File not found"),
-        Err(e) => {
-            error!("Failed to query file metadata: {}", e);
-            return HttpResponse::InternalServerError().body("Failed to query file metadata");
-        }
-    };
+    let upload_item = get_upload_item(connection_ref, upload_id)
+        .await
+        .context("Failed to query file metadata")?
+        .ok_or_else(|| ApiError::not_found("File not found"))?;
 
     let upload_dir = Path::new(storage_folder).join(&upload_item.id);
     match merge_chunks(connection_ref, &upload_item, &upload_dir).await {
@@ -412,25 +443,43 @@ pub async fn complete_upload(
             info!("Successfully completed upload {:?}", output_file);
         }
         Err(e) => {
-            error!("Failed to merge chunks: {}", e);
-            return HttpResponse::InternalServerError().body("Failed to merge chunks");
+            track_upload_finished(&req, connection_ref, &upload_item, TransferResult::Failed).await;
+            return Err(e.context("Failed to merge chunks").into());
         }
     }
 
+    track_upload_finished(
+        &req,
+        connection_ref,
+        &upload_item,
+        TransferResult::Completed,
+    )
+    .await;
+
+    let file_name = upload_item.file_name.clone();
+    let file_path = upload_item.file_path.clone();
+
     // 更新文件状态为已完成
     let mut upload_item = upload_item.into_active_model();
     upload_item.status = Set(UploadStatus::Completed.to_string());
-    if let Err(e) = update_upload_item(connection_ref, upload_item).await {
-        error!("Failed to update upload item status: {}", e);
-        return HttpResponse::InternalServerError().body("Failed to update upload item status");
-    }
+    update_upload_item(connection_ref, upload_item)
+        .await
+        .context("Failed to update upload item status")?;
 
     // 清理临时切片文件
     if let Err(e) = async_fs::remove_dir_all(&upload_dir).await {
         warn!("Failed to remove chunk directory: {}", e);
     }
 
-    HttpResponse::Ok().body("File uploaded and verified successfully")
+    notify(
+        &notification_options,
+        NotificationEvent::UploadCompleted,
+        &file_name,
+        &ClientInfo::from_request(&req).client_ip,
+        Some(file_path),
+    );
+
+    Ok(HttpResponse::Ok().body("File uploaded and verified successfully"))
 }
 
 /// 合并所有分片文件
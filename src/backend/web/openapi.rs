@@ -0,0 +1,97 @@
+use crate::backend::web::handlers::{
+    collections, devices, downloads, previews, shares, thumbnails, uploads,
+};
+use actix_web::{Responder, get, web};
+use utoipa::OpenApi;
+
+/// 根据接口的签名以及请求和响应结构生成的接口文档，所有路径都相对于`/api/v1`
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "share-rs",
+        description = "在局域网内分享和上传文件的接口。大文件上传时先调用`/upload/init`获取已经上传的分片，\
+            然后通过`/upload/chunk`逐个上传剩余的分片，最后调用`/upload/complete`合并分片并校验文件的SHA-256。"
+    ),
+    servers((url = "/api/v1")),
+    paths(
+        shares::get_shares,
+        shares::list_shares,
+        shares::get_share_detail,
+        collections::get_collection_info,
+        collections::list_collection_shares,
+        collections::stream_collection_download,
+        downloads::stream_download,
+        downloads::get_icon_for_mime_type,
+        thumbnails::get_thumbnail,
+        previews::preview,
+        uploads::init_upload,
+        uploads::upload_chunk,
+        uploads::complete_upload,
+        devices::register_device,
+    ),
+    tags(
+        (name = "shares", description = "分享列表"),
+        (name = "collections", description = "分享集合，集合可以设置PIN"),
+        (name = "downloads", description = "下载、预览分享的文件"),
+        (name = "uploads", description = "分片上传文件"),
+        (name = "devices", description = "网页端设备"),
+    )
+)]
+pub struct ApiDoc;
+
+/// 获取OpenAPI格式的接口文档
+#[get("/openapi.json")]
+pub async fn openapi_json() -> impl Responder {
+    web::Json(ApiDoc::openapi())
+}
+
+#[test]
+fn test_openapi_document() {
+    let openapi = ApiDoc::openapi();
+    let servers = openapi.servers.as_deref().unwrap_or_default();
+    assert_eq!(
+        vec!["/api/v1"],
+        servers
+            .iter()
+            .map(|server| server.url.as_str())
+            .collect::<Vec<_>>()
+    );
+
+    let paths = &openapi.paths.paths;
+    for path in [
+        "/shares",
+        "/shares/{access_token}",
+        "/collections/{access_token}",
+        "/collections/{access_token}/shares",
+        "/collections/{access_token}/stream/{share_id}",
+        "/stream/{access_token}",
+        "/icons",
+        "/thumbnails/{access_token}",
+        "/preview/{access_token}",
+        "/upload/init",
+        "/upload/chunk",
+        "/upload/complete",
+        "/devices/register",
+    ] {
+        assert!(paths.contains_key(path), "missing path {path}");
+    }
+    // 旧版本网页端的POST接口和分页查询的GET接口共用路径
+    let shares = &paths["/shares"];
+    assert!(shares.get.is_some() && shares.post.is_some());
+
+    let schemas = &openapi.components.as_ref().unwrap().schemas;
+    for schema in [
+        "ApiError",
+        "ShareItem",
+        "ShareListPage",
+        "CollectionInfo",
+        "InitUploadRequest",
+        "InitUploadResponse",
+        "CompleteUploadRequest",
+        "RegisterDeviceRequest",
+        "RegisterDeviceResponse",
+    ] {
+        assert!(schemas.contains_key(schema), "missing schema {schema}");
+    }
+    assert!(openapi.to_json().is_ok());
+}
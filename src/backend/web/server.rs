@@ -1,30 +1,37 @@
 use crate::assets::Assets;
 use crate::backend::db::SqliteDatabaseSource;
-use crate::backend::entities::prelude::Shares;
+use crate::backend::web::access_control::{AccessControl, access_control_middleware};
+use crate::backend::web::error::configure_extractors;
+use crate::backend::web::handlers::collections::{
+    PinLimiter, get_collection_info, list_collection_shares, stream_collection_download,
+};
+use crate::backend::web::handlers::devices::register_device;
 use crate::backend::web::handlers::downloads::{get_icon_for_mime_type, stream_download};
+use crate::backend::web::handlers::previews::preview;
+use crate::backend::web::handlers::shares::{get_share_detail, get_shares, list_shares};
+use crate::backend::web::handlers::thumbnails::get_thumbnail;
 use crate::backend::web::handlers::uploads::{complete_upload, init_upload, upload_chunk};
+use crate::backend::web::openapi::openapi_json;
 use crate::backend::web::server::ServerState::{Off, On};
+use crate::backend::web::throttle::{BandwidthLimiter, throttle_upload_middleware};
+use crate::network::parse_bind_address;
+use crate::notification::NotificationOptions;
 use crate::setting::Settings;
-use actix_web::error::ErrorInternalServerError;
 use actix_web::http::header::ContentType;
-use actix_web::{App, HttpResponse, HttpServer, Responder, Result, get, mime, post, web};
+use actix_web::middleware::from_fn;
+use actix_web::{App, HttpResponse, HttpServer, Responder, get, mime, web};
 use log::{error, info, warn};
 use mime_guess2::MimeGuess;
-use sea_orm::{DatabaseConnection, EntityTrait};
-use serde::Serialize;
+use socket2::{Domain, Protocol, Socket, Type};
+use std::fmt::{Display, Formatter};
+use std::io;
+use std::net::{IpAddr, SocketAddr, TcpListener};
 use std::str::FromStr;
 use std::sync::{Arc, Mutex};
-use std::thread::spawn;
+use std::thread::{JoinHandle, spawn};
 use tokio::sync::oneshot;
 use tokio::sync::oneshot::{Receiver, Sender};
 
-#[derive(Debug, Serialize)]
-struct ShareItem {
-    id: String,
-    file_name: String,
-    mime_type: String,
-}
-
 #[get("/web/{path:.*}")]
 async fn index(path: web::Path<String>) -> impl Responder {
     let path = path.into_inner();
@@ -47,25 +54,23 @@ async fn index(path: web::Path<String>) -> impl Responder {
     HttpResponse::Ok().body(buf)
 }
 
-#[post("/shares")]
-async fn get_shares(conn: web::Data<DatabaseConnection>) -> Result<impl Responder> {
-    info!("Accept request: POST /shares");
-
-    let result = Shares::find().all(conn.get_ref()).await;
-    if let Err(e) = result {
-        error!("Failed to query shares, {e}");
-        return Err(ErrorInternalServerError(e));
-    }
-    let share_list = result.unwrap();
-    let share_list: Vec<ShareItem> = share_list
-        .iter()
-        .map(|item| ShareItem {
-            id: item.id.to_string(),
-            file_name: item.file_name.clone(),
-            mime_type: item.mime_type.clone(),
-        })
-        .collect();
-    Ok(web::Json(share_list))
+/// 注册所有接口。接口挂载在`/api/v1`下，同时挂载在根路径下作为兼容旧版本网页端的别名
+fn configure_api(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_shares)
+        .service(list_shares)
+        .service(get_share_detail)
+        .service(get_collection_info)
+        .service(list_collection_shares)
+        .service(stream_collection_download)
+        .service(stream_download)
+        .service(get_icon_for_mime_type)
+        .service(get_thumbnail)
+        .service(preview)
+        .service(init_upload)
+        .service(upload_chunk)
+        .service(complete_upload)
+        .service(register_device)
+        .service(openapi_json);
 }
 
 type ShutdownSignalSender = Sender<()>;
@@ -100,32 +105,96 @@ async fn receive_shutdown_signal(shutdown_signal_receiver: ShutdownSignalReceive
     }
 }
 
+/// 与actix-web默认的连接队列长度保持一致
+const LISTEN_BACKLOG: i32 = 1024;
+
+/// 按照actix-web绑定地址的方式创建监听套接字。非Windows系统上开启地址复用，
+/// 重启服务时不会因为TIME_WAIT状态的连接绑定失败；绑定IPv6的任意地址时同时接受IPv4连接
+fn create_listener(addr: SocketAddr) -> io::Result<TcpListener> {
+    let socket = Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))?;
+    #[cfg(not(windows))]
+    socket.set_reuse_address(true)?;
+    if addr.is_ipv6() {
+        socket.set_only_v6(!addr.ip().is_unspecified())?;
+    }
+    socket.bind(&addr.into())?;
+    socket.listen(LISTEN_BACKLOG)?;
+    Ok(socket.into())
+}
+
 async fn start_server(
     settings: Settings,
+    listener: TcpListener,
     shutdown_signal_receiver: ShutdownSignalReceiver,
     datasource: Arc<SqliteDatabaseSource>,
+    access_control: Arc<AccessControl>,
+    bandwidth_limiter: Arc<BandwidthLimiter>,
+    notification_options: Arc<NotificationOptions>,
 ) -> std::io::Result<()> {
     let connection = datasource.clone().connection().await.unwrap();
     let settings = Arc::new(settings);
-    let port = settings.port;
+    let pin_limiter = web::Data::new(PinLimiter::default());
     HttpServer::new(move || {
         App::new()
+            .wrap(from_fn(throttle_upload_middleware))
+            .wrap(from_fn(access_control_middleware))
             .app_data(web::Data::new(connection.clone()))
             .app_data(web::Data::new(settings.clone()))
+            .app_data(web::Data::new(access_control.clone()))
+            .app_data(web::Data::new(bandwidth_limiter.clone()))
+            .app_data(web::Data::new(notification_options.clone()))
+            .app_data(pin_limiter.clone())
+            .configure(configure_extractors)
             .service(index)
-            .service(get_shares)
-            .service(stream_download)
-            .service(get_icon_for_mime_type)
-            .service(init_upload)
-            .service(upload_chunk)
-            .service(complete_upload)
+            .service(web::scope("/api/v1").configure(configure_api))
+            // 分页查询分享列表的接口最初挂载在`/api/shares`
+            .service(web::scope("/api").service(list_shares))
+            .configure(configure_api)
     })
     .shutdown_signal(receive_shutdown_signal(shutdown_signal_receiver))
-    .bind(format!("[::]:{}", port))?
+    .listen(listener)?
     .run()
     .await
 }
 
+/// 服务启动失败的原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StartServerError {
+    /// 端口已经被其他程序占用
+    PortInUse(u16),
+    /// 没有权限绑定端口，例如Linux上小于1024的端口
+    PermissionDenied(u16),
+    /// 绑定的地址不属于本机，例如网卡已经断开
+    AddressUnavailable(IpAddr),
+    Other(String),
+}
+
+impl StartServerError {
+    fn from_io_error(e: &io::Error, bind_address: IpAddr, port: u16) -> Self {
+        match e.kind() {
+            io::ErrorKind::AddrInUse => StartServerError::PortInUse(port),
+            io::ErrorKind::PermissionDenied => StartServerError::PermissionDenied(port),
+            io::ErrorKind::AddrNotAvailable => StartServerError::AddressUnavailable(bind_address),
+            _ => StartServerError::Other(e.to_string()),
+        }
+    }
+}
+
+impl Display for StartServerError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StartServerError::PortInUse(port) => write!(f, "port {port} is already in use"),
+            StartServerError::PermissionDenied(port) => {
+                write!(f, "permission denied to bind port {port}")
+            }
+            StartServerError::AddressUnavailable(addr) => {
+                write!(f, "address {addr} is not available")
+            }
+            StartServerError::Other(msg) => write!(f, "{msg}"),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
 pub enum ServerState {
     On,
@@ -136,6 +205,11 @@ pub struct ShareServer {
     runtime: tokio::runtime::Handle,
     server_state: Arc<Mutex<ServerState>>,
     shutdown_token: Option<ShutdownToken>,
+    /// 服务所在的线程，线程结束之后端口才会被释放
+    server_thread: Option<JoinHandle<()>>,
+    access_control: Arc<AccessControl>,
+    bandwidth_limiter: Arc<BandwidthLimiter>,
+    notification_options: Arc<NotificationOptions>,
 }
 
 impl ShareServer {
@@ -144,31 +218,68 @@ impl ShareServer {
             runtime,
             server_state: Arc::new(Mutex::new(Off)),
             shutdown_token: None,
+            server_thread: None,
+            access_control: Arc::new(AccessControl::default()),
+            bandwidth_limiter: Arc::new(BandwidthLimiter::default()),
+            notification_options: Arc::new(NotificationOptions::default()),
         }
     }
 
-    pub fn start(&mut self, settings: Settings, datasource: Arc<SqliteDatabaseSource>) {
+    /// 启动服务，在当前线程绑定端口，绑定失败时返回失败的原因
+    pub fn start(
+        &mut self,
+        settings: Settings,
+        datasource: Arc<SqliteDatabaseSource>,
+    ) -> Result<(), StartServerError> {
         let mut server_state = self.server_state.lock().unwrap();
         if *server_state == On {
             warn!("The backend is already up, do not start again!");
-            return;
+            return Ok(());
         }
 
+        let port = settings.port;
+        let bind_address = parse_bind_address(&settings.bind_address);
+        info!("Binding server to {}:{}", bind_address, port);
+        let listener = create_listener(SocketAddr::new(bind_address, port))
+            .map_err(|e| StartServerError::from_io_error(&e, bind_address, port))?;
+
         let (shutdown_token, shutdown_signal_receiver) = ShutdownToken::new();
         self.shutdown_token = Some(shutdown_token);
         *server_state = On;
+        self.reload_settings(&settings);
 
         let server_state = self.server_state.clone();
         let runtime = self.runtime.clone();
-        spawn(move || {
+        let access_control = self.access_control.clone();
+        let bandwidth_limiter = self.bandwidth_limiter.clone();
+        let notification_options = self.notification_options.clone();
+        self.server_thread = Some(spawn(move || {
             if let Err(e) = runtime.block_on(async {
-                start_server(settings, shutdown_signal_receiver, datasource).await
+                start_server(
+                    settings,
+                    listener,
+                    shutdown_signal_receiver,
+                    datasource,
+                    access_control,
+                    bandwidth_limiter,
+                    notification_options,
+                )
+                .await
             }) {
                 error!("Failed to start backend, {e}");
                 let mut server_state = server_state.lock().unwrap();
                 *server_state = Off;
             }
-        });
+        }));
+        Ok(())
+    }
+
+    /// Apply the settings which do not need a restart (access rules, rate and speed limits,
+    /// notifications) to the running server
+    pub fn reload_settings(&self, settings: &Settings) {
+        self.access_control.reload(settings);
+        self.bandwidth_limiter.reload(settings);
+        self.notification_options.reload(settings);
     }
 
     pub fn state(&self) -> ServerState {
@@ -176,16 +287,48 @@ impl ShareServer {
         *server_state
     }
 
-    pub fn stop(&mut self) {
+    /// 停止服务，返回服务所在的线程，需要重新绑定相同端口时应该等待线程结束
+    pub fn stop(&mut self) -> Option<JoinHandle<()>> {
         let mut server_state = self.server_state.lock().unwrap();
         if *server_state == Off {
             warn!("The backend is already down, do not stop again!");
-            return;
+            return None;
         }
 
         if let Some(shutdown_token) = self.shutdown_token.take() {
             shutdown_token.shutdown();
             *server_state = Off;
         }
+        self.server_thread.take()
     }
 }
+
+#[test]
+fn test_start_server_error_from_io_error() {
+    let addr = "192.168.1.2".parse::<IpAddr>().unwrap();
+    let error = io::Error::from(io::ErrorKind::AddrInUse);
+    assert_eq!(
+        StartServerError::PortInUse(12345),
+        StartServerError::from_io_error(&error, addr, 12345)
+    );
+    let error = io::Error::from(io::ErrorKind::PermissionDenied);
+    assert_eq!(
+        StartServerError::PermissionDenied(80),
+        StartServerError::from_io_error(&error, addr, 80)
+    );
+    let error = io::Error::from(io::ErrorKind::AddrNotAvailable);
+    assert_eq!(
+        StartServerError::AddressUnavailable(addr),
+        StartServerError::from_io_error(&error, addr, 12345)
+    );
+}
+
+#[test]
+fn test_create_listener() {
+    let listener = create_listener("127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = listener.local_addr().unwrap();
+    assert_ne!(0, addr.port());
+    assert!(std::net::TcpStream::connect(addr).is_ok());
+    let error = create_listener(addr).unwrap_err();
+    assert_eq!(io::ErrorKind::AddrInUse, error.kind());
+}
@@ -0,0 +1,244 @@
+use crate::setting::Settings;
+use actix_web::body::{BodySize, BodyStream, BoxBody, MessageBody, SizedStream};
+use actix_web::dev::{Payload, ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::web::Bytes;
+use actix_web::{Error, HttpMessage, HttpResponse, web};
+use futures::{Stream, StreamExt};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// 允许的突发流量时长，避免限速器在空闲之后一次性放行过多的数据
+const MAX_BURST: Duration = Duration::from_millis(200);
+
+/// 每次放行的最大时长对应的数据量，数据块过大时会被拆分，保证速度平滑
+const MAX_SLICE: Duration = Duration::from_millis(100);
+
+/// 数据块被拆分后的最小大小
+const MIN_SLICE_SIZE: usize = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Download,
+    Upload,
+}
+
+/// 基于“下一次可发送时间”的令牌桶
+struct TokenBucket {
+    next_free: Instant,
+}
+
+impl TokenBucket {
+    fn new() -> Self {
+        Self {
+            next_free: Instant::now(),
+        }
+    }
+
+    /// 预留`bytes`字节的发送额度，返回需要等待的时长，`rate`为0表示不限速
+    fn reserve(&mut self, bytes: usize, rate: u64, now: Instant) -> Duration {
+        if rate == 0 {
+            self.next_free = now;
+            return Duration::ZERO;
+        }
+        let start = self.next_free.max(now);
+        self.next_free = start + Duration::from_secs_f64(bytes as f64 / rate as f64);
+        self.next_free.saturating_duration_since(now + MAX_BURST)
+    }
+}
+
+/// 上传和下载的限速器，全局限速由所有连接共享，单连接限速由每个连接各自计算。
+/// 速度以字节/秒为单位，0表示不限速，可以在服务器运行时修改。
+pub struct BandwidthLimiter {
+    max_download_speed: AtomicU64,
+    max_download_speed_per_connection: AtomicU64,
+    max_upload_speed: AtomicU64,
+    max_upload_speed_per_connection: AtomicU64,
+    download_bucket: Mutex<TokenBucket>,
+    upload_bucket: Mutex<TokenBucket>,
+}
+
+impl Default for BandwidthLimiter {
+    fn default() -> Self {
+        Self {
+            max_download_speed: AtomicU64::new(0),
+            max_download_speed_per_connection: AtomicU64::new(0),
+            max_upload_speed: AtomicU64::new(0),
+            max_upload_speed_per_connection: AtomicU64::new(0),
+            download_bucket: Mutex::new(TokenBucket::new()),
+            upload_bucket: Mutex::new(TokenBucket::new()),
+        }
+    }
+}
+
+impl BandwidthLimiter {
+    pub fn reload(&self, settings: &Settings) {
+        let kib = |speed: u32| speed as u64 * 1024;
+        self.max_download_speed
+            .store(kib(settings.max_download_speed), Ordering::Relaxed);
+        self.max_download_speed_per_connection.store(
+            kib(settings.max_download_speed_per_connection),
+            Ordering::Relaxed,
+        );
+        self.max_upload_speed
+            .store(kib(settings.max_upload_speed), Ordering::Relaxed);
+        self.max_upload_speed_per_connection.store(
+            kib(settings.max_upload_speed_per_connection),
+            Ordering::Relaxed,
+        );
+    }
+
+    /// 返回(全局限速, 单连接限速)
+    fn speeds(&self, direction: Direction) -> (u64, u64) {
+        match direction {
+            Direction::Download => (
+                self.max_download_speed.load(Ordering::Relaxed),
+                self.max_download_speed_per_connection
+                    .load(Ordering::Relaxed),
+            ),
+            Direction::Upload => (
+                self.max_upload_speed.load(Ordering::Relaxed),
+                self.max_upload_speed_per_connection.load(Ordering::Relaxed),
+            ),
+        }
+    }
+
+    /// 计算单次放行的最大数据量
+    fn slice_size(&self, direction: Direction) -> usize {
+        let slowest = match self.speeds(direction) {
+            (0, 0) => return usize::MAX,
+            (0, speed) | (speed, 0) => speed,
+            (global, per_connection) => global.min(per_connection),
+        };
+        let slice_size = (slowest as f64 * MAX_SLICE.as_secs_f64()) as usize;
+        slice_size.max(MIN_SLICE_SIZE)
+    }
+
+    /// 为一个连接预留`bytes`字节的额度，返回需要等待的时长
+    fn reserve(
+        &self,
+        direction: Direction,
+        connection: &mut TokenBucket,
+        bytes: usize,
+    ) -> Duration {
+        let (global_speed, connection_speed) = self.speeds(direction);
+        let now = Instant::now();
+        let global_bucket = match direction {
+            Direction::Download => &self.download_bucket,
+            Direction::Upload => &self.upload_bucket,
+        };
+        let global_delay = global_bucket
+            .lock()
+            .unwrap()
+            .reserve(bytes, global_speed, now);
+        let connection_delay = connection.reserve(bytes, connection_speed, now);
+        global_delay.max(connection_delay)
+    }
+}
+
+/// 对数据流进行限速，超过限速的数据块会被拆分并延迟发送
+pub fn throttle_stream<S, E>(
+    stream: S,
+    limiter: Arc<BandwidthLimiter>,
+    direction: Direction,
+) -> impl Stream<Item = Result<Bytes, E>>
+where
+    S: Stream<Item = Result<Bytes, E>> + Unpin,
+{
+    let state = (stream, TokenBucket::new(), Bytes::new());
+    futures::stream::unfold(state, move |(mut stream, mut bucket, mut pending)| {
+        let limiter = limiter.clone();
+        async move {
+            if pending.is_empty() {
+                match stream.next().await? {
+                    Ok(bytes) => pending = bytes,
+                    Err(e) => return Some((Err(e), (stream, bucket, pending))),
+                }
+            }
+            let slice_size = limiter.slice_size(direction).min(pending.len());
+            let slice = pending.split_to(slice_size);
+            let delay = limiter.reserve(direction, &mut bucket, slice.len());
+            if !delay.is_zero() {
+                actix_web::rt::time::sleep(delay).await;
+            }
+            Some((Ok(slice), (stream, bucket, pending)))
+        }
+    })
+}
+
+/// 对响应体进行下载限速，保留原有的Content-Length
+pub fn throttle_response(
+    response: HttpResponse<BoxBody>,
+    limiter: Arc<BandwidthLimiter>,
+) -> HttpResponse<BoxBody> {
+    response.map_body(|_, body| {
+        let size = body.size();
+        if size == BodySize::None {
+            return body;
+        }
+        let mut body = Box::pin(body);
+        let stream = futures::stream::poll_fn(move |cx| body.as_mut().poll_next(cx));
+        let stream = Box::pin(throttle_stream(stream, limiter, Direction::Download));
+        match size {
+            BodySize::Sized(size) => BoxBody::new(SizedStream::new(size, stream)),
+            _ => BoxBody::new(BodyStream::new(stream)),
+        }
+    })
+}
+
+/// 上传限速中间件，通过延迟读取请求体来降低客户端的上传速度
+pub async fn throttle_upload_middleware(
+    mut req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    if let Some(limiter) = req.app_data::<web::Data<Arc<BandwidthLimiter>>>() {
+        let limiter = limiter.get_ref().clone();
+        let payload = req.take_payload();
+        let stream = throttle_stream(payload, limiter, Direction::Upload);
+        let stream: Pin<Box<dyn Stream<Item = _>>> = Box::pin(stream);
+        req.set_payload(Payload::from(stream));
+    }
+    next.call(req).await
+}
+
+#[test]
+fn test_token_bucket_reserve() {
+    let now = Instant::now();
+    let mut bucket = TokenBucket { next_free: now };
+
+    // 不限速时不需要等待
+    assert_eq!(Duration::ZERO, bucket.reserve(1024 * 1024, 0, now));
+
+    // 在突发额度内不需要等待
+    assert_eq!(Duration::ZERO, bucket.reserve(100, 1000, now));
+
+    // 超过突发额度之后需要等待
+    let delay = bucket.reserve(1000, 1000, now);
+    assert_eq!(Duration::from_millis(900), delay);
+}
+
+#[test]
+fn test_throttle_stream_keeps_content() {
+    let limiter = Arc::new(BandwidthLimiter::default());
+    limiter
+        .max_download_speed_per_connection
+        .store(100 * 1024, Ordering::Relaxed);
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    rt.block_on(async {
+        let data = Bytes::from(vec![7u8; 30 * 1024]);
+        let stream = futures::stream::iter(vec![Ok::<_, ()>(data.clone())]);
+        let chunks: Vec<Bytes> = throttle_stream(stream, limiter, Direction::Download)
+            .map(|chunk| chunk.unwrap())
+            .collect()
+            .await;
+        // 30KB的数据块在100KB/s的限速下按照10KB拆分
+        assert_eq!(3, chunks.len());
+        assert_eq!(data, chunks.concat());
+    });
+}
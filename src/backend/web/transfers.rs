@@ -0,0 +1,292 @@
+use crate::backend::db::repository::{
+    Share, Transfer, UploadItem, add_transfer_bytes, finish_transfer, get_latest_upload_transfer,
+    insert_transfer,
+};
+use crate::backend::web::handlers::devices::get_device_id;
+use crate::notification::{NotificationEvent, NotificationOptions, notify};
+use actix_web::body::{BodySize, BodyStream, BoxBody, MessageBody, SizedStream};
+use actix_web::http::{StatusCode, header};
+use actix_web::web::Bytes;
+use actix_web::{HttpRequest, HttpResponse, web};
+use chrono::Local;
+use futures::Stream;
+use log::error;
+use sea_orm::DatabaseConnection;
+use sea_orm::strum::{Display as StrumDisplay, EnumString};
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+/// 传输方向
+#[derive(EnumString, StrumDisplay, Debug, PartialEq, Clone, Copy)]
+pub enum TransferDirection {
+    Download,
+    Upload,
+}
+
+/// 传输结果
+#[derive(EnumString, StrumDisplay, Debug, PartialEq, Clone, Copy)]
+pub enum TransferResult {
+    InProgress,
+    Completed,
+    Failed,
+    /// 客户端在传输完成之前断开了连接
+    Aborted,
+}
+
+/// 发起传输的客户端信息
+#[derive(Debug, Clone)]
+pub struct ClientInfo {
+    pub client_ip: String,
+    pub user_agent: Option<String>,
+    pub device_id: Option<String>,
+}
+
+impl ClientInfo {
+    pub fn from_request(req: &HttpRequest) -> Self {
+        let client_ip = req
+            .peer_addr()
+            .map(|addr| addr.ip().to_canonical().to_string())
+            .unwrap_or_default();
+        let user_agent = req
+            .headers()
+            .get(header::USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string());
+        Self {
+            client_ip,
+            user_agent,
+            device_id: get_device_id(req),
+        }
+    }
+}
+
+/// 新建一条进行中的传输记录，返回记录的id
+async fn start_transfer(
+    connection: &DatabaseConnection,
+    direction: TransferDirection,
+    share_id: Option<i64>,
+    upload_id: Option<String>,
+    file_name: String,
+    client: ClientInfo,
+) -> anyhow::Result<i32> {
+    let transfer = Transfer {
+        id: 0,
+        direction: direction.to_string(),
+        share_id,
+        upload_id,
+        file_name,
+        client_ip: client.client_ip,
+        user_agent: client.user_agent,
+        device_id: client.device_id,
+        bytes: 0,
+        started_at: Local::now().naive_local(),
+        finished_at: None,
+        result: TransferResult::InProgress.to_string(),
+    };
+    let transfer = insert_transfer(connection, transfer).await?;
+    Ok(transfer.id)
+}
+
+/// 异步结束传输记录，不阻塞响应
+fn spawn_finish_transfer(
+    connection: DatabaseConnection,
+    transfer_id: i32,
+    bytes: Option<i64>,
+    result: TransferResult,
+) {
+    actix_web::rt::spawn(async move {
+        let result = result.to_string();
+        if let Err(e) = finish_transfer(&connection, transfer_id, bytes, &result).await {
+            error!("Failed to finish transfer {transfer_id}, {e}");
+        }
+    });
+}
+
+/// 下载完成时通知的文件和客户端地址
+struct DownloadNotification {
+    options: Arc<NotificationOptions>,
+    file_name: String,
+    file_path: String,
+    client_ip: String,
+}
+
+/// 统计已发送字节数的响应体，在结束或者被丢弃时更新传输记录
+struct TrackedStream<S> {
+    stream: S,
+    connection: DatabaseConnection,
+    transfer_id: i32,
+    bytes: i64,
+    finished: bool,
+    /// 为None时下载完成后不发送通知
+    notification: Option<DownloadNotification>,
+}
+
+impl<S> TrackedStream<S> {
+    fn finish(&mut self, result: TransferResult) {
+        if self.finished {
+            return;
+        }
+        self.finished = true;
+        if result == TransferResult::Completed
+            && let Some(notification) = self.notification.take()
+        {
+            notify(
+                &notification.options,
+                NotificationEvent::Download,
+                &notification.file_name,
+                &notification.client_ip,
+                Some(notification.file_path),
+            );
+        }
+        spawn_finish_transfer(
+            self.connection.clone(),
+            self.transfer_id,
+            Some(self.bytes),
+            result,
+        );
+    }
+}
+
+impl<S, E> Stream for TrackedStream<S>
+where
+    S: Stream<Item = Result<Bytes, E>> + Unpin,
+{
+    type Item = Result<Bytes, E>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let poll = Pin::new(&mut self.stream).poll_next(cx);
+        match &poll {
+            Poll::Ready(Some(Ok(bytes))) => self.bytes += bytes.len() as i64,
+            Poll::Ready(Some(Err(_))) => self.finish(TransferResult::Failed),
+            Poll::Ready(None) => self.finish(TransferResult::Completed),
+            Poll::Pending => {}
+        }
+        poll
+    }
+}
+
+impl<S> Drop for TrackedStream<S> {
+    fn drop(&mut self) {
+        self.finish(TransferResult::Aborted);
+    }
+}
+
+/// 记录一次下载，响应体发送完成之后更新传输记录并发送通知。
+/// 只记录带有响应体的成功响应，HEAD请求、304等不会被记录。
+pub async fn track_download(
+    req: &HttpRequest,
+    response: HttpResponse<BoxBody>,
+    connection: &DatabaseConnection,
+    share: Share,
+) -> HttpResponse<BoxBody> {
+    if !response.status().is_success() || response.body().size() == BodySize::None {
+        return response;
+    }
+    let client = ClientInfo::from_request(req);
+    // 断点续传或者在线播放时会发出很多范围请求，只在下载完整文件时发送通知
+    let notification = req
+        .app_data::<web::Data<Arc<NotificationOptions>>>()
+        .filter(|_| response.status() == StatusCode::OK)
+        .map(|options| DownloadNotification {
+            options: options.get_ref().clone(),
+            file_name: share.file_name.clone(),
+            file_path: share.file_path,
+            client_ip: client.client_ip.clone(),
+        });
+    let transfer_id = match start_transfer(
+        connection,
+        TransferDirection::Download,
+        Some(share.id),
+        None,
+        share.file_name,
+        client,
+    )
+    .await
+    {
+        Ok(transfer_id) => transfer_id,
+        Err(e) => {
+            error!("Failed to record download, {e}");
+            return response;
+        }
+    };
+
+    let connection = connection.clone();
+    response.map_body(move |_, body| {
+        let size = body.size();
+        let mut body = Box::pin(body);
+        let stream = futures::stream::poll_fn(move |cx| body.as_mut().poll_next(cx));
+        let stream = TrackedStream {
+            stream: Box::pin(stream),
+            connection,
+            transfer_id,
+            bytes: 0,
+            finished: false,
+            notification,
+        };
+        match size {
+            BodySize::Sized(size) => BoxBody::new(SizedStream::new(size, stream)),
+            _ => BoxBody::new(BodyStream::new(stream)),
+        }
+    })
+}
+
+/// 获取上传项进行中的传输记录，不存在时新建一条，返回记录的id。
+/// 断点续传时会为每次续传新建一条记录。
+async fn ensure_upload_transfer(
+    connection: &DatabaseConnection,
+    upload_item: &UploadItem,
+    client: ClientInfo,
+) -> anyhow::Result<i32> {
+    if let Some(transfer) = get_latest_upload_transfer(connection, &upload_item.id).await?
+        && TransferResult::from_str(&transfer.result) == Ok(TransferResult::InProgress)
+    {
+        return Ok(transfer.id);
+    }
+    start_transfer(
+        connection,
+        TransferDirection::Upload,
+        None,
+        Some(upload_item.id.clone()),
+        upload_item.file_name.clone(),
+        client,
+    )
+    .await
+}
+
+/// 记录上传的分片
+pub async fn track_upload_chunk(
+    req: &HttpRequest,
+    connection: &DatabaseConnection,
+    upload_item: &UploadItem,
+    chunk_size: i64,
+) {
+    let client = ClientInfo::from_request(req);
+    let result = async {
+        let transfer_id = ensure_upload_transfer(connection, upload_item, client).await?;
+        add_transfer_bytes(connection, transfer_id, chunk_size).await
+    }
+    .await;
+    if let Err(e) = result {
+        error!("Failed to record upload chunk, {e}");
+    }
+}
+
+/// 上传完成或者合并失败时结束传输记录
+pub async fn track_upload_finished(
+    req: &HttpRequest,
+    connection: &DatabaseConnection,
+    upload_item: &UploadItem,
+    result: TransferResult,
+) {
+    let client = ClientInfo::from_request(req);
+    let result = async {
+        let transfer_id = ensure_upload_transfer(connection, upload_item, client).await?;
+        finish_transfer(connection, transfer_id, None, &result.to_string()).await
+    }
+    .await;
+    if let Err(e) = result {
+        error!("Failed to record upload result, {e}");
+    }
+}
@@ -1,65 +1,85 @@
+use std::env;
 use std::env::args;
 use std::process;
 
 mod assets;
+mod autostart;
 mod backend;
 mod gpui_tokio;
 mod migrator;
 mod mimes;
+mod network;
+mod notification;
+mod overrides;
+mod profile;
 mod setting;
 mod single_instance;
+mod thumbnail;
+mod tray;
 mod ui;
 mod util;
 
 use crate::assets::Assets;
 use crate::backend::db::DatabaseSource;
-use crate::backend::entities::shares;
+use crate::backend::db::repository::insert_share;
+use crate::backend::watcher::ShareWatcher;
 use crate::backend::web::server::ShareServer;
 use crate::gpui_tokio::Tokio;
-use crate::mimes::detect_mime_type;
-use crate::setting::configuration_dir;
+use crate::overrides::{CommandLine, Overrides};
+use crate::profile::active_profile;
+use crate::setting::{Settings, database_file};
 use crate::single_instance::NextStep::{Abort, Continue};
 use crate::single_instance::{OpenRequest, check_single_instance};
+use crate::tray::{TrayCommand, TrayIcon};
+use crate::ui::blocked_requests_page::BlockedRequestsPage;
+use crate::ui::collections_page::CollectionsPage;
 use crate::ui::file_list_page::FileListPage;
+use crate::ui::history_page::HistoryPage;
+use crate::ui::profiles_page::ProfilesPage;
 use crate::ui::root_view::{Pages, WindowRootView};
 use crate::ui::server_info_page::ServerInfoPage;
 use crate::ui::settings_page::SettingsPage;
-use crate::ui::title_bar::TitleBar;
+use crate::ui::title_bar::{TitleBar, switch_server};
+use crate::ui::toast::Toast;
 use futures::StreamExt;
 use gpui::{
-    App, Application, AsyncApp, Bounds, ReadGlobal, WindowBackgroundAppearance, WindowBounds,
-    WindowDecorations, WindowOptions, prelude::*, px, size,
+    App, Application, AsyncApp, Bounds, ReadGlobal, UpdateGlobal, WindowBackgroundAppearance,
+    WindowBounds, WindowDecorations, WindowHandle, WindowOptions, prelude::*, px, size,
 };
 use gpui_component::{Theme, ThemeMode};
 use log::{error, info};
-use sea_orm::{ActiveModelTrait, IntoActiveModel};
+use rust_i18n::t;
 use snowflaked::sync::Generator;
+use std::time::Duration;
 
 rust_i18n::i18n!("locales", fallback = "en");
 
 fn handle_open_args(args: Vec<String>, cx: &mut App) {
-    if args.len() > 1 {
+    // The overrides only apply at startup, they are ignored when another instance forwards them
+    let files = match CommandLine::parse(&args, Overrides::default()) {
+        Ok(command_line) => command_line.files,
+        Err(e) => {
+            error!("Invalid arguments {:?}, {e}", args);
+            return;
+        }
+    };
+    if !files.is_empty() {
         let db = DatabaseSource::global(cx);
         let sqlite = db.instance.clone();
         cx.spawn(async move |cx: &mut AsyncApp| {
             Tokio::spawn(cx, async move {
-                // args[1] is program name
-                for file in args.iter().skip(1) {
+                for file in files.iter() {
                     match async_fs::canonicalize(&file).await {
                         Ok(path) => {
                             let connection = sqlite.clone().connection().await.unwrap();
 
                             let generator = Generator::new(0);
 
-                            let file_path = path.to_str().unwrap().to_string();
-                            let model: shares::ActiveModel = shares::Model {
-                                id: generator.generate(),
-                                file_name: path.file_name().unwrap().to_str().unwrap().to_string(),
-                                mime_type: detect_mime_type(&file_path),
-                                file_path,
+                            if let Err(e) =
+                                insert_share(&connection, generator.generate(), &path).await
+                            {
+                                error!("Failed to insert share file: {}", e);
                             }
-                            .into_active_model();
-                            model.insert(&connection).await.unwrap();
                         }
                         Err(_) => {
                             error!("Failed to canonicalize path: {}", file);
@@ -79,9 +99,93 @@ fn handle_open_args(args: Vec<String>, cx: &mut App) {
     }
 }
 
+/// Reload the share list when shared files are deleted, moved or modified.
+/// Events are debounced since saving a file usually produces several of them.
+fn watch_shared_files(cx: &mut App) {
+    let (watcher, mut receiver) = match ShareWatcher::new() {
+        Ok(result) => result,
+        Err(e) => {
+            error!("Failed to create share watcher, {e}");
+            return;
+        }
+    };
+    cx.set_global(watcher);
+    cx.spawn(async move |cx: &mut AsyncApp| {
+        while receiver.next().await.is_some() {
+            cx.background_executor()
+                .timer(Duration::from_millis(500))
+                .await;
+            while let Ok(Some(_)) = receiver.try_next() {}
+            if cx
+                .update(|cx: &mut App| FileListPage::check_and_reload(cx))
+                .is_err()
+            {
+                break;
+            }
+        }
+    })
+    .detach();
+}
+
+fn show_window(window: WindowHandle<WindowRootView>, cx: &mut App) {
+    if let Err(e) = window.update(cx, |_, window, _| window.activate_window()) {
+        error!("Failed to show window, {e}");
+    }
+}
+
+/// Show the tray icon and handle the requests from its menu.
+/// Without a status notifier host the app runs without the tray.
+fn show_tray_icon(window: WindowHandle<WindowRootView>, cx: &mut App) {
+    let server_state = ShareServer::global(cx).state();
+    cx.spawn(async move |cx: &mut AsyncApp| {
+        let result = Tokio::spawn(cx, TrayIcon::spawn(server_state))
+            .unwrap()
+            .await
+            .unwrap();
+        let (tray_icon, mut receiver) = match result {
+            Ok(result) => result,
+            Err(e) => {
+                error!("Failed to show tray icon, {e:?}");
+                return;
+            }
+        };
+        if cx.update(|cx: &mut App| cx.set_global(tray_icon)).is_err() {
+            return;
+        }
+
+        while let Some(command) = receiver.next().await {
+            let result = cx.update(|cx: &mut App| match command {
+                TrayCommand::ShowWindow => show_window(window, cx),
+                TrayCommand::ShowQrCode => {
+                    Pages::set_global(cx, Pages::ServerInfoPage);
+                    show_window(window, cx);
+                }
+                TrayCommand::SwitchServer(is_on) => switch_server(is_on, cx),
+                TrayCommand::Quit => cx.quit(),
+            });
+            if result.is_err() {
+                break;
+            }
+        }
+    })
+    .detach();
+}
+
 fn main() {
     env_logger::init();
 
+    let args: Vec<String> = args().collect();
+    let command_line = match Overrides::from_env(|name| env::var(name).ok())
+        .and_then(|overrides| CommandLine::parse(&args, overrides))
+    {
+        Ok(command_line) => command_line,
+        Err(e) => {
+            error!("Invalid arguments, {e:#}");
+            process::exit(2);
+        }
+    };
+    Overrides::init(command_line.overrides);
+
     let mut open_request_receiver = match check_single_instance() {
         Ok(next_step) => match next_step {
             Continue(rx) => rx,
@@ -96,62 +200,97 @@ fn main() {
         }
     };
 
-    let db_file = configuration_dir().join("data.db");
+    let db_file = database_file(&active_profile());
     let db_source = DatabaseSource::new(db_file.to_str().unwrap());
 
-    Application::new().with_assets(Assets).run(|cx: &mut App| {
-        gpui_component::init(cx);
-        gpui_tokio::init(cx);
-        cx.set_global(ShareServer::new(Tokio::handle(cx)));
-
-        setting::Settings::init();
-        cx.set_global::<DatabaseSource>(db_source);
-
-        let bounds = Bounds::centered(None, size(px(800.0), px(600.0)), cx);
-        cx.open_window(
-            WindowOptions {
-                window_bounds: Some(WindowBounds::Windowed(bounds)),
-                window_background: WindowBackgroundAppearance::Opaque,
-                window_decorations: Some(WindowDecorations::Client),
-                ..Default::default()
-            },
-            |window, cx| {
-                cx.new(|cx| {
-                    cx.observe_window_appearance(window, |_, window, cx| {
-                        Theme::change(ThemeMode::from(window.appearance()), None, cx);
-                        window.refresh();
-                    })
-                    .detach();
-
-                    let title_bar = TitleBar::new(cx);
-                    let file_list_page = FileListPage::new(cx);
-                    FileListPage::reload(cx);
-                    let settings_page = SettingsPage::new(window, cx);
-                    cx.set_global::<Pages>(Pages::FileListPage);
-                    let server_info_page = ServerInfoPage::new(window, cx);
-
-                    WindowRootView {
-                        title_bar,
-                        file_list_page,
-                        settings_page,
-                        server_info_page,
-                    }
-                })
-            },
-        )
-        .unwrap();
-
-        handle_open_args(args().collect(), cx);
-        cx.spawn(async move |cx| {
-            while let Some(open_request) = open_request_receiver.next().await {
-                cx.update(|cx: &mut App| {
-                    let OpenRequest { args } = open_request;
-                    info!("open_request.args: {:?}", &args);
-                    handle_open_args(args, cx);
-                })
-                .ok();
+    Application::new()
+        .with_assets(Assets)
+        .run(move |cx: &mut App| {
+            gpui_component::init(cx);
+            gpui_tokio::init(cx);
+            cx.set_global(ShareServer::new(Tokio::handle(cx)));
+
+            setting::Settings::init();
+            cx.set_global::<DatabaseSource>(db_source);
+            watch_shared_files(cx);
+            if Settings::clone().start_server_on_launch {
+                switch_server(true, cx);
             }
-        })
-        .detach();
-    });
+
+            let bounds = Bounds::centered(None, size(px(800.0), px(600.0)), cx);
+            let window = cx
+                .open_window(
+                    WindowOptions {
+                        window_bounds: Some(WindowBounds::Windowed(bounds)),
+                        window_background: WindowBackgroundAppearance::Opaque,
+                        window_decorations: Some(WindowDecorations::Client),
+                        ..Default::default()
+                    },
+                    |window, cx| {
+                        window.on_window_should_close(cx, |window, cx| {
+                            if TrayIcon::minimizes_on_close(cx) {
+                                window.minimize_window();
+                                return false;
+                            }
+                            true
+                        });
+                        cx.new(|cx| {
+                            cx.observe_window_appearance(window, |_, window, cx| {
+                                Theme::change(ThemeMode::from(window.appearance()), None, cx);
+                                window.refresh();
+                            })
+                            .detach();
+
+                            let title_bar = TitleBar::new(cx);
+                            let file_list_page = FileListPage::new(cx);
+                            FileListPage::check_and_reload(cx);
+                            let settings_page = SettingsPage::new(window, cx);
+                            cx.set_global::<Pages>(Pages::FileListPage);
+                            let server_info_page = ServerInfoPage::new(window, cx);
+                            let blocked_requests_page = BlockedRequestsPage::new(cx);
+                            let history_page = HistoryPage::new(cx);
+                            let collections_page = CollectionsPage::new(cx);
+                            let profiles_page = ProfilesPage::new(settings_page.clone(), cx);
+
+                            WindowRootView {
+                                title_bar,
+                                file_list_page,
+                                settings_page,
+                                server_info_page,
+                                blocked_requests_page,
+                                history_page,
+                                collections_page,
+                                profiles_page,
+                            }
+                        })
+                    },
+                )
+                .unwrap();
+            if command_line.minimized {
+                window
+                    .update(cx, |_, window, _| window.minimize_window())
+                    .ok();
+            }
+            show_tray_icon(window, cx);
+            if let Some(backup) = Settings::corrupt_backup() {
+                Toast::show(
+                    t!("toast.settings-corrupt", backup = backup.display()).into_owned(),
+                    None,
+                    cx,
+                );
+            }
+
+            handle_open_args(args, cx);
+            cx.spawn(async move |cx| {
+                while let Some(open_request) = open_request_receiver.next().await {
+                    cx.update(|cx: &mut App| {
+                        let OpenRequest { args } = open_request;
+                        info!("open_request.args: {:?}", &args);
+                        handle_open_args(args, cx);
+                    })
+                    .ok();
+                }
+            })
+            .detach();
+        });
 }
@@ -0,0 +1,60 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261018_000001_create_blocked_requests_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Create the BlockedRequests table.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(BlockedRequests::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(BlockedRequests::Id)
+                            .integer()
+                            .not_null()
+                            .primary_key()
+                            .auto_increment(),
+                    )
+                    .col(
+                        ColumnDef::new(BlockedRequests::ClientIp)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(BlockedRequests::Path).string().not_null())
+                    .col(ColumnDef::new(BlockedRequests::Reason).string().not_null())
+                    .col(
+                        ColumnDef::new(BlockedRequests::CreatedAt)
+                            .date_time()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    // Define how to rollback this migration: Drop the BlockedRequests table.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(BlockedRequests::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum BlockedRequests {
+    Table,
+    Id,
+    ClientIp,
+    Path,
+    Reason,
+    CreatedAt,
+}
@@ -0,0 +1,75 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261018_000002_create_transfers_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Create the Transfers table.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Transfers::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Transfers::Id)
+                            .integer()
+                            .not_null()
+                            .primary_key()
+                            .auto_increment(),
+                    )
+                    .col(ColumnDef::new(Transfers::Direction).string().not_null())
+                    .col(ColumnDef::new(Transfers::ShareId).big_integer().null())
+                    .col(ColumnDef::new(Transfers::UploadId).string().null())
+                    .col(ColumnDef::new(Transfers::FileName).string().not_null())
+                    .col(ColumnDef::new(Transfers::ClientIp).string().not_null())
+                    .col(ColumnDef::new(Transfers::UserAgent).string().null())
+                    .col(ColumnDef::new(Transfers::Bytes).big_integer().not_null())
+                    .col(ColumnDef::new(Transfers::StartedAt).date_time().not_null())
+                    .col(ColumnDef::new(Transfers::FinishedAt).date_time().null())
+                    .col(ColumnDef::new(Transfers::Result).string().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // The history page always lists the latest transfers first
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_transfers_started_at")
+                    .table(Transfers::Table)
+                    .col(Transfers::StartedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    // Define how to rollback this migration: Drop the Transfers table.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Transfers::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Transfers {
+    Table,
+    Id,
+    Direction,
+    ShareId,
+    UploadId,
+    FileName,
+    ClientIp,
+    UserAgent,
+    Bytes,
+    StartedAt,
+    FinishedAt,
+    Result,
+}
@@ -0,0 +1,77 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261018_000003_create_devices_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Create the Devices table and link transfers to devices.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Devices::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Devices::Id)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Devices::Name).string().not_null())
+                    .col(ColumnDef::new(Devices::ClientIp).string().not_null())
+                    .col(ColumnDef::new(Devices::UserAgent).string().null())
+                    .col(ColumnDef::new(Devices::CreatedAt).date_time().not_null())
+                    .col(ColumnDef::new(Devices::UpdatedAt).date_time().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transfers::Table)
+                    .add_column(ColumnDef::new(Transfers::DeviceId).string().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    // Define how to rollback this migration: Drop the Devices table.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transfers::Table)
+                    .drop_column(Transfers::DeviceId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Devices::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Devices {
+    Table,
+    Id,
+    Name,
+    ClientIp,
+    UserAgent,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+enum Transfers {
+    Table,
+    DeviceId,
+}
@@ -0,0 +1,69 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261018_000004_add_metadata_to_shares"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Add the file metadata columns to the Shares table.
+    // SQLite only supports one column per ALTER TABLE statement.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Shares::Table)
+                    .add_column(
+                        ColumnDef::new(Shares::FileSize)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Shares::Table)
+                    .add_column(ColumnDef::new(Shares::ModifiedAt).date_time().null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Shares::Table)
+                    .add_column(ColumnDef::new(Shares::Sha256).string().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    // Define how to rollback this migration: Drop the metadata columns.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [Shares::FileSize, Shares::ModifiedAt, Shares::Sha256] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Shares::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum Shares {
+    Table,
+    FileSize,
+    ModifiedAt,
+    Sha256,
+}
@@ -0,0 +1,103 @@
+use crate::migrator::m20250816_000002_create_shares_table::Shares;
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261018_000006_create_collections_table"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Create the Collections table and the table linking collections to shares.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Collections::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Collections::Id)
+                            .big_integer()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Collections::Name).string().not_null())
+                    .col(ColumnDef::new(Collections::Pin).string().null())
+                    .col(
+                        ColumnDef::new(Collections::CreatedAt)
+                            .date_time()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(CollectionShares::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(CollectionShares::CollectionId)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CollectionShares::ShareId)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(CollectionShares::CollectionId)
+                            .col(CollectionShares::ShareId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_collection_shares_collection_id")
+                            .from(CollectionShares::Table, CollectionShares::CollectionId)
+                            .to(Collections::Table, Collections::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_collection_shares_share_id")
+                            .from(CollectionShares::Table, CollectionShares::ShareId)
+                            .to(Shares::Table, Shares::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    // Define how to rollback this migration: Drop the CollectionShares and Collections tables.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(CollectionShares::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Collections::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Collections {
+    Table,
+    Id,
+    Name,
+    Pin,
+    CreatedAt,
+}
+
+#[derive(Iden)]
+pub enum CollectionShares {
+    Table,
+    CollectionId,
+    ShareId,
+}
@@ -0,0 +1,125 @@
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20261018_000007_add_access_tokens"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    // Define how to apply this migration: Add the AccessToken column to the Shares and Collections tables.
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Shares::Table)
+                    .add_column(
+                        ColumnDef::new(Shares::AccessToken)
+                            .string()
+                            .not_null()
+                            .default(""),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Collections::Table)
+                    .add_column(
+                        ColumnDef::new(Collections::AccessToken)
+                            .string()
+                            .not_null()
+                            .default(""),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // 已有的分享项和集合生成随机的访问令牌，格式与`generate_access_token`一致
+        let connection = manager.get_connection();
+        connection
+            .execute_unprepared("UPDATE shares SET access_token = lower(hex(randomblob(16)))")
+            .await?;
+        connection
+            .execute_unprepared("UPDATE collections SET access_token = lower(hex(randomblob(16)))")
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .unique()
+                    .name("idx_shares_access_token")
+                    .table(Shares::Table)
+                    .col(Shares::AccessToken)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .unique()
+                    .name("idx_collections_access_token")
+                    .table(Collections::Table)
+                    .col(Collections::AccessToken)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    // Define how to rollback this migration: Drop the AccessToken columns.
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_collections_access_token")
+                    .table(Collections::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_shares_access_token")
+                    .table(Shares::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Collections::Table)
+                    .drop_column(Collections::AccessToken)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Shares::Table)
+                    .drop_column(Shares::AccessToken)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum Shares {
+    Table,
+    AccessToken,
+}
+
+#[derive(Iden)]
+enum Collections {
+    Table,
+    AccessToken,
+}
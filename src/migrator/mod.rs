@@ -1,6 +1,14 @@
 mod m20250816_000002_create_chunks_table;
 mod m20250816_000002_create_shares_table;
 mod m20250816_000002_create_uploads_table;
+mod m20261018_000001_create_blocked_requests_table;
+mod m20261018_000002_create_transfers_table;
+mod m20261018_000003_create_devices_table;
+mod m20261018_000004_add_metadata_to_shares;
+mod m20261018_000005_add_status_to_shares;
+mod m20261018_000006_create_collections_table;
+mod m20261018_000007_add_access_tokens;
+mod m20261018_000008_add_expires_at_to_shares;
 
 use sea_orm::Database;
 use sea_orm_migration::prelude::*;
@@ -14,6 +22,14 @@ impl MigratorTrait for Migrator {
             Box::new(m20250816_000002_create_shares_table::Migration),
             Box::new(m20250816_000002_create_uploads_table::Migration),
             Box::new(m20250816_000002_create_chunks_table::Migration),
+            Box::new(m20261018_000001_create_blocked_requests_table::Migration),
+            Box::new(m20261018_000002_create_transfers_table::Migration),
+            Box::new(m20261018_000003_create_devices_table::Migration),
+            Box::new(m20261018_000004_add_metadata_to_shares::Migration),
+            Box::new(m20261018_000005_add_status_to_shares::Migration),
+            Box::new(m20261018_000006_create_collections_table::Migration),
+            Box::new(m20261018_000007_add_access_tokens::Migration),
+            Box::new(m20261018_000008_add_expires_at_to_shares::Migration),
         ]
     }
 }
@@ -40,5 +56,36 @@ fn test_create_sqlite_database_file() {
         assert!(schema_manager.has_table("shares").await.unwrap());
         assert!(schema_manager.has_table("uploads").await.unwrap());
         assert!(schema_manager.has_table("chunks").await.unwrap());
+        assert!(schema_manager.has_table("blocked_requests").await.unwrap());
+        assert!(schema_manager.has_table("transfers").await.unwrap());
+        assert!(schema_manager.has_table("devices").await.unwrap());
+        assert!(schema_manager.has_table("collections").await.unwrap());
+        assert!(schema_manager.has_table("collection_shares").await.unwrap());
+        assert!(
+            schema_manager
+                .has_column("transfers", "device_id")
+                .await
+                .unwrap()
+        );
+        assert!(schema_manager.has_column("shares", "sha256").await.unwrap());
+        assert!(schema_manager.has_column("shares", "status").await.unwrap());
+        assert!(
+            schema_manager
+                .has_column("shares", "access_token")
+                .await
+                .unwrap()
+        );
+        assert!(
+            schema_manager
+                .has_column("collections", "access_token")
+                .await
+                .unwrap()
+        );
+        assert!(
+            schema_manager
+                .has_column("shares", "expires_at")
+                .await
+                .unwrap()
+        );
     });
 }
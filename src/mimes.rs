@@ -1,6 +1,91 @@
 use mime_guess2::MimeGuess;
+use sea_orm::strum::{Display as StrumDisplay, EnumString};
 use std::sync::Arc;
 
+/// Coarse groups of mime types used to filter the share list
+#[derive(EnumString, StrumDisplay, Debug, PartialEq, Clone, Copy)]
+#[strum(serialize_all = "lowercase")]
+pub enum MimeCategory {
+    Image,
+    Video,
+    Audio,
+    Text,
+    Document,
+    Archive,
+    /// Anything not in the other categories
+    Other,
+}
+
+/// Mime types of office documents, e-books and pdf files
+pub const DOCUMENT_MIME_TYPES: [&str; 12] = [
+    "application/pdf",
+    "application/msword",
+    "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+    "application/vnd.ms-excel",
+    "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+    "application/vnd.ms-powerpoint",
+    "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+    "application/vnd.oasis.opendocument.text",
+    "application/vnd.oasis.opendocument.spreadsheet",
+    "application/vnd.oasis.opendocument.presentation",
+    "application/rtf",
+    "application/epub+zip",
+];
+
+/// Mime types of compressed archives
+pub const ARCHIVE_MIME_TYPES: [&str; 9] = [
+    "application/zip",
+    "application/x-tar",
+    "application/gzip",
+    "application/x-bzip2",
+    "application/x-xz",
+    "application/x-7z-compressed",
+    "application/vnd.rar",
+    "application/x-rar-compressed",
+    "application/zstd",
+];
+
+impl MimeCategory {
+    /// The prefix shared by all mime types in the category, e.g. `image/`
+    pub fn mime_prefix(&self) -> Option<&'static str> {
+        match self {
+            MimeCategory::Image => Some("image/"),
+            MimeCategory::Video => Some("video/"),
+            MimeCategory::Audio => Some("audio/"),
+            MimeCategory::Text => Some("text/"),
+            _ => None,
+        }
+    }
+
+    /// The exact mime types in the category, for categories without a common prefix
+    pub fn mime_types(&self) -> &'static [&'static str] {
+        match self {
+            MimeCategory::Document => &DOCUMENT_MIME_TYPES,
+            MimeCategory::Archive => &ARCHIVE_MIME_TYPES,
+            _ => &[],
+        }
+    }
+
+    pub fn of(mime: &str) -> MimeCategory {
+        [
+            MimeCategory::Image,
+            MimeCategory::Video,
+            MimeCategory::Audio,
+            MimeCategory::Text,
+            MimeCategory::Document,
+            MimeCategory::Archive,
+        ]
+        .into_iter()
+        .find(|category| {
+            category
+                .mime_prefix()
+                .is_some_and(|prefix| mime.starts_with(prefix))
+                || category.mime_types().contains(&mime)
+        })
+        .unwrap_or(MimeCategory::Other)
+    }
+}
+
 pub fn get_icon_for_mime(mime: &str) -> Arc<str> {
     let file_name = match mime {
         "android/package-archive" => "android-package-archive.svg",
@@ -345,3 +430,18 @@ pub fn detect_mime_type(file_path: &str) -> String {
             }
         })
 }
+
+#[test]
+fn test_mime_category() {
+    use std::str::FromStr;
+
+    assert_eq!(MimeCategory::Image, MimeCategory::of("image/png"));
+    assert_eq!(MimeCategory::Document, MimeCategory::of("application/pdf"));
+    assert_eq!(MimeCategory::Archive, MimeCategory::of("application/zip"));
+    assert_eq!(MimeCategory::Other, MimeCategory::of("unknown"));
+    assert_eq!(
+        MimeCategory::Video,
+        MimeCategory::from_str("video").unwrap()
+    );
+    assert_eq!("audio", MimeCategory::Audio.to_string());
+}
@@ -0,0 +1,165 @@
+use local_ip_address::{list_afinet_netifas, local_ip};
+use log::error;
+use std::iter;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, TcpListener};
+
+/// Bind address meaning "all interfaces"
+pub const ANY_ADDRESS: &str = "::";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetworkInterface {
+    /// Interface name, e.g. `wlan0`, `docker0`
+    pub name: String,
+    pub addr: IpAddr,
+}
+
+impl NetworkInterface {
+    pub fn label(&self) -> String {
+        format!("{} ({})", self.addr, self.name)
+    }
+}
+
+/// List all network interfaces with an IPv4/IPv6 address.
+/// Returns an empty list if the interfaces can not be enumerated.
+pub fn list_network_interfaces() -> Vec<NetworkInterface> {
+    match list_afinet_netifas() {
+        Ok(interfaces) => {
+            let mut interfaces: Vec<NetworkInterface> = interfaces
+                .into_iter()
+                .map(|(name, addr)| NetworkInterface { name, addr })
+                .collect();
+            // Show the most useful addresses first: IPv4 before IPv6, loopback last
+            interfaces.sort_by_key(|interface| {
+                (
+                    interface.addr.is_loopback(),
+                    interface.addr.is_ipv6(),
+                    interface.name.clone(),
+                )
+            });
+            interfaces
+        }
+        Err(e) => {
+            error!("Failed to list network interfaces, {e}");
+            vec![]
+        }
+    }
+}
+
+/// Resolve the address the server should be advertised with (QR code / URL).
+///
+/// A server bound to a single address is only reachable there, so that address is advertised.
+/// Otherwise the preferred address is used if it still belongs to one of the interfaces,
+/// falling back to the system's default route address and finally to loopback,
+/// so that a machine without network never panics.
+pub fn resolve_advertise_address(
+    bind_address: &str,
+    preferred: Option<&str>,
+    interfaces: &[NetworkInterface],
+) -> IpAddr {
+    if let Ok(bind_address) = bind_address.parse::<IpAddr>()
+        && !bind_address.is_unspecified()
+    {
+        return bind_address;
+    }
+    if let Some(preferred) = preferred.and_then(|addr| addr.parse::<IpAddr>().ok())
+        && interfaces
+            .iter()
+            .any(|interface| interface.addr == preferred)
+    {
+        return preferred;
+    }
+    if let Ok(addr) = local_ip() {
+        return addr;
+    }
+    interfaces
+        .iter()
+        .map(|interface| interface.addr)
+        .find(|addr| !addr.is_loopback())
+        .unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST))
+}
+
+/// Parse the configured bind address, falling back to all interfaces if it is invalid
+pub fn parse_bind_address(bind_address: &str) -> IpAddr {
+    match bind_address.parse::<IpAddr>() {
+        Ok(addr) => addr,
+        Err(e) => {
+            error!("Invalid bind address {bind_address}, {e}");
+            IpAddr::V6(Ipv6Addr::UNSPECIFIED)
+        }
+    }
+}
+
+/// Find a port the server can bind to. The ports after `port` are tried first so that the
+/// suggestion stays close to the configured port, then any port the system picks.
+pub fn find_free_port(bind_address: IpAddr, port: u16) -> Option<u16> {
+    (port.saturating_add(1)..=port.saturating_add(100))
+        .chain(iter::once(0))
+        .find_map(|port| TcpListener::bind((bind_address, port)).ok())
+        .and_then(|listener| listener.local_addr().ok())
+        .map(|addr| addr.port())
+}
+
+/// Build an url pointing to the server, IPv6 addresses are wrapped in brackets
+pub fn server_url(addr: IpAddr, port: u16, path: &str) -> String {
+    match addr {
+        IpAddr::V4(addr) => format!("http://{}:{}/{}", addr, port, path),
+        IpAddr::V6(addr) => format!("http://[{}]:{}/{}", addr, port, path),
+    }
+}
+
+#[test]
+fn test_server_url() {
+    let v4 = IpAddr::V4(Ipv4Addr::new(192, 168, 1, 2));
+    assert_eq!(
+        "http://192.168.1.2:12345/web/index.html",
+        server_url(v4, 12345, "web/index.html")
+    );
+    let v6 = "fe80::1".parse::<IpAddr>().unwrap();
+    assert_eq!(
+        "http://[fe80::1]:12345/web/index.html",
+        server_url(v6, 12345, "web/index.html")
+    );
+}
+
+#[test]
+fn test_resolve_advertise_address() {
+    let interfaces = vec![
+        NetworkInterface {
+            name: "wlan0".to_string(),
+            addr: IpAddr::V4(Ipv4Addr::new(192, 168, 1, 2)),
+        },
+        NetworkInterface {
+            name: "docker0".to_string(),
+            addr: IpAddr::V4(Ipv4Addr::new(172, 17, 0, 1)),
+        },
+    ];
+    assert_eq!(
+        IpAddr::V4(Ipv4Addr::new(172, 17, 0, 1)),
+        resolve_advertise_address(ANY_ADDRESS, Some("172.17.0.1"), &interfaces)
+    );
+    // An address that no longer exists must not be advertised
+    assert_ne!(
+        "10.8.0.5".parse::<IpAddr>().unwrap(),
+        resolve_advertise_address(ANY_ADDRESS, Some("10.8.0.5"), &interfaces)
+    );
+    // A server bound to a single address is only reachable there
+    assert_eq!(
+        IpAddr::V4(Ipv4Addr::new(192, 168, 1, 2)),
+        resolve_advertise_address("192.168.1.2", Some("172.17.0.1"), &interfaces)
+    );
+    assert_eq!(
+        IpAddr::V4(Ipv4Addr::new(172, 17, 0, 1)),
+        resolve_advertise_address("0.0.0.0", Some("172.17.0.1"), &interfaces)
+    );
+}
+
+#[test]
+fn test_find_free_port() {
+    let localhost = IpAddr::V4(Ipv4Addr::LOCALHOST);
+    let listener = TcpListener::bind((localhost, 0)).unwrap();
+    let port_in_use = listener.local_addr().unwrap().port();
+
+    let port = find_free_port(localhost, port_in_use).unwrap();
+    assert_ne!(port_in_use, port);
+    assert!(TcpListener::bind((localhost, port)).is_ok());
+}
@@ -0,0 +1,194 @@
+use crate::setting::Settings;
+use crate::util::{open_file, open_file_in_file_manager};
+use futures::StreamExt;
+use futures::channel::mpsc::{UnboundedSender, unbounded};
+use futures::executor::LocalPool;
+use futures::task::LocalSpawnExt;
+use log::error;
+use notify_rust::Notification;
+use rust_i18n::t;
+use std::sync::LazyLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::spawn;
+
+const OPEN_FILE_ACTION: &str = "open-file";
+const SHOW_IN_FOLDER_ACTION: &str = "show-in-folder";
+
+/// Events shown as desktop notifications, each of them can be turned off in the settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationEvent {
+    /// A client started uploading a new file. Uploads need no approval, so the notification
+    /// only tells that the upload has started.
+    UploadStarted,
+    UploadCompleted,
+    Download,
+}
+
+impl NotificationEvent {
+    fn summary(&self) -> String {
+        match self {
+            NotificationEvent::UploadStarted => t!("notification.upload-started").into_owned(),
+            NotificationEvent::UploadCompleted => t!("notification.upload-completed").into_owned(),
+            NotificationEvent::Download => t!("notification.download").into_owned(),
+        }
+    }
+
+    fn icon(&self) -> &'static str {
+        match self {
+            NotificationEvent::UploadStarted | NotificationEvent::UploadCompleted => {
+                "document-save"
+            }
+            NotificationEvent::Download => "document-send",
+        }
+    }
+}
+
+/// The notifications turned on in the settings of a server. Every server has its own, so a
+/// server running in the background follows the settings of its profile.
+#[derive(Default)]
+pub struct NotificationOptions {
+    upload_started: AtomicBool,
+    upload_completed: AtomicBool,
+    download: AtomicBool,
+}
+
+impl NotificationOptions {
+    pub fn reload(&self, settings: &Settings) {
+        self.upload_started
+            .store(settings.notify_upload_started, Ordering::Relaxed);
+        self.upload_completed
+            .store(settings.notify_upload_completed, Ordering::Relaxed);
+        self.download
+            .store(settings.notify_download, Ordering::Relaxed);
+    }
+
+    fn is_enabled(&self, event: NotificationEvent) -> bool {
+        let enabled = match event {
+            NotificationEvent::UploadStarted => &self.upload_started,
+            NotificationEvent::UploadCompleted => &self.upload_completed,
+            NotificationEvent::Download => &self.download,
+        };
+        enabled.load(Ordering::Relaxed)
+    }
+}
+
+/// A notification waiting to be shown by the notification thread
+struct NotificationRequest {
+    event: NotificationEvent,
+    body: String,
+    file_path: Option<String>,
+}
+
+/// Sends the notifications to a single thread, which shows them and waits for their actions
+/// without blocking the others
+static NOTIFIER: LazyLock<UnboundedSender<NotificationRequest>> = LazyLock::new(|| {
+    let (sender, mut receiver) = unbounded::<NotificationRequest>();
+    spawn(move || {
+        let mut pool = LocalPool::new();
+        let spawner = pool.spawner();
+        pool.run_until(async move {
+            while let Some(request) = receiver.next().await {
+                if let Err(e) = spawner.spawn_local(show(request)) {
+                    error!("Failed to show notification, {e}");
+                }
+            }
+        });
+    });
+    sender
+});
+
+/// Show a freedesktop notification for the event unless it is turned off in the settings of
+/// the server. "Open file" and "Show in folder" are offered when `file_path` is a file on this
+/// device.
+pub fn notify(
+    options: &NotificationOptions,
+    event: NotificationEvent,
+    file_name: &str,
+    client_ip: &str,
+    file_path: Option<String>,
+) {
+    if !options.is_enabled(event) {
+        return;
+    }
+
+    let body = t!(
+        "notification.body",
+        file_name = file_name,
+        client = client_ip
+    )
+    .into_owned();
+    let request = NotificationRequest {
+        event,
+        body,
+        file_path,
+    };
+    if let Err(e) = NOTIFIER.unbounded_send(request) {
+        error!("Failed to show notification, {e}");
+    }
+}
+
+async fn show(request: NotificationRequest) {
+    let NotificationRequest {
+        event,
+        body,
+        file_path,
+    } = request;
+    let mut notification = Notification::new();
+    notification
+        .appname("Share")
+        .summary(&event.summary())
+        .body(&body)
+        .icon(event.icon());
+    if file_path.is_some() {
+        notification
+            .action(OPEN_FILE_ACTION, &t!("label.open-file"))
+            .action(SHOW_IN_FOLDER_ACTION, &t!("label.show-in-folder"));
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let handle = match notification.show_async().await {
+            Ok(handle) => handle,
+            Err(e) => {
+                error!("Failed to show notification, {e}");
+                return;
+            }
+        };
+        if let Some(file_path) = file_path {
+            handle
+                .wait_for_action_async(|response| {
+                    let notify_rust::NotificationResponse::Action(action) = response else {
+                        return;
+                    };
+                    match action.as_str() {
+                        OPEN_FILE_ACTION => {
+                            if let Err(e) = open_file(&file_path) {
+                                error!("Failed to open {file_path}, {e}");
+                            }
+                        }
+                        SHOW_IN_FOLDER_ACTION => open_file_in_file_manager(&file_path),
+                        _ => {}
+                    }
+                })
+                .await;
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        drop(file_path);
+        if let Err(e) = notification.show() {
+            error!("Failed to show notification, {e}");
+        }
+    }
+}
+
+#[test]
+fn test_notification_options() {
+    let options = NotificationOptions::default();
+    let mut settings = Settings::default();
+    settings.notify_upload_started = false;
+    options.reload(&settings);
+    assert!(!options.is_enabled(NotificationEvent::UploadStarted));
+    assert!(options.is_enabled(NotificationEvent::UploadCompleted));
+    assert!(options.is_enabled(NotificationEvent::Download));
+}
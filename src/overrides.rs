@@ -0,0 +1,248 @@
+use crate::autostart::MINIMIZED_ARG;
+use crate::profile::{DEFAULT_PROFILE, is_valid_profile_name};
+use crate::setting::Settings;
+use anyhow::{Context, anyhow};
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// Command line flags and the environment variables of the same settings
+const OPTIONS: [(&str, &str); 7] = [
+    ("--profile", "SHARE_RS_PROFILE"),
+    ("--config-dir", "SHARE_RS_CONFIG_DIR"),
+    ("--db-file", "SHARE_RS_DB_FILE"),
+    ("--port", "SHARE_RS_PORT"),
+    ("--storage-folder", "SHARE_RS_STORAGE_FOLDER"),
+    ("--bind-address", "SHARE_RS_BIND_ADDRESS"),
+    ("--advertise-address", "SHARE_RS_ADVERTISE_ADDRESS"),
+];
+
+static OVERRIDES: OnceLock<Overrides> = OnceLock::new();
+
+/// Settings given by environment variables or command line flags. They take precedence over
+/// `settings.json` and are never stored, the command line takes precedence over the
+/// environment.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Overrides {
+    /// The profile used on launch instead of the last active one
+    pub profile: Option<String>,
+    /// Replaces the folder of `settings.json`, `data.db` and the thumbnails, for portable installs
+    pub config_dir: Option<PathBuf>,
+    /// Replaces `data.db` in the configuration folder
+    pub db_file: Option<PathBuf>,
+    pub port: Option<u16>,
+    pub storage_folder: Option<String>,
+    pub bind_address: Option<String>,
+    pub advertise_address: Option<String>,
+}
+
+impl Overrides {
+    /// Read the `SHARE_RS_*` variables, `var` returns the value of a variable
+    pub fn from_env(var: impl Fn(&str) -> Option<String>) -> anyhow::Result<Overrides> {
+        let mut overrides = Overrides::default();
+        for (flag, name) in OPTIONS {
+            if let Some(value) = var(name) {
+                overrides
+                    .set(flag, value)
+                    .with_context(|| format!("Invalid {name}"))?;
+            }
+        }
+        Ok(overrides)
+    }
+
+    fn set(&mut self, flag: &str, value: String) -> anyhow::Result<()> {
+        match flag {
+            "--profile" => {
+                if value != DEFAULT_PROFILE && !is_valid_profile_name(&value) {
+                    return Err(anyhow!("{value} is not a valid profile name"));
+                }
+                self.profile = Some(value);
+            }
+            "--config-dir" => self.config_dir = Some(value.into()),
+            "--db-file" => self.db_file = Some(value.into()),
+            "--port" => match value.parse::<u16>() {
+                Ok(port) if port > 0 => self.port = Some(port),
+                _ => return Err(anyhow!("{value} is not a valid port")),
+            },
+            "--storage-folder" => self.storage_folder = Some(value),
+            "--bind-address" => {
+                value.parse::<IpAddr>()?;
+                self.bind_address = Some(value);
+            }
+            "--advertise-address" => {
+                value.parse::<IpAddr>()?;
+                self.advertise_address = Some(value);
+            }
+            _ => return Err(anyhow!("Unknown option {flag}")),
+        }
+        Ok(())
+    }
+
+    /// Must be called before the settings are loaded
+    pub fn init(overrides: Overrides) {
+        if OVERRIDES.set(overrides).is_err() {
+            panic!("The overrides are already initialized");
+        }
+    }
+
+    pub fn global() -> &'static Overrides {
+        OVERRIDES.get_or_init(Overrides::default)
+    }
+
+    /// Names of the fields of `Settings` which are overridden
+    pub fn overridden_fields(&self) -> Vec<&'static str> {
+        [
+            ("port", self.port.is_some()),
+            ("storage_folder", self.storage_folder.is_some()),
+            ("bind_address", self.bind_address.is_some()),
+            ("advertise_address", self.advertise_address.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(field, overridden)| overridden.then_some(field))
+        .collect()
+    }
+
+    pub fn is_overridden(&self, field: &str) -> bool {
+        self.overridden_fields().contains(&field)
+    }
+
+    pub fn apply(&self, settings: &mut Settings) {
+        if let Some(port) = self.port {
+            settings.port = port;
+        }
+        if let Some(storage_folder) = &self.storage_folder {
+            settings.storage_folder = storage_folder.clone();
+        }
+        if let Some(bind_address) = &self.bind_address {
+            settings.bind_address = bind_address.clone();
+        }
+        if let Some(advertise_address) = &self.advertise_address {
+            settings.advertise_address = Some(advertise_address.clone());
+        }
+    }
+}
+
+/// The arguments share-rs is launched with
+#[derive(Debug, Default, PartialEq)]
+pub struct CommandLine {
+    pub overrides: Overrides,
+    /// Launched by the autostart entry
+    pub minimized: bool,
+    /// Files to share, everything after `--` is a file
+    pub files: Vec<String>,
+}
+
+impl CommandLine {
+    /// Parse the arguments, `args[0]` is the program name. The flags are applied on top of
+    /// `overrides`, flags take a value either as `--port 8080` or `--port=8080`.
+    pub fn parse(args: &[String], overrides: Overrides) -> anyhow::Result<CommandLine> {
+        let mut command_line = CommandLine {
+            overrides,
+            ..Default::default()
+        };
+        let mut args = args.iter().skip(1);
+        while let Some(arg) = args.next() {
+            if arg == "--" {
+                command_line.files.extend(args.cloned());
+                break;
+            }
+            if arg == MINIMIZED_ARG {
+                command_line.minimized = true;
+                continue;
+            }
+            let (flag, value) = match arg.split_once('=') {
+                Some((flag, value)) => (flag, Some(value.to_string())),
+                None => (arg.as_str(), None),
+            };
+            if !OPTIONS.iter().any(|(option, _)| *option == flag) {
+                command_line.files.push(arg.clone());
+                continue;
+            }
+            let value = match value {
+                Some(value) => value,
+                None => args
+                    .next()
+                    .cloned()
+                    .ok_or_else(|| anyhow!("{flag} requires a value"))?,
+            };
+            command_line.overrides.set(flag, value)?;
+        }
+        Ok(command_line)
+    }
+}
+
+#[test]
+fn test_overrides_from_env() {
+    let overrides = Overrides::from_env(|name| match name {
+        "SHARE_RS_PORT" => Some("8080".into()),
+        "SHARE_RS_CONFIG_DIR" => Some("/opt/share-rs/config".into()),
+        _ => None,
+    })
+    .unwrap();
+    assert_eq!(Some(8080), overrides.port);
+    assert_eq!(
+        Some(PathBuf::from("/opt/share-rs/config")),
+        overrides.config_dir
+    );
+    assert_eq!(None, overrides.storage_folder);
+    assert_eq!(vec!["port"], overrides.overridden_fields());
+
+    assert!(Overrides::from_env(|name| (name == "SHARE_RS_PORT").then(|| "0".into())).is_err());
+    assert!(
+        Overrides::from_env(|name| (name == "SHARE_RS_BIND_ADDRESS").then(|| "lan".into()))
+            .is_err()
+    );
+}
+
+#[test]
+fn test_parse_command_line() {
+    let args: Vec<String> = [
+        "share-rs",
+        "--port",
+        "9000",
+        "--storage-folder=/srv/uploads",
+        MINIMIZED_ARG,
+        "a.txt",
+        "--",
+        "--port",
+    ]
+    .iter()
+    .map(|arg| arg.to_string())
+    .collect();
+    let env_overrides = Overrides {
+        port: Some(8080),
+        bind_address: Some("127.0.0.1".into()),
+        ..Default::default()
+    };
+    let command_line = CommandLine::parse(&args, env_overrides).unwrap();
+    assert!(command_line.minimized);
+    assert_eq!(vec!["a.txt", "--port"], command_line.files);
+    assert_eq!(Some(9000), command_line.overrides.port);
+    assert_eq!(
+        Some("/srv/uploads".to_string()),
+        command_line.overrides.storage_folder
+    );
+    assert_eq!(
+        Some("127.0.0.1".to_string()),
+        command_line.overrides.bind_address
+    );
+
+    let args = vec!["share-rs".to_string(), "--port".to_string()];
+    assert!(CommandLine::parse(&args, Overrides::default()).is_err());
+}
+
+#[test]
+fn test_apply_overrides() {
+    let mut settings = Settings::default();
+    let overrides = Overrides {
+        port: Some(9000),
+        advertise_address: Some("192.168.1.2".into()),
+        ..Default::default()
+    };
+    overrides.apply(&mut settings);
+    assert_eq!(9000, settings.port);
+    assert_eq!(Some("192.168.1.2".to_string()), settings.advertise_address);
+    assert_eq!(Settings::default().storage_folder, settings.storage_folder);
+    assert!(overrides.is_overridden("advertise_address"));
+    assert!(!overrides.is_overridden("storage_folder"));
+}
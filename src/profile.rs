@@ -0,0 +1,155 @@
+use crate::overrides::Overrides;
+use crate::setting::configuration_dir;
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{LazyLock, RwLock};
+
+/// The profile stored directly in the configuration folder, it exists before profiles were
+/// introduced and can not be removed
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// The profile used on launch and whether the servers of the other profiles keep running,
+/// stored in `profiles.json`
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct ProfileOptions {
+    active: Option<String>,
+    run_simultaneously: bool,
+}
+
+/// The profile given by `--profile` or the last active one, the overrides apply to it
+static LAUNCH_PROFILE: LazyLock<String> = LazyLock::new(|| {
+    Overrides::global()
+        .profile
+        .clone()
+        .or(load_options().active)
+        .filter(|profile| profile_exists(profile))
+        .unwrap_or_else(|| DEFAULT_PROFILE.to_string())
+});
+
+static ACTIVE_PROFILE: LazyLock<RwLock<String>> =
+    LazyLock::new(|| RwLock::new(LAUNCH_PROFILE.clone()));
+
+pub fn launch_profile() -> &'static str {
+    &LAUNCH_PROFILE
+}
+
+/// The profile shown in the window, the settings and the database belong to it
+pub fn active_profile() -> String {
+    ACTIVE_PROFILE.read().unwrap().clone()
+}
+
+pub fn set_active_profile(profile: &str) -> anyhow::Result<()> {
+    if !profile_exists(profile) {
+        return Err(anyhow!("Profile {profile} does not exist"));
+    }
+    *ACTIVE_PROFILE.write().unwrap() = profile.to_string();
+    let mut options = load_options();
+    options.active = Some(profile.to_string());
+    store_options(&options)
+}
+
+/// Whether the server of a profile keeps running after switching to another profile
+pub fn runs_simultaneously() -> bool {
+    load_options().run_simultaneously
+}
+
+pub fn set_run_simultaneously(run_simultaneously: bool) -> anyhow::Result<()> {
+    let mut options = load_options();
+    options.run_simultaneously = run_simultaneously;
+    store_options(&options)
+}
+
+/// `settings.json` and `data.db` of the profile are stored in this folder
+pub fn profile_dir(profile: &str) -> PathBuf {
+    if profile == DEFAULT_PROFILE {
+        configuration_dir()
+    } else {
+        profiles_dir().join(profile)
+    }
+}
+
+fn profiles_dir() -> PathBuf {
+    configuration_dir().join("profiles")
+}
+
+fn profile_exists(profile: &str) -> bool {
+    profile == DEFAULT_PROFILE || profile_dir(profile).is_dir()
+}
+
+/// The default profile followed by the other profiles sorted by name
+pub fn list_profiles() -> Vec<String> {
+    let mut profiles: Vec<String> = fs::read_dir(profiles_dir())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|profile| is_valid_profile_name(profile))
+        .collect();
+    profiles.sort();
+    profiles.insert(0, DEFAULT_PROFILE.to_string());
+    profiles
+}
+
+/// The name is used as the folder name of the profile
+pub fn is_valid_profile_name(name: &str) -> bool {
+    !name.is_empty()
+        && name != DEFAULT_PROFILE
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Create the folder of the profile, its settings are created when it is loaded
+pub fn create_profile(profile: &str) -> anyhow::Result<()> {
+    if !is_valid_profile_name(profile) {
+        return Err(anyhow!("Invalid profile name {profile}"));
+    }
+    if profile_exists(profile) {
+        return Err(anyhow!("Profile {profile} already exists"));
+    }
+    fs::create_dir_all(profile_dir(profile))?;
+    Ok(())
+}
+
+fn options_file() -> PathBuf {
+    configuration_dir().join("profiles.json")
+}
+
+fn load_options() -> ProfileOptions {
+    fs::read_to_string(options_file())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn store_options(options: &ProfileOptions) -> anyhow::Result<()> {
+    fs::create_dir_all(configuration_dir())?;
+    fs::write(options_file(), serde_json::to_string(options)?)?;
+    Ok(())
+}
+
+#[test]
+fn test_is_valid_profile_name() {
+    assert!(is_valid_profile_name("team"));
+    assert!(is_valid_profile_name("personal_2"));
+    assert!(is_valid_profile_name("团队"));
+    assert!(!is_valid_profile_name(""));
+    assert!(!is_valid_profile_name(DEFAULT_PROFILE));
+    assert!(!is_valid_profile_name("../team"));
+    assert!(!is_valid_profile_name("team share"));
+    assert!(!is_valid_profile_name(&"a".repeat(65)));
+}
+
+#[test]
+fn test_profile_dir() {
+    assert_eq!(configuration_dir(), profile_dir(DEFAULT_PROFILE));
+    assert_eq!(
+        configuration_dir().join("profiles").join("team"),
+        profile_dir("team")
+    );
+}
@@ -1,43 +1,222 @@
+use crate::network::ANY_ADDRESS;
+use crate::overrides::Overrides;
+use crate::profile::{active_profile, launch_profile, profile_dir};
 use anyhow::{Result, anyhow};
+use log::{error, info, warn};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use std::fs;
 use std::ops::DerefMut;
-use std::path::PathBuf;
-use std::sync::{LazyLock, RwLock};
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, OnceLock, RwLock};
 use std::thread::spawn;
 
+/// Version of the schema of `settings.json`, increase it and add a migration to
+/// `MIGRATIONS` when a field is renamed or its meaning changes. New fields only need a
+/// default value.
+pub const SETTINGS_VERSION: u32 = 1;
+
+/// A migration converts the settings of version `n` to version `n + 1`, where `n` is the
+/// index of the migration
+type Migration = fn(&mut Map<String, Value>);
+
+const MIGRATIONS: [Migration; SETTINGS_VERSION as usize] = [migrate_v0_to_v1];
+
+/// Missing fields use the value of `Settings::default()`
 #[derive(Serialize, Deserialize)]
+#[serde(default)]
 pub struct Settings {
+    /// Version of the schema, files written before the version was introduced are version 0
+    version: u32,
+
     /// Server port
     pub port: u16,
 
     /// Folder path for saving uploaded file
     pub storage_folder: String,
+
+    /// Address the server binds to, `::` means all interfaces
+    #[serde(default = "default_bind_address")]
+    pub bind_address: String,
+
+    /// Address shown in the QR code/URL, `None` means auto detect
+    #[serde(default)]
+    pub advertise_address: Option<String>,
+
+    /// Networks (CIDR) allowed to access the server, empty means all
+    #[serde(default)]
+    pub allow_list: Vec<String>,
+
+    /// Networks (CIDR) not allowed to access the server
+    #[serde(default)]
+    pub deny_list: Vec<String>,
+
+    /// Max requests per minute for each client, 0 means unlimited
+    #[serde(default)]
+    pub max_requests_per_minute: u32,
+
+    /// Max download speed of all clients in KiB/s, 0 means unlimited
+    #[serde(default)]
+    pub max_download_speed: u32,
+
+    /// Max download speed of each connection in KiB/s, 0 means unlimited
+    #[serde(default)]
+    pub max_download_speed_per_connection: u32,
+
+    /// Max upload speed of all clients in KiB/s, 0 means unlimited
+    #[serde(default)]
+    pub max_upload_speed: u32,
+
+    /// Max upload speed of each connection in KiB/s, 0 means unlimited
+    #[serde(default)]
+    pub max_upload_speed_per_connection: u32,
+
+    /// Minimize the window instead of quitting when it is closed while the tray icon is
+    /// shown
+    #[serde(default = "default_minimize_on_close")]
+    pub minimize_on_close: bool,
+
+    /// Show a notification when a client starts uploading a new file
+    #[serde(default = "default_notify")]
+    pub notify_upload_started: bool,
+
+    /// Show a notification when an upload is completed
+    #[serde(default = "default_notify")]
+    pub notify_upload_completed: bool,
+
+    /// Show a notification when a client downloads a share
+    #[serde(default = "default_notify")]
+    pub notify_download: bool,
+
+    /// Start the server when share-rs is launched
+    #[serde(default)]
+    pub start_server_on_launch: bool,
+
+    /// Fields written by a newer share-rs, they are kept when the settings are stored so
+    /// going back to the newer version does not lose them
+    #[serde(flatten)]
+    unknown_fields: Map<String, Value>,
+}
+
+fn default_bind_address() -> String {
+    ANY_ADDRESS.to_string()
+}
+
+fn default_minimize_on_close() -> bool {
+    true
+}
+
+fn default_notify() -> bool {
+    true
 }
 
 impl Default for Settings {
     fn default() -> Self {
         Self {
+            version: SETTINGS_VERSION,
             port: 12345,
+            // There is no download folder when the XDG user directories are not set up
             storage_folder: dirs::download_dir()
-                .unwrap()
+                .or_else(|| dirs::home_dir().map(|home| home.join("Downloads")))
+                .unwrap_or_default()
                 .join("Uploads")
                 .to_string_lossy()
                 .into_owned(),
+            bind_address: default_bind_address(),
+            advertise_address: None,
+            allow_list: vec![],
+            deny_list: vec![],
+            max_requests_per_minute: 0,
+            max_download_speed: 0,
+            max_download_speed_per_connection: 0,
+            max_upload_speed: 0,
+            max_upload_speed_per_connection: 0,
+            minimize_on_close: default_minimize_on_close(),
+            notify_upload_started: default_notify(),
+            notify_upload_completed: default_notify(),
+            notify_download: default_notify(),
+            start_server_on_launch: false,
+            unknown_fields: Map::new(),
         }
     }
 }
 
 static SETTINGS: LazyLock<RwLock<Settings>> = LazyLock::new(|| RwLock::new(Settings::new()));
 
+/// The backup of a settings file which could not be loaded
+static CORRUPT_SETTINGS_BACKUP: OnceLock<PathBuf> = OnceLock::new();
+
+/// The values in `settings.json` of the fields given by `Overrides`, they are stored
+/// instead of the overridden values
+static OVERRIDDEN_VALUES: OnceLock<Map<String, Value>> = OnceLock::new();
+
 impl Settings {
     fn new() -> Self {
-        if !is_setting_file_exists() {
+        Self::load(&active_profile())
+    }
+
+    /// Load the settings of the profile, the overrides apply to the profile used on launch
+    pub fn load(profile: &str) -> Self {
+        let mut settings = Self::load_or_default(profile);
+        if profile == launch_profile() {
+            let overrides = Overrides::global();
+            let _ = OVERRIDDEN_VALUES.set(overridden_values(&settings, overrides));
+            overrides.apply(&mut settings);
+        }
+        settings
+    }
+
+    fn load_or_default(profile: &str) -> Self {
+        if !is_setting_file_exists(profile) {
             let settings = Settings::default();
-            let _ = store_settings(&settings);
-            settings
-        } else {
-            load_settings().unwrap()
+            let _ = store_settings(profile, &settings);
+            return settings;
+        }
+
+        match load_settings(profile) {
+            Ok(settings) => settings,
+            Err(e) => {
+                error!("Failed to load settings, use the default settings instead, {e}");
+                match backup_corrupt_settings(profile) {
+                    Ok(backup) => {
+                        let _ = CORRUPT_SETTINGS_BACKUP.set(backup);
+                    }
+                    Err(e) => error!("Failed to back up the settings file, {e}"),
+                }
+                let settings = Settings::default();
+                let _ = store_settings(profile, &settings);
+                settings
+            }
+        }
+    }
+
+    /// Load the settings of the active profile after switching profiles
+    pub fn reload() {
+        let settings = Self::new();
+        *Self::global().write().unwrap() = settings;
+    }
+
+    /// Store the settings of a profile which is not active
+    pub fn save(profile: &str, settings: &Settings) -> Result<()> {
+        store_settings(profile, settings)
+    }
+
+    /// The settings of a new profile. It gets its own port and storage folder, the options
+    /// of the application, e.g. notifications, are copied from `self`.
+    pub fn for_new_profile(&self, profile: &str, port: u16) -> Settings {
+        let default_settings = Settings::default();
+        let storage_folder = Path::new(&default_settings.storage_folder)
+            .with_file_name(format!("Uploads-{profile}"))
+            .to_string_lossy()
+            .into_owned();
+        Settings {
+            port,
+            storage_folder,
+            minimize_on_close: self.minimize_on_close,
+            notify_upload_started: self.notify_upload_started,
+            notify_upload_completed: self.notify_upload_completed,
+            notify_download: self.notify_download,
+            ..default_settings
         }
     }
 
@@ -51,11 +230,34 @@ impl Settings {
         LazyLock::force(&SETTINGS)
     }
 
+    /// Where the settings file was moved to when it could not be loaded on startup,
+    /// the default settings are used in that case
+    pub fn corrupt_backup() -> Option<&'static PathBuf> {
+        LazyLock::force(&SETTINGS);
+        CORRUPT_SETTINGS_BACKUP.get()
+    }
+
     pub fn clone() -> Self {
         let settings = Self::global().read().unwrap();
         Self {
+            version: settings.version,
             port: settings.port,
             storage_folder: settings.storage_folder.clone(),
+            bind_address: settings.bind_address.clone(),
+            advertise_address: settings.advertise_address.clone(),
+            allow_list: settings.allow_list.clone(),
+            deny_list: settings.deny_list.clone(),
+            max_requests_per_minute: settings.max_requests_per_minute,
+            max_download_speed: settings.max_download_speed,
+            max_download_speed_per_connection: settings.max_download_speed_per_connection,
+            max_upload_speed: settings.max_upload_speed,
+            max_upload_speed_per_connection: settings.max_upload_speed_per_connection,
+            minimize_on_close: settings.minimize_on_close,
+            notify_upload_started: settings.notify_upload_started,
+            notify_upload_completed: settings.notify_upload_completed,
+            notify_download: settings.notify_download,
+            start_server_on_launch: settings.start_server_on_launch,
+            unknown_fields: settings.unknown_fields.clone(),
         }
     }
 
@@ -74,7 +276,7 @@ impl Settings {
         match Self::global().read() {
             Ok(read_guard) => {
                 let settings = &*read_guard;
-                let _ = store_settings(settings)?;
+                store_settings(&active_profile(), settings)?;
                 Ok(())
             }
             Err(e) => Err(anyhow!("Failed to get read lock, {e}")),
@@ -83,31 +285,207 @@ impl Settings {
 }
 
 pub fn configuration_dir() -> PathBuf {
-    dirs::config_dir().unwrap().join("share-rs")
+    match &Overrides::global().config_dir {
+        Some(config_dir) => config_dir.clone(),
+        None => dirs::config_dir().unwrap().join("share-rs"),
+    }
 }
 
-fn setting_file() -> PathBuf {
-    configuration_dir().join("settings.json")
+/// The database of the profile, the overrides apply to the profile used on launch
+pub fn database_file(profile: &str) -> PathBuf {
+    match &Overrides::global().db_file {
+        Some(db_file) if profile == launch_profile() => db_file.clone(),
+        _ => profile_dir(profile).join("data.db"),
+    }
+}
+
+fn setting_file(profile: &str) -> PathBuf {
+    profile_dir(profile).join("settings.json")
 }
 
-fn is_setting_file_exists() -> bool {
-    fs::exists(setting_file()).unwrap()
+fn is_setting_file_exists(profile: &str) -> bool {
+    fs::exists(setting_file(profile)).unwrap()
 }
 
-fn load_settings() -> Result<Settings> {
-    let buf = fs::read(setting_file())?;
+fn load_settings(profile: &str) -> Result<Settings> {
+    let buf = fs::read(setting_file(profile))?;
     let content = String::from_utf8(buf)?;
-    let settings = serde_json::from_str(&content)?;
+    let (settings, migrated) = parse_settings(&content)?;
+    if migrated {
+        store_settings(profile, &settings)?;
+    }
     Ok(settings)
 }
 
-fn store_settings(settings: &Settings) -> Result<()> {
-    let content = serde_json::to_string(settings)?;
-    let setting_file_path = setting_file();
+/// Parse the content of `settings.json` and migrate it to `SETTINGS_VERSION`.
+/// Returns whether the settings were migrated and need to be stored again.
+fn parse_settings(content: &str) -> Result<(Settings, bool)> {
+    let mut value: Value = serde_json::from_str(content)?;
+    let Some(object) = value.as_object_mut() else {
+        return Err(anyhow!("The settings must be a JSON object"));
+    };
+    let version = match object.get("version") {
+        None => 0,
+        Some(version) => version
+            .as_u64()
+            .ok_or_else(|| anyhow!("Invalid settings version {version}"))?
+            as u32,
+    };
+    if version > SETTINGS_VERSION {
+        // Written by a newer share-rs, unknown fields are kept but not used
+        warn!("Settings version {version} is newer than {SETTINGS_VERSION}");
+    }
+    for (from, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        info!("Migrate settings from version {from} to {}", from + 1);
+        migration(object);
+    }
+    let migrated = version < SETTINGS_VERSION;
+    if migrated {
+        object.insert("version".into(), SETTINGS_VERSION.into());
+    }
+    let settings = serde_json::from_value(value)?;
+    Ok((settings, migrated))
+}
+
+/// Version 0 is the settings without version. An invalid `bind_address` was treated as
+/// all interfaces, store that explicitly.
+fn migrate_v0_to_v1(settings: &mut Map<String, Value>) {
+    let bind_address = settings.get("bind_address").and_then(Value::as_str);
+    if bind_address.is_some_and(|address| address.parse::<std::net::IpAddr>().is_err()) {
+        settings.insert("bind_address".into(), default_bind_address().into());
+    }
+}
+
+/// Move the settings file aside so the default settings can be stored, the user can
+/// restore their settings from the backup
+fn backup_corrupt_settings(profile: &str) -> Result<PathBuf> {
+    backup_file(&setting_file(profile))
+}
+
+fn backup_file(file: &Path) -> Result<PathBuf> {
+    let timestamp = chrono::Local::now().format("%Y%m%d%H%M%S");
+    let mut backup = file.as_os_str().to_owned();
+    backup.push(format!(".{timestamp}.bak"));
+    let backup = PathBuf::from(backup);
+    fs::rename(file, &backup)?;
+    Ok(backup)
+}
+
+/// The values of the overridden fields before they are overridden
+fn overridden_values(settings: &Settings, overrides: &Overrides) -> Map<String, Value> {
+    let fields = overrides.overridden_fields();
+    match serde_json::to_value(settings) {
+        Ok(Value::Object(mut values)) => {
+            values.retain(|field, _| fields.contains(&field.as_str()));
+            values
+        }
+        _ => Map::new(),
+    }
+}
+
+fn store_settings(profile: &str, settings: &Settings) -> Result<()> {
+    let mut value = serde_json::to_value(settings)?;
+    if let (Some(object), Some(overridden_values)) =
+        (value.as_object_mut(), OVERRIDDEN_VALUES.get())
+        && profile == launch_profile()
+    {
+        object.extend(overridden_values.clone());
+    }
+    let content = serde_json::to_string(&value)?;
+    let setting_file_path = setting_file(profile);
     let parent_directory = setting_file_path.parent().unwrap();
-    if !is_setting_file_exists() {
+    if !is_setting_file_exists(profile) {
         fs::create_dir_all(parent_directory)?;
     }
     fs::write(setting_file_path, content)?;
     Ok(())
 }
+
+#[test]
+fn test_parse_settings_without_version() {
+    let (settings, migrated) =
+        parse_settings(r#"{"port":8080,"storage_folder":"/tmp/Uploads","bind_address":""}"#)
+            .unwrap();
+    assert!(migrated);
+    assert_eq!(SETTINGS_VERSION, settings.version);
+    assert_eq!(8080, settings.port);
+    assert_eq!("/tmp/Uploads", settings.storage_folder);
+    assert_eq!(ANY_ADDRESS, settings.bind_address);
+    // Fields added later use their default value
+    assert!(settings.minimize_on_close);
+    assert!(settings.allow_list.is_empty());
+}
+
+#[test]
+fn test_parse_settings_of_current_version() {
+    let content = serde_json::to_string(&Settings::default()).unwrap();
+    let (settings, migrated) = parse_settings(&content).unwrap();
+    assert!(!migrated);
+    assert_eq!(Settings::default().port, settings.port);
+
+    // Fields unknown to this version are stored again as they were
+    let (settings, migrated) =
+        parse_settings(r#"{"version":99,"port":8080,"unknown_field":true}"#).unwrap();
+    assert!(!migrated);
+    assert_eq!(99, settings.version);
+    assert_eq!(8080, settings.port);
+    let value = serde_json::to_value(&settings).unwrap();
+    assert_eq!(Some(&Value::Bool(true)), value.get("unknown_field"));
+    assert_eq!(Some(&Value::from(99)), value.get("version"));
+}
+
+#[test]
+fn test_parse_corrupt_settings() {
+    assert!(parse_settings("").is_err());
+    assert!(parse_settings(r#"{"port":8080,"#).is_err());
+    assert!(parse_settings("[]").is_err());
+    assert!(parse_settings(r#"{"port":"8080"}"#).is_err());
+    assert!(parse_settings(r#"{"version":"1"}"#).is_err());
+}
+
+#[test]
+fn test_backup_file() {
+    let file = std::env::temp_dir().join("share-rs-backup-test.json");
+    fs::write(&file, "{").unwrap();
+    let backup = backup_file(&file).unwrap();
+    assert!(!fs::exists(&file).unwrap());
+    assert_eq!("{", fs::read_to_string(&backup).unwrap());
+    assert!(
+        backup
+            .to_string_lossy()
+            .starts_with(&*file.to_string_lossy())
+    );
+    fs::remove_file(backup).unwrap();
+}
+
+#[test]
+fn test_overridden_values() {
+    let settings = Settings::default();
+    let overrides = Overrides {
+        port: Some(9000),
+        storage_folder: Some("/srv/uploads".into()),
+        ..Default::default()
+    };
+    let values = overridden_values(&settings, &overrides);
+    assert_eq!(2, values.len());
+    assert_eq!(Some(&Value::from(settings.port)), values.get("port"));
+    assert_eq!(
+        Some(&Value::from(settings.storage_folder)),
+        values.get("storage_folder")
+    );
+}
+
+#[test]
+fn test_settings_for_new_profile() {
+    let settings = Settings {
+        port: 8080,
+        minimize_on_close: false,
+        allow_list: vec!["192.168.1.0/24".into()],
+        ..Settings::default()
+    };
+    let new_settings = settings.for_new_profile("team", 8081);
+    assert_eq!(8081, new_settings.port);
+    assert!(new_settings.storage_folder.ends_with("Uploads-team"));
+    assert!(!new_settings.minimize_on_close);
+    assert!(new_settings.allow_list.is_empty());
+}
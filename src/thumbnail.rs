@@ -0,0 +1,133 @@
+use crate::setting::configuration_dir;
+use anyhow::Context;
+use image::ImageFormat;
+use log::warn;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Longest edge of a generated thumbnail in pixels
+pub const THUMBNAIL_SIZE: u32 = 256;
+
+/// Folder the generated thumbnails are cached in
+pub fn thumbnail_cache_dir() -> PathBuf {
+    configuration_dir().join("thumbnails")
+}
+
+/// Whether a thumbnail can be generated for files of the mime type
+pub fn is_thumbnail_supported(mime_type: &str) -> bool {
+    ImageFormat::from_mime_type(mime_type).is_some_and(|format| format.reading_enabled())
+}
+
+/// Prefix shared by every thumbnail generated for the file, whatever its version
+fn cache_prefix(file_path: &Path) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(file_path.to_string_lossy().as_bytes());
+    format!("{:x}-", hasher.finalize())
+}
+
+/// The cache key changes whenever the file is moved or modified, so a stale thumbnail is never served
+fn cache_key(file_path: &Path) -> anyhow::Result<String> {
+    let modified = fs::metadata(file_path)
+        .and_then(|metadata| metadata.modified())
+        .context(format!("Failed to read modified time of {:?}", file_path))?;
+    let modified = modified
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    Ok(format!("{}{modified}", cache_prefix(file_path)))
+}
+
+/// Remove the thumbnails generated for older versions of the file
+fn remove_stale_thumbnails(cache_dir: &Path, file_path: &Path, thumbnail_path: &Path) {
+    let Ok(entries) = fs::read_dir(cache_dir) else {
+        return;
+    };
+    let prefix = cache_prefix(file_path);
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let is_stale = path != thumbnail_path
+            && path.extension().is_some_and(|extension| extension == "png")
+            && entry.file_name().to_string_lossy().starts_with(&prefix);
+        if is_stale && let Err(e) = fs::remove_file(&path) {
+            warn!("Failed to remove stale thumbnail {:?}, {e}", path);
+        }
+    }
+}
+
+/// Return the cached thumbnail of an image, generating it first if there is none yet.
+///
+/// Decoding a large photo takes a while, so this must not be called on the UI thread
+/// or directly on an async executor.
+pub fn get_or_create_thumbnail(cache_dir: &Path, file_path: &Path) -> anyhow::Result<PathBuf> {
+    let key = cache_key(file_path)?;
+    let thumbnail_path = cache_dir.join(format!("{key}.png"));
+    if thumbnail_path.is_file() {
+        return Ok(thumbnail_path);
+    }
+
+    let image =
+        image::open(file_path).context(format!("Failed to decode image {:?}", file_path))?;
+    let thumbnail = image.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE);
+
+    fs::create_dir_all(cache_dir).context("Failed to create thumbnail cache folder")?;
+    // Write to a temporary file first, so a concurrent request never reads a half written thumbnail
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let temp_path = cache_dir.join(format!("{key}.{nanos}.tmp"));
+    thumbnail
+        .save_with_format(&temp_path, ImageFormat::Png)
+        .context("Failed to save thumbnail")?;
+    fs::rename(&temp_path, &thumbnail_path).context("Failed to save thumbnail")?;
+    remove_stale_thumbnails(cache_dir, file_path, &thumbnail_path);
+    Ok(thumbnail_path)
+}
+
+#[test]
+fn test_is_thumbnail_supported() {
+    assert!(is_thumbnail_supported("image/png"));
+    assert!(is_thumbnail_supported("image/jpeg"));
+    assert!(!is_thumbnail_supported("image/svg+xml"));
+    assert!(!is_thumbnail_supported("text/plain"));
+}
+
+#[test]
+fn test_get_or_create_thumbnail() {
+    let project_dir = env!("CARGO_MANIFEST_DIR");
+    let test_dir = Path::new(project_dir).join("target/debug/test_get_or_create_thumbnail");
+    let _ = fs::remove_dir_all(&test_dir);
+    fs::create_dir_all(&test_dir).unwrap();
+
+    let image_path = test_dir.join("photo.png");
+    image::RgbImage::new(1024, 512).save(&image_path).unwrap();
+
+    let cache_dir = test_dir.join("thumbnails");
+    let thumbnail_path = get_or_create_thumbnail(&cache_dir, &image_path).unwrap();
+    let thumbnail = image::open(&thumbnail_path).unwrap();
+    assert_eq!(THUMBNAIL_SIZE, thumbnail.width());
+    assert_eq!(THUMBNAIL_SIZE / 2, thumbnail.height());
+
+    // The cached thumbnail is reused as long as the image is unchanged
+    assert_eq!(
+        thumbnail_path,
+        get_or_create_thumbnail(&cache_dir, &image_path).unwrap()
+    );
+
+    // Regenerating the thumbnail of a modified image removes the old one
+    let modified = SystemTime::now() + std::time::Duration::from_secs(60);
+    fs::File::options()
+        .write(true)
+        .open(&image_path)
+        .unwrap()
+        .set_modified(modified)
+        .unwrap();
+    let new_thumbnail_path = get_or_create_thumbnail(&cache_dir, &image_path).unwrap();
+    assert_ne!(thumbnail_path, new_thumbnail_path);
+    assert!(!thumbnail_path.exists());
+    assert!(new_thumbnail_path.is_file());
+
+    fs::remove_dir_all(&test_dir).unwrap();
+}
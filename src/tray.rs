@@ -0,0 +1,167 @@
+use crate::backend::web::server::{ServerState, ShareServer};
+use crate::gpui_tokio::Tokio;
+use crate::setting::Settings;
+use futures::channel::mpsc::{UnboundedReceiver, UnboundedSender, unbounded};
+use gpui::{App, Global, ReadGlobal, Window};
+use ksni::menu::StandardItem;
+use ksni::{MenuItem, ToolTip, Tray, TrayMethods};
+use log::error;
+use rust_i18n::t;
+
+/// Requests from the tray menu, they are handled on the main thread
+pub enum TrayCommand {
+    ShowWindow,
+    ShowQrCode,
+    SwitchServer(bool),
+    Quit,
+}
+
+struct ShareTray {
+    server_state: ServerState,
+    sender: UnboundedSender<TrayCommand>,
+}
+
+impl ShareTray {
+    fn send(&self, command: TrayCommand) {
+        if let Err(e) = self.sender.unbounded_send(command) {
+            error!("Failed to send tray command, {e}");
+        }
+    }
+
+    fn server_state_label(&self) -> String {
+        match self.server_state {
+            ServerState::On => t!("label.server-running").into_owned(),
+            ServerState::Off => t!("label.server-stopped").into_owned(),
+        }
+    }
+}
+
+impl Tray for ShareTray {
+    fn id(&self) -> String {
+        "share-rs".into()
+    }
+
+    fn title(&self) -> String {
+        "Share".into()
+    }
+
+    fn icon_name(&self) -> String {
+        match self.server_state {
+            ServerState::On => "network-transmit-receive".into(),
+            ServerState::Off => "network-offline".into(),
+        }
+    }
+
+    fn tool_tip(&self) -> ToolTip {
+        ToolTip {
+            title: "Share".into(),
+            description: self.server_state_label(),
+            ..Default::default()
+        }
+    }
+
+    fn activate(&mut self, _x: i32, _y: i32) {
+        self.send(TrayCommand::ShowWindow);
+    }
+
+    fn menu(&self) -> Vec<MenuItem<Self>> {
+        let server_item = match self.server_state {
+            ServerState::On => StandardItem {
+                label: t!("label.stop-server").into_owned(),
+                activate: Box::new(|tray: &mut Self| tray.send(TrayCommand::SwitchServer(false))),
+                ..Default::default()
+            },
+            ServerState::Off => StandardItem {
+                label: t!("label.start-server").into_owned(),
+                activate: Box::new(|tray: &mut Self| tray.send(TrayCommand::SwitchServer(true))),
+                ..Default::default()
+            },
+        };
+        vec![
+            StandardItem {
+                label: self.server_state_label(),
+                enabled: false,
+                ..Default::default()
+            }
+            .into(),
+            server_item.into(),
+            MenuItem::Separator,
+            StandardItem {
+                label: t!("label.show-window").into_owned(),
+                activate: Box::new(|tray: &mut Self| tray.send(TrayCommand::ShowWindow)),
+                ..Default::default()
+            }
+            .into(),
+            StandardItem {
+                label: t!("label.show-qr-code").into_owned(),
+                activate: Box::new(|tray: &mut Self| tray.send(TrayCommand::ShowQrCode)),
+                ..Default::default()
+            }
+            .into(),
+            MenuItem::Separator,
+            StandardItem {
+                label: t!("label.quit").into_owned(),
+                icon_name: "application-exit".into(),
+                activate: Box::new(|tray: &mut Self| tray.send(TrayCommand::Quit)),
+                ..Default::default()
+            }
+            .into(),
+        ]
+    }
+}
+
+/// The status notifier icon showing the server state, the server keeps running
+/// while the window is hidden to the tray
+pub struct TrayIcon {
+    handle: ksni::Handle<ShareTray>,
+}
+
+impl Global for TrayIcon {}
+
+impl TrayIcon {
+    /// Register the icon on the session bus, fails when there is no status notifier host,
+    /// e.g. GNOME without the AppIndicator extension
+    pub async fn spawn(
+        server_state: ServerState,
+    ) -> anyhow::Result<(TrayIcon, UnboundedReceiver<TrayCommand>)> {
+        let (sender, receiver) = unbounded();
+        let handle = ShareTray {
+            server_state,
+            sender,
+        }
+        .spawn()
+        .await?;
+        Ok((TrayIcon { handle }, receiver))
+    }
+
+    /// Show the current state of `ShareServer` in the tray
+    pub fn update_server_state(cx: &mut App) {
+        let Some(tray_icon) = cx.try_global::<TrayIcon>() else {
+            return;
+        };
+        let handle = tray_icon.handle.clone();
+        let server_state = ShareServer::global(cx).state();
+        Tokio::spawn(cx, async move {
+            handle
+                .update(|tray: &mut ShareTray| tray.server_state = server_state)
+                .await;
+        })
+        .detach();
+    }
+
+    /// Close the window, or minimize it when the tray icon is shown and `minimize_on_close`
+    /// is enabled, so the window can be restored from the tray. GPUI can not hide a window
+    /// and quits once the last window is closed on Linux.
+    pub fn close_window(window: &mut Window, cx: &mut App) {
+        if Self::minimizes_on_close(cx) {
+            window.minimize_window();
+        } else {
+            window.remove_window();
+        }
+    }
+
+    /// Whether closing the window should minimize it
+    pub fn minimizes_on_close(cx: &App) -> bool {
+        cx.has_global::<TrayIcon>() && Settings::global().read().unwrap().minimize_on_close
+    }
+}
@@ -1,6 +1,11 @@
+pub mod blocked_requests_page;
+pub mod collections_page;
 pub mod component;
 pub mod file_list_page;
+pub mod history_page;
+pub mod profiles_page;
 pub mod root_view;
 pub mod server_info_page;
 pub mod settings_page;
 pub mod title_bar;
+pub mod toast;
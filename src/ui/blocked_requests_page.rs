@@ -0,0 +1,228 @@
+use crate::backend::db::DatabaseSource;
+use crate::backend::db::repository::{
+    BlockedRequest, delete_all_blocked_requests, get_blocked_requests,
+};
+use crate::backend::web::access_control::IpNetwork;
+use crate::backend::web::server::ShareServer;
+use crate::gpui_tokio::Tokio;
+use crate::setting::Settings;
+use crate::ui::root_view::Pages;
+use gpui::prelude::FluentBuilder;
+use gpui::{
+    App, AppContext, AsyncApp, Context, Entity, Global, InteractiveElement, IntoElement,
+    ParentElement, ReadGlobal, Render, Styled, TextOverflow, UpdateGlobal, Window, black, div, px,
+    uniform_list,
+};
+use gpui_component::button::{Button, ButtonVariant, ButtonVariants};
+use gpui_component::{
+    Icon, IconName, StyledExt, Theme, ThemeMode, gray_100, h_flex, neutral_400, neutral_500, v_flex,
+};
+use log::error;
+use rust_i18n::t;
+use std::net::IpAddr;
+
+pub struct BlockedRequestList {
+    data: Vec<BlockedRequest>,
+}
+
+impl Default for BlockedRequestList {
+    fn default() -> Self {
+        Self { data: vec![] }
+    }
+}
+
+impl Global for BlockedRequestList {}
+
+pub struct BlockedRequestsPage {}
+
+impl BlockedRequestsPage {
+    pub fn new(cx: &mut App) -> Entity<BlockedRequestsPage> {
+        cx.new(|_cx| BlockedRequestsPage {})
+    }
+
+    pub fn reload(cx: &mut App) {
+        let db = DatabaseSource::global(cx).instance.clone();
+        cx.spawn(async move |cx: &mut AsyncApp| {
+            let data = Tokio::spawn(cx, async move {
+                let connection = db.connection().await.unwrap();
+                get_blocked_requests(&connection).await.unwrap()
+            })
+            .unwrap()
+            .await
+            .unwrap();
+
+            let blocked_request_list = BlockedRequestList { data };
+            cx.update(move |cx: &mut App| {
+                cx.set_global::<BlockedRequestList>(blocked_request_list);
+                cx.refresh_windows();
+            })
+        })
+        .detach();
+    }
+
+    fn clear(cx: &mut App) {
+        let db = DatabaseSource::global(cx).instance.clone();
+        cx.spawn(async move |cx: &mut AsyncApp| {
+            Tokio::spawn(cx, async move {
+                let connection = db.connection().await.unwrap();
+                if let Err(e) = delete_all_blocked_requests(&connection).await {
+                    error!("Failed to clear blocked requests, {e}");
+                }
+            })
+            .unwrap()
+            .await
+            .unwrap();
+
+            cx.update(|cx: &mut App| {
+                Self::reload(cx);
+            })
+        })
+        .detach();
+    }
+
+    /// Whether the client is blocked by an entry of exactly this address in the deny list
+    fn is_blocked(client_ip: IpAddr, deny_list: &[String]) -> bool {
+        let host = IpNetwork::host(client_ip);
+        deny_list
+            .iter()
+            .any(|network| network.parse::<IpNetwork>().ok() == Some(host))
+    }
+
+    /// Add the client to the deny list, or remove it if it has been blocked already
+    fn toggle_block(client_ip: IpAddr, cx: &mut App) {
+        let host = IpNetwork::host(client_ip);
+        let update_fn = move |settings: &mut Settings| {
+            if Self::is_blocked(client_ip, &settings.deny_list) {
+                settings
+                    .deny_list
+                    .retain(|network| network.parse::<IpNetwork>().ok() != Some(host));
+            } else {
+                settings.deny_list.push(host.to_string());
+            }
+        };
+        if let Err(e) = Settings::update(Box::new(update_fn)) {
+            error!("Failed to update deny list, {e}");
+            return;
+        }
+        ShareServer::global(cx).reload_settings(&Settings::clone());
+        cx.refresh_windows();
+    }
+}
+
+impl Render for BlockedRequestsPage {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let blocked_request_count = cx.default_global::<BlockedRequestList>().data.len();
+        let theme = Theme::global(cx);
+
+        v_flex()
+            .size_full()
+            .bg(theme.colors.background)
+            .text_color(neutral_400())
+            .child(
+                h_flex()
+                    .h_8()
+                    .pl_4()
+                    .pr_4()
+                    .justify_between()
+                    .child(
+                        div()
+                            .when(theme.mode == ThemeMode::Dark, |this| {
+                                this.text_color(gray_100())
+                            })
+                            .when(theme.mode == ThemeMode::Light, |this| {
+                                this.text_color(black())
+                            })
+                            .font_bold()
+                            .child(t!("label.blocked-requests").to_string()),
+                    )
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .child(
+                                Button::new("clear-blocked-requests")
+                                    .icon(Icon::new(IconName::Delete).text_color(neutral_500()))
+                                    .with_variant(ButtonVariant::Ghost)
+                                    .tooltip(t!("tooltip.clear-blocked-requests"))
+                                    .on_click(|_ev, _window, cx| {
+                                        Self::clear(cx);
+                                    }),
+                            )
+                            .child(
+                                Button::new("close-blocked-requests-page")
+                                    .icon(Icon::new(IconName::Close).text_color(neutral_500()))
+                                    .with_variant(ButtonVariant::Ghost)
+                                    .on_click(|_ev, _window, cx| {
+                                        Pages::set_global(cx, Pages::FileListPage);
+                                    }),
+                            ),
+                    ),
+            )
+            .child(
+                uniform_list(
+                    "blocked-requests",
+                    blocked_request_count,
+                    cx.processor(|_this, range, _window, cx| {
+                        let mut items = Vec::new();
+                        let theme = Theme::global(cx);
+                        let deny_list = Settings::clone().deny_list;
+                        let blocked_request_list = BlockedRequestList::global(cx);
+
+                        for ix in range {
+                            let Some(item) = blocked_request_list.data.get(ix) else {
+                                continue;
+                            };
+                            let client_ip = item.client_ip.parse::<IpAddr>().ok();
+                            let is_blocked = client_ip
+                                .map(|client_ip| Self::is_blocked(client_ip, &deny_list))
+                                .unwrap_or(false);
+                            items.push(
+                                h_flex()
+                                    .id(ix)
+                                    .h(px(48.))
+                                    .pl(px(16.))
+                                    .pr(px(16.))
+                                    .gap_4()
+                                    .items_center()
+                                    .bg(theme.colors.list)
+                                    .text_color(theme.colors.foreground)
+                                    .child(
+                                        div().w(px(160.)).flex_none().child(item.client_ip.clone()),
+                                    )
+                                    .child(div().w(px(100.)).flex_none().child(item.reason.clone()))
+                                    .child(
+                                        div()
+                                            .min_w_0()
+                                            .flex_grow()
+                                            .overflow_x_hidden()
+                                            .text_overflow(TextOverflow::Truncate("...".into()))
+                                            .child(item.path.clone()),
+                                    )
+                                    .child(div().flex_none().text_color(neutral_500()).child(
+                                        item.created_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+                                    ))
+                                    .when_some(client_ip, |this, client_ip| {
+                                        let label = if is_blocked {
+                                            t!("label.unblock")
+                                        } else {
+                                            t!("label.block")
+                                        };
+                                        this.child(
+                                            Button::new(("toggle-block-button", ix))
+                                                .flex_none()
+                                                .with_variant(ButtonVariant::Secondary)
+                                                .child(label.to_string())
+                                                .on_click(move |_ev, _window, cx| {
+                                                    cx.stop_propagation();
+                                                    Self::toggle_block(client_ip, cx);
+                                                }),
+                                        )
+                                    }),
+                            );
+                        }
+                        items
+                    }),
+                )
+                .flex_grow(),
+            )
+    }
+}
@@ -0,0 +1,513 @@
+use crate::backend::db::DatabaseSource;
+use crate::backend::db::repository::{
+    Collection, Share, add_shares_to_collection, delete_collection, get_collection_shares,
+    get_collections, get_share_by_path, insert_collection, insert_share,
+    regenerate_collection_token, remove_share_from_collection,
+};
+use crate::gpui_tokio::Tokio;
+use crate::network::{list_network_interfaces, resolve_advertise_address, server_url};
+use crate::setting::Settings;
+use crate::ui::component::input::TextInput;
+use crate::ui::file_list_page::FileListPage;
+use crate::ui::root_view::Pages;
+use crate::ui::server_info_page::ServerInfoPage;
+use gpui::prelude::FluentBuilder;
+use gpui::{
+    App, AppContext, AsyncApp, Context, Entity, Global, InteractiveElement, IntoElement,
+    ParentElement, ReadGlobal, Render, StatefulInteractiveElement, Styled, TextOverflow,
+    UpdateGlobal, Window, black, div, px,
+};
+use gpui_component::button::{Button, ButtonVariant, ButtonVariants};
+use gpui_component::{
+    Icon, IconName, StyledExt, Theme, ThemeMode, gray_100, h_flex, neutral_400, neutral_500, v_flex,
+};
+use log::error;
+use rfd::AsyncFileDialog;
+use rust_i18n::t;
+use snowflaked::sync::Generator;
+
+#[derive(Default)]
+pub struct CollectionList {
+    data: Vec<Collection>,
+    /// The collection shown on the right side of the page
+    selected: Option<i64>,
+    /// Shares of the selected collection
+    shares: Vec<Share>,
+}
+
+impl Global for CollectionList {}
+
+pub struct CollectionsPage {
+    name_input: Entity<TextInput>,
+    pin_input: Entity<TextInput>,
+}
+
+impl CollectionsPage {
+    pub fn new(cx: &mut App) -> Entity<CollectionsPage> {
+        let name_input = TextInput::new(
+            None,
+            Some("Collection name, e.g. Trip photos".into()),
+            None,
+            cx,
+        );
+        let pin_input = TextInput::new(None, Some("PIN (optional)".into()), None, cx);
+        cx.new(|_cx| CollectionsPage {
+            name_input,
+            pin_input,
+        })
+    }
+
+    /// Reload the collections and the shares of the selected collection,
+    /// the first collection is selected if the selected one is gone
+    pub fn reload(cx: &mut App) {
+        let db = DatabaseSource::global(cx).instance.clone();
+        let selected = cx.default_global::<CollectionList>().selected;
+        cx.spawn(async move |cx: &mut AsyncApp| {
+            let collection_list = Tokio::spawn(cx, async move {
+                let connection = db.connection().await.unwrap();
+                let data = get_collections(&connection).await.unwrap();
+                let selected = selected
+                    .filter(|selected| data.iter().any(|collection| collection.id == *selected))
+                    .or_else(|| data.first().map(|collection| collection.id));
+                let shares = match selected {
+                    Some(collection_id) => get_collection_shares(&connection, collection_id)
+                        .await
+                        .unwrap(),
+                    None => vec![],
+                };
+                CollectionList {
+                    data,
+                    selected,
+                    shares,
+                }
+            })
+            .unwrap()
+            .await
+            .unwrap();
+
+            cx.update(move |cx: &mut App| {
+                cx.set_global::<CollectionList>(collection_list);
+                cx.refresh_windows();
+            })
+        })
+        .detach();
+    }
+
+    fn select(collection_id: i64, cx: &mut App) {
+        cx.default_global::<CollectionList>().selected = Some(collection_id);
+        Self::reload(cx);
+    }
+
+    fn handle_create(&self, cx: &mut Context<Self>) {
+        let name = self.name_input.read(cx).content.trim().to_string();
+        if name.is_empty() {
+            return;
+        }
+        let pin = self.pin_input.read(cx).content.trim().to_string();
+        let pin = Some(pin).filter(|pin| !pin.is_empty());
+        self.name_input
+            .update(cx, |input, _cx| input.content = "".into());
+        self.pin_input
+            .update(cx, |input, _cx| input.content = "".into());
+
+        Self::create_collection(name, pin, cx);
+    }
+
+    fn create_collection(name: String, pin: Option<String>, cx: &mut App) {
+        let db = DatabaseSource::global(cx).instance.clone();
+        cx.spawn(async move |cx: &mut AsyncApp| {
+            let collection = Tokio::spawn(cx, async move {
+                let connection = db.connection().await.unwrap();
+                let generator = Generator::new(0);
+                insert_collection(&connection, generator.generate(), name, pin).await
+            })
+            .unwrap()
+            .await
+            .unwrap();
+
+            cx.update(|cx: &mut App| match collection {
+                Ok(collection) => Self::select(collection.id, cx),
+                Err(e) => error!("Failed to create collection, {e:?}"),
+            })
+        })
+        .detach();
+    }
+
+    fn remove_collection(collection_id: i64, cx: &mut App) {
+        let db = DatabaseSource::global(cx).instance.clone();
+        cx.spawn(async move |cx: &mut AsyncApp| {
+            Tokio::spawn(cx, async move {
+                let connection = db.connection().await.unwrap();
+                if let Err(e) = delete_collection(&connection, collection_id).await {
+                    error!("Failed to delete collection, {e:?}");
+                }
+            })
+            .unwrap()
+            .await
+            .unwrap();
+
+            cx.update(|cx: &mut App| {
+                Self::reload(cx);
+            })
+        })
+        .detach();
+    }
+
+    /// Add the picked files to the collection, files already in the share list are reused
+    fn add_files(collection_id: i64, cx: &mut App) {
+        let db = DatabaseSource::global(cx).instance.clone();
+        cx.spawn(async move |cx: &mut AsyncApp| {
+            Tokio::spawn(cx, async move {
+                let Some(files) = AsyncFileDialog::new().pick_files().await else {
+                    return;
+                };
+                let connection = db.connection().await.unwrap();
+                let generator = Generator::new(0);
+
+                let mut share_ids = Vec::new();
+                for file in files {
+                    let file_path = file.path().to_string_lossy().into_owned();
+                    let share = match get_share_by_path(&connection, &file_path).await {
+                        Ok(Some(share)) => Ok(share),
+                        Ok(None) => {
+                            insert_share(&connection, generator.generate(), &file_path).await
+                        }
+                        Err(e) => Err(e),
+                    };
+                    match share {
+                        Ok(share) => share_ids.push(share.id),
+                        Err(e) => error!("Failed to insert share file: {e:?}"),
+                    }
+                }
+                if let Err(e) =
+                    add_shares_to_collection(&connection, collection_id, &share_ids).await
+                {
+                    error!("Failed to add shares to collection, {e:?}");
+                }
+            })
+            .unwrap()
+            .await
+            .unwrap();
+
+            cx.update(|cx: &mut App| {
+                Self::reload(cx);
+                FileListPage::reload(cx);
+            })
+        })
+        .detach();
+    }
+
+    fn remove_share(collection_id: i64, share_id: i64, cx: &mut App) {
+        let db = DatabaseSource::global(cx).instance.clone();
+        cx.spawn(async move |cx: &mut AsyncApp| {
+            Tokio::spawn(cx, async move {
+                let connection = db.connection().await.unwrap();
+                if let Err(e) =
+                    remove_share_from_collection(&connection, collection_id, share_id).await
+                {
+                    error!("Failed to remove share from collection, {e:?}");
+                }
+            })
+            .unwrap()
+            .await
+            .unwrap();
+
+            cx.update(|cx: &mut App| {
+                Self::reload(cx);
+            })
+        })
+        .detach();
+    }
+
+    /// Give the collection a new access token, links shared before stop working
+    fn regenerate_link(collection_id: i64, cx: &mut App) {
+        let db = DatabaseSource::global(cx).instance.clone();
+        cx.spawn(async move |cx: &mut AsyncApp| {
+            Tokio::spawn(cx, async move {
+                let connection = db.connection().await.unwrap();
+                if let Err(e) = regenerate_collection_token(&connection, collection_id).await {
+                    error!("Failed to regenerate access token, {e:?}");
+                }
+            })
+            .unwrap()
+            .await
+            .unwrap();
+
+            cx.update(|cx: &mut App| {
+                Self::reload(cx);
+            })
+        })
+        .detach();
+    }
+
+    /// The page of the collection in the web client
+    fn collection_url(access_token: &str) -> String {
+        let settings = Settings::clone();
+        let advertise_address = resolve_advertise_address(
+            &settings.bind_address,
+            settings.advertise_address.as_deref(),
+            &list_network_interfaces(),
+        );
+        server_url(
+            advertise_address,
+            settings.port,
+            &format!("web/collection.html?token={access_token}"),
+        )
+    }
+
+    fn render_detail(
+        collection: &Collection,
+        shares: &[Share],
+        cx: &Context<Self>,
+    ) -> impl IntoElement {
+        let theme = Theme::global(cx);
+        let collection_id = collection.id;
+        let url = Self::collection_url(&collection.access_token);
+        let pin = match &collection.pin {
+            Some(_) => t!("label.pin-protected").to_string(),
+            None => t!("label.no-pin").to_string(),
+        };
+
+        v_flex()
+            .flex_grow()
+            .min_w_0()
+            .p_4()
+            .gap_2()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .child(
+                        div()
+                            .min_w_0()
+                            .flex_grow()
+                            .overflow_x_hidden()
+                            .text_overflow(TextOverflow::Truncate("...".into()))
+                            .text_color(theme.colors.foreground)
+                            .font_bold()
+                            .child(collection.name.clone()),
+                    )
+                    .child(div().flex_none().child(pin))
+                    .child(
+                        Button::new("add-files-to-collection")
+                            .icon(Icon::new(IconName::Plus).text_color(neutral_500()))
+                            .with_variant(ButtonVariant::Ghost)
+                            .tooltip(t!("tooltip.add-files-to-collection"))
+                            .on_click(move |_ev, _window, cx| {
+                                Self::add_files(collection_id, cx);
+                            }),
+                    )
+                    .child(
+                        Button::new("delete-collection")
+                            .icon(Icon::new(IconName::Delete).text_color(theme.colors.danger))
+                            .with_variant(ButtonVariant::Ghost)
+                            .tooltip(t!("tooltip.delete-collection"))
+                            .on_click(move |_ev, _window, cx| {
+                                Self::remove_collection(collection_id, cx);
+                            }),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .gap_4()
+                    .items_center()
+                    .child(
+                        ServerInfoPage::create_qr_code(&url)
+                            .size(px(160.))
+                            .flex_none(),
+                    )
+                    .child(
+                        h_flex()
+                            .min_w_0()
+                            .gap_2()
+                            .items_center()
+                            .child(
+                                div()
+                                    .min_w_0()
+                                    .overflow_x_hidden()
+                                    .text_overflow(TextOverflow::Truncate("...".into()))
+                                    .child(url.clone()),
+                            )
+                            .child(
+                                Button::new("regenerate-collection-link")
+                                    .icon(Icon::new(IconName::Redo).text_color(neutral_500()))
+                                    .with_variant(ButtonVariant::Ghost)
+                                    .tooltip(t!("tooltip.regenerate-link"))
+                                    .on_click(move |_ev, _window, cx| {
+                                        Self::regenerate_link(collection_id, cx);
+                                    }),
+                            )
+                            .child(
+                                Button::new("copy-collection-url")
+                                    .icon(Icon::new(IconName::Copy).text_color(neutral_500()))
+                                    .with_variant(ButtonVariant::Ghost)
+                                    .on_click(move |_ev, _window, _cx| {
+                                        ServerInfoPage::copy_to_clipboard(url.clone());
+                                    }),
+                            ),
+                    ),
+            )
+            .child(
+                v_flex()
+                    .id("collection-shares")
+                    .flex_grow()
+                    .overflow_y_scroll()
+                    .children(shares.iter().enumerate().map(|(ix, share)| {
+                        let share_id = share.id;
+                        h_flex()
+                            .h(px(40.))
+                            .gap_4()
+                            .items_center()
+                            .text_color(theme.colors.foreground)
+                            .child(
+                                div()
+                                    .min_w_0()
+                                    .flex_grow()
+                                    .overflow_x_hidden()
+                                    .text_overflow(TextOverflow::Truncate("...".into()))
+                                    .child(share.file_name.clone()),
+                            )
+                            .child(
+                                Button::new(("remove-from-collection", ix))
+                                    .icon(Icon::new(IconName::Close).text_color(neutral_500()))
+                                    .with_variant(ButtonVariant::Ghost)
+                                    .tooltip(t!("tooltip.remove-from-collection"))
+                                    .on_click(move |_ev, _window, cx| {
+                                        Self::remove_share(collection_id, share_id, cx);
+                                    }),
+                            )
+                    })),
+            )
+    }
+}
+
+impl Render for CollectionsPage {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        cx.default_global::<CollectionList>();
+        let theme = Theme::global(cx);
+        let collection_list = CollectionList::global(cx);
+        let selected = collection_list.selected.and_then(|selected| {
+            collection_list
+                .data
+                .iter()
+                .find(|collection| collection.id == selected)
+        });
+
+        v_flex()
+            .size_full()
+            .bg(theme.colors.background)
+            .text_color(neutral_400())
+            .child(
+                h_flex()
+                    .h_8()
+                    .pl_4()
+                    .pr_4()
+                    .justify_between()
+                    .child(
+                        div()
+                            .when(theme.mode == ThemeMode::Dark, |this| {
+                                this.text_color(gray_100())
+                            })
+                            .when(theme.mode == ThemeMode::Light, |this| {
+                                this.text_color(black())
+                            })
+                            .font_bold()
+                            .child(t!("label.collections").to_string()),
+                    )
+                    .child(
+                        Button::new("close-collections-page")
+                            .icon(Icon::new(IconName::Close).text_color(neutral_500()))
+                            .with_variant(ButtonVariant::Ghost)
+                            .on_click(|_ev, _window, cx| {
+                                Pages::set_global(cx, Pages::FileListPage);
+                            }),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .h_10()
+                    .pl_4()
+                    .pr_4()
+                    .gap_2()
+                    .child(
+                        div()
+                            .flex_grow()
+                            .border_b_1()
+                            .border_color(theme.colors.input)
+                            .text_color(theme.colors.foreground)
+                            .child(self.name_input.clone()),
+                    )
+                    .child(
+                        div()
+                            .w(px(160.))
+                            .border_b_1()
+                            .border_color(theme.colors.input)
+                            .text_color(theme.colors.foreground)
+                            .child(self.pin_input.clone()),
+                    )
+                    .child(
+                        Button::new("create-collection")
+                            .with_variant(ButtonVariant::Primary)
+                            .child(t!("label.create-collection").to_string())
+                            .on_click(cx.listener(|this, _ev, _window, cx| {
+                                this.handle_create(cx);
+                            })),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .flex_grow()
+                    .min_h_0()
+                    .items_start()
+                    .child(
+                        v_flex()
+                            .id("collections")
+                            .w(px(240.))
+                            .h_full()
+                            .flex_none()
+                            .overflow_y_scroll()
+                            .border_r_1()
+                            .border_color(theme.colors.border)
+                            .children(collection_list.data.iter().enumerate().map(
+                                |(ix, collection)| {
+                                    let collection_id = collection.id;
+                                    let is_selected =
+                                        Some(collection_id) == collection_list.selected;
+                                    h_flex()
+                                        .id(("collection", ix))
+                                        .h(px(40.))
+                                        .pl_4()
+                                        .pr_4()
+                                        .gap_2()
+                                        .items_center()
+                                        .text_color(theme.colors.foreground)
+                                        .when(is_selected, |this| this.bg(theme.colors.list_active))
+                                        .hover(|style| style.bg(theme.colors.list_hover))
+                                        .child(
+                                            div()
+                                                .min_w_0()
+                                                .flex_grow()
+                                                .overflow_x_hidden()
+                                                .text_overflow(TextOverflow::Truncate("...".into()))
+                                                .child(collection.name.clone()),
+                                        )
+                                        .when(collection.pin.is_some(), |this| {
+                                            this.child(
+                                                div()
+                                                    .flex_none()
+                                                    .text_color(neutral_500())
+                                                    .child("PIN"),
+                                            )
+                                        })
+                                        .on_click(move |_ev, _window, cx| {
+                                            Self::select(collection_id, cx);
+                                        })
+                                },
+                            )),
+                    )
+                    .when_some(selected, |this, collection| {
+                        this.child(Self::render_detail(collection, &collection_list.shares, cx))
+                    }),
+            )
+    }
+}
@@ -69,6 +69,8 @@ pub struct TextInput {
     last_bounds: Option<Bounds<Pixels>>,
     is_selecting: bool,
     pub style: TextInputStyle,
+    /// The content can be selected and copied but not edited
+    pub read_only: bool,
 }
 
 impl TextInput {
@@ -89,6 +91,7 @@ impl TextInput {
             last_bounds: None,
             is_selecting: false,
             style: style.unwrap_or(TextInputStyle::default()),
+            read_only: false,
         })
     }
 
@@ -354,6 +357,9 @@ impl EntityInputHandler for TextInput {
         _: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        if self.read_only {
+            return;
+        }
         let range = range_utf16
             .as_ref()
             .map(|range_utf16| self.range_from_utf16(range_utf16))
@@ -376,6 +382,9 @@ impl EntityInputHandler for TextInput {
         _window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        if self.read_only {
+            return;
+        }
         let range = range_utf16
             .as_ref()
             .map(|range_utf16| self.range_from_utf16(range_utf16))
@@ -492,6 +501,7 @@ impl Element for TextElement {
         let content = input.content.clone();
         let selected_range = input.selected_range.clone();
         let cursor = input.cursor_offset();
+        let read_only = input.read_only;
         let style = window.text_style();
 
         let (display_text, text_color) = if content.is_empty() {
@@ -603,7 +613,7 @@ impl Element for TextElement {
         };
         PrepaintState {
             line: Some(line),
-            cursor,
+            cursor: cursor.filter(|_| !read_only),
             selection,
             scroll_offset,
         }
@@ -1,52 +1,195 @@
 use crate::backend::db::DatabaseSource;
-use crate::backend::entities::shares;
+use crate::backend::db::repository::{
+    Collection, ShareFilter, ShareSortBy, add_shares_to_collection, delete_expired_shares,
+    delete_shares, get_collections, get_shares, insert_share, regenerate_share_token,
+    relocate_share, set_shares_expiry,
+};
 use crate::backend::entities::shares::Model;
+use crate::backend::watcher::{ShareStatus, ShareWatcher, check_shares};
 use crate::gpui_tokio::Tokio;
 use crate::mimes::get_icon_for_mime;
-use crate::util::open_file_in_file_manager;
+use crate::network::{list_network_interfaces, resolve_advertise_address, server_url};
+use crate::setting::Settings;
+use crate::thumbnail::{get_or_create_thumbnail, is_thumbnail_supported, thumbnail_cache_dir};
+use crate::ui::collections_page::CollectionsPage;
+use crate::ui::component::input::TextInput;
+use crate::ui::server_info_page::ServerInfoPage;
+use crate::util::{expand_folders, open_file_in_file_manager};
+use chrono::{Duration, Local};
 use gpui::prelude::FluentBuilder;
 use gpui::{
-    App, AppContext, AsyncApp, Context, Entity, Fill, Global, ImageSource, InteractiveElement,
-    IntoElement, ParentElement, ReadGlobal, Render, Resource, StatefulInteractiveElement, Styled,
-    TextOverflow, Window, div, img, px, uniform_list,
+    App, AppContext, AsyncApp, ClickEvent, Context, Entity, ExternalPaths, Fill, FocusHandle,
+    Global, Image, ImageSource, InteractiveElement, IntoElement, KeyBinding, ObjectFit,
+    ParentElement, ReadGlobal, Render, Resource, ScrollStrategy, StatefulInteractiveElement,
+    Styled, StyledImage, TextOverflow, UniformListScrollHandle, WeakEntity, Window, actions, div,
+    hsla, img, px, uniform_list,
 };
 use gpui_component::button::{Button, ButtonCustomVariant, ButtonVariant, ButtonVariants};
-use gpui_component::{Icon, IconName, StyledExt, Theme, h_flex, neutral_500};
+use gpui_component::{Icon, IconName, StyledExt, Theme, h_flex, neutral_500, v_flex};
+use log::error;
+use rfd::AsyncFileDialog;
 use rust_i18n::t;
-use sea_orm::EntityTrait;
+use snowflaked::sync::Generator;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+actions!(
+    file_list,
+    [
+        SelectPrevious,
+        SelectNext,
+        ExtendSelectionUp,
+        ExtendSelectionDown,
+        SelectAll,
+        ClearSelection,
+        RemoveSelected,
+    ]
+);
 
 pub struct ShareList {
+    filter: ShareFilter,
     data: Vec<Model>,
 }
 
 impl Default for ShareList {
     fn default() -> Self {
-        Self { data: vec![] }
+        Self {
+            filter: ShareFilter::default(),
+            data: vec![],
+        }
     }
 }
 
 impl Global for ShareList {}
 
-pub struct FileListPage {}
+/// Cached thumbnails of the image shares, keyed by share id
+#[derive(Default)]
+pub struct Thumbnails {
+    paths: HashMap<i64, Arc<Path>>,
+}
+
+impl Global for Thumbnails {}
+
+/// A link of the link dialog and its QR code
+struct ShareLink {
+    url: String,
+    qr_code: Arc<Image>,
+}
+
+impl ShareLink {
+    fn new(url: String) -> Self {
+        let qr_code = ServerInfoPage::create_qr_image(&url);
+        Self { url, qr_code }
+    }
+}
+
+/// The share whose direct link is shown in the link dialog
+struct ShareLinkDialog {
+    file_name: String,
+    download: ShareLink,
+    preview: ShareLink,
+    /// Show the link to the preview page instead of the download
+    show_preview: bool,
+}
+
+impl ShareLinkDialog {
+    /// Resolve the links and build their QR codes once, enumerating the network interfaces
+    /// and encoding the QR code is too slow to do on every render
+    fn new(file_name: String, access_token: &str) -> Self {
+        let settings = Settings::clone();
+        let advertise_address = resolve_advertise_address(
+            &settings.bind_address,
+            settings.advertise_address.as_deref(),
+            &list_network_interfaces(),
+        );
+        let link =
+            |path: String| ShareLink::new(server_url(advertise_address, settings.port, &path));
+        Self {
+            file_name,
+            download: link(format!("api/v1/stream/{access_token}?force_download=true")),
+            preview: link(format!("api/v1/preview/{access_token}")),
+            show_preview: false,
+        }
+    }
+
+    fn link(&self) -> &ShareLink {
+        if self.show_preview {
+            &self.preview
+        } else {
+            &self.download
+        }
+    }
+}
+
+pub struct FileListPage {
+    keyword_input: Entity<TextInput>,
+    link_dialog: Option<ShareLinkDialog>,
+    focus_handle: FocusHandle,
+    scroll_handle: UniformListScrollHandle,
+    /// Ids of the selected shares
+    selected: HashSet<i64>,
+    /// Row moved to by the keyboard or the last click
+    cursor: Option<usize>,
+    /// Row a shift selection starts from
+    anchor: Option<usize>,
+    /// Collections to pick from when adding the selected shares to a collection
+    collection_picker: Option<Vec<Collection>>,
+}
 
 impl FileListPage {
     pub fn new(cx: &mut App) -> Entity<FileListPage> {
-        cx.new(|_cx| FileListPage {})
+        cx.bind_keys([
+            KeyBinding::new("up", SelectPrevious, Some("FileList")),
+            KeyBinding::new("down", SelectNext, Some("FileList")),
+            KeyBinding::new("shift-up", ExtendSelectionUp, Some("FileList")),
+            KeyBinding::new("shift-down", ExtendSelectionDown, Some("FileList")),
+            KeyBinding::new("secondary-a", SelectAll, Some("FileList")),
+            KeyBinding::new("escape", ClearSelection, Some("FileList")),
+            KeyBinding::new("delete", RemoveSelected, Some("FileList")),
+        ]);
+        let keyword_input = TextInput::new(None, Some("Search by file name...".into()), None, cx);
+        cx.new(|cx| FileListPage {
+            keyword_input,
+            link_dialog: None,
+            focus_handle: cx.focus_handle(),
+            scroll_handle: UniformListScrollHandle::new(),
+            selected: HashSet::new(),
+            cursor: None,
+            anchor: None,
+            collection_picker: None,
+        })
     }
 
+    /// Reload the share list with the current filter
     pub fn reload(cx: &mut App) {
         let db = DatabaseSource::global(cx).instance.clone();
+        let filter = cx.default_global::<ShareList>().filter.clone();
         cx.spawn(async move |cx: &mut AsyncApp| {
+            let query_filter = filter.clone();
             let data = Tokio::spawn(cx, async move {
                 let connection = db.connection().await.unwrap();
-                shares::Entity::find().all(&connection).await.unwrap()
+                if let Err(e) = delete_expired_shares(&connection).await {
+                    error!("Failed to delete expired shares, {e:?}");
+                }
+                get_shares(&connection, &query_filter).await.unwrap()
             })
             .unwrap()
             .await
             .unwrap();
 
-            let share_list = ShareList { data };
+            let share_list = ShareList { filter, data };
             cx.update(move |cx: &mut App| {
+                if let Some(watcher) = cx.try_global::<ShareWatcher>() {
+                    watcher.watch(
+                        share_list
+                            .data
+                            .iter()
+                            .map(|item| Path::new(item.file_path.as_str())),
+                    );
+                }
+                Self::load_thumbnails(&share_list.data, cx);
                 cx.set_global::<ShareList>(share_list);
                 cx.refresh_windows();
             })
@@ -54,121 +197,766 @@ impl FileListPage {
         .detach();
     }
 
-    pub fn remove_item(share_id: i64, cx: &mut App) {
+    /// Check whether the shared files were deleted, moved or modified, then reload the share
+    /// list. It reads the metadata of every shared file, so it runs on launch and when the
+    /// share watcher reports a change rather than on every reload.
+    pub fn check_and_reload(cx: &mut App) {
         let db = DatabaseSource::global(cx).instance.clone();
         cx.spawn(async move |cx: &mut AsyncApp| {
-            let data = Tokio::spawn(cx, async move {
+            Tokio::spawn(cx, async move {
                 let connection = db.connection().await.unwrap();
-                shares::Entity::delete_by_id(share_id)
-                    .exec(&connection)
-                    .await
-                    .unwrap();
-                shares::Entity::find().all(&connection).await.unwrap()
+                if let Err(e) = check_shares(&connection).await {
+                    error!("Failed to check shares, {e:?}");
+                }
+            })
+            .unwrap()
+            .await
+            .unwrap();
+
+            cx.update(|cx: &mut App| Self::reload(cx))
+        })
+        .detach();
+    }
+
+    /// Generate the thumbnails of the image shares in the background,
+    /// the mime icon is shown until a thumbnail is ready
+    fn load_thumbnails(share_list: &[Model], cx: &mut App) {
+        let images: Vec<(i64, PathBuf)> = share_list
+            .iter()
+            .filter(|item| is_thumbnail_supported(&item.mime_type))
+            .map(|item| (item.id, PathBuf::from(&item.file_path)))
+            .collect();
+        if images.is_empty() {
+            return;
+        }
+        cx.spawn(async move |cx: &mut AsyncApp| {
+            let paths = Tokio::spawn(cx, async move {
+                tokio::task::spawn_blocking(move || {
+                    let cache_dir = thumbnail_cache_dir();
+                    images
+                        .into_iter()
+                        .filter_map(|(share_id, file_path)| {
+                            match get_or_create_thumbnail(&cache_dir, &file_path) {
+                                Ok(thumbnail) => Some((share_id, Arc::from(thumbnail))),
+                                Err(e) => {
+                                    error!("Failed to create thumbnail for {:?}, {e:?}", file_path);
+                                    None
+                                }
+                            }
+                        })
+                        .collect::<HashMap<i64, Arc<Path>>>()
+                })
+                .await
+                .unwrap()
             })
             .unwrap()
             .await
             .unwrap();
 
-            let share_list = ShareList { data };
             cx.update(move |cx: &mut App| {
-                cx.set_global::<ShareList>(share_list);
+                cx.set_global::<Thumbnails>(Thumbnails { paths });
                 cx.refresh_windows();
             })
         })
         .detach();
     }
-}
 
-impl Render for FileListPage {
-    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
-        let background = Theme::global(cx).background;
-        let share_list_size = cx.default_global::<ShareList>().data.len();
+    /// Add the files to the share list, the files inside folders are added one by one
+    pub fn add_files(paths: Vec<PathBuf>, cx: &mut App) {
+        let db = DatabaseSource::global(cx).instance.clone();
+        cx.spawn(async move |cx: &mut AsyncApp| {
+            Tokio::spawn(cx, async move {
+                let files = tokio::task::spawn_blocking(move || expand_folders(&paths))
+                    .await
+                    .unwrap();
+                let connection = db.connection().await.unwrap();
+                let generator = Generator::new(0);
 
-        div().size_full().bg(background).child(
-            uniform_list(
-                "entries",
-                share_list_size,
-                cx.processor(|_this, range, _window, cx| {
-                    let mut items = Vec::new();
-                    let theme = Theme::global(cx);
-                    let share_list = ShareList::global(cx);
-
-                    for ix in range {
-                        let item = share_list.data.get(ix);
-                        items.push(
-                            div()
-                                .id(ix)
-                                .h_full()
-                                .pl(px(16.))
-                                .pr(px(16.))
-                                .h(px(48.))
-                                .bg(theme.colors.list)
-                                .text_color(theme.colors.foreground)
-                                .hover(|mut style| {
-                                    style.background = Some(Fill::from(theme.colors.list_hover));
-
-                                    style
-                                })
-                                .active(|mut style| {
-                                    style.background = Some(Fill::from(theme.colors.list_active));
-                                    style
-                                })
-                                .on_click(move |_event, _window, _cx| {
-                                    println!("clicked Item {ix:?}");
+                for file in files {
+                    if let Err(e) = insert_share(&connection, generator.generate(), &file).await {
+                        error!("Failed to insert share file: {}", e);
+                    }
+                }
+            })
+            .unwrap()
+            .await
+            .unwrap();
+
+            cx.update(|cx: &mut App| {
+                FileListPage::reload(cx);
+            })
+        })
+        .detach();
+    }
+
+    /// Let the user pick the new location of a missing or changed file
+    pub fn relocate_item(share_id: i64, cx: &mut App) {
+        let db = DatabaseSource::global(cx).instance.clone();
+        cx.spawn(async move |cx: &mut AsyncApp| {
+            Tokio::spawn(cx, async move {
+                let Some(file) = AsyncFileDialog::new().pick_file().await else {
+                    return;
+                };
+                let connection = db.connection().await.unwrap();
+                if let Err(e) = relocate_share(&connection, share_id, file.path()).await {
+                    error!("Failed to relocate share: {e:?}");
+                }
+            })
+            .unwrap()
+            .await
+            .unwrap();
+
+            cx.update(|cx: &mut App| {
+                FileListPage::reload(cx);
+            })
+        })
+        .detach();
+    }
+
+    /// Give the share a new access token, links shared before stop working
+    pub fn regenerate_link(share_id: i64, cx: &mut App) {
+        let db = DatabaseSource::global(cx).instance.clone();
+        cx.spawn(async move |cx: &mut AsyncApp| {
+            Tokio::spawn(cx, async move {
+                let connection = db.connection().await.unwrap();
+                if let Err(e) = regenerate_share_token(&connection, share_id).await {
+                    error!("Failed to regenerate access token: {e:?}");
+                }
+            })
+            .unwrap()
+            .await
+            .unwrap();
+
+            cx.update(|cx: &mut App| {
+                FileListPage::reload(cx);
+            })
+        })
+        .detach();
+    }
+
+    pub fn remove_items(share_ids: Vec<i64>, cx: &mut App) {
+        let db = DatabaseSource::global(cx).instance.clone();
+        cx.spawn(async move |cx: &mut AsyncApp| {
+            Tokio::spawn(cx, async move {
+                let connection = db.connection().await.unwrap();
+                if let Err(e) = delete_shares(&connection, &share_ids).await {
+                    error!("Failed to remove shares: {e:?}");
+                }
+            })
+            .unwrap()
+            .await
+            .unwrap();
+
+            cx.update(|cx: &mut App| {
+                FileListPage::reload(cx);
+            })
+        })
+        .detach();
+    }
+
+    /// Let the shares expire after the duration, `None` keeps them until removed
+    fn set_expiry(share_ids: Vec<i64>, expires_in: Option<Duration>, cx: &mut App) {
+        let db = DatabaseSource::global(cx).instance.clone();
+        let expires_at = expires_in.map(|expires_in| Local::now().naive_local() + expires_in);
+        cx.spawn(async move |cx: &mut AsyncApp| {
+            Tokio::spawn(cx, async move {
+                let connection = db.connection().await.unwrap();
+                if let Err(e) = set_shares_expiry(&connection, &share_ids, expires_at).await {
+                    error!("Failed to set expiry of shares: {e:?}");
+                }
+            })
+            .unwrap()
+            .await
+            .unwrap();
+
+            cx.update(|cx: &mut App| {
+                FileListPage::reload(cx);
+            })
+        })
+        .detach();
+    }
+
+    fn add_to_collection(collection_id: i64, share_ids: Vec<i64>, cx: &mut App) {
+        let db = DatabaseSource::global(cx).instance.clone();
+        cx.spawn(async move |cx: &mut AsyncApp| {
+            Tokio::spawn(cx, async move {
+                let connection = db.connection().await.unwrap();
+                if let Err(e) =
+                    add_shares_to_collection(&connection, collection_id, &share_ids).await
+                {
+                    error!("Failed to add shares to collection: {e:?}");
+                }
+            })
+            .unwrap()
+            .await
+            .unwrap();
+
+            cx.update(|cx: &mut App| {
+                CollectionsPage::reload(cx);
+            })
+        })
+        .detach();
+    }
+
+    fn show_collection_picker(cx: &mut Context<Self>) {
+        let db = DatabaseSource::global(cx).instance.clone();
+        cx.spawn(
+            async move |this: WeakEntity<FileListPage>, cx: &mut AsyncApp| {
+                let collections = Tokio::spawn(cx, async move {
+                    let connection = db.connection().await.unwrap();
+                    get_collections(&connection).await
+                })
+                .unwrap()
+                .await
+                .unwrap();
+
+                match collections {
+                    Ok(collections) => {
+                        if let Some(file_list_page) = this.upgrade() {
+                            file_list_page
+                                .update(cx, |this, cx| {
+                                    this.collection_picker = Some(collections);
+                                    cx.notify();
                                 })
-                                .when_some(item, |this, item: &Model| {
-                                    let share_id = item.id;
-                                    let file_name = item.file_name.clone();
-                                    let file_path = item.file_path.clone();
-                                    let icon_file_mime = get_icon_for_mime(&item.mime_type);
-                                    this.child(
-                                        h_flex()
-                                            .h_full()
-                                            .gap_4()
-                                            .items_center()
-                                            .justify_between()
-                                            .flex_nowrap()
-                                            .child(
-                                                img(ImageSource::Resource(Resource::Embedded(
-                                                    icon_file_mime.into(),
-                                                )))
+                                .unwrap();
+                        }
+                    }
+                    Err(e) => error!("Failed to query collections: {e:?}"),
+                }
+            },
+        )
+        .detach();
+    }
+
+    /// The selected shares in the order of the list, shares removed since are skipped
+    fn selected_ids(&self, cx: &App) -> Vec<i64> {
+        ShareList::global(cx)
+            .data
+            .iter()
+            .map(|item| item.id)
+            .filter(|share_id| self.selected.contains(share_id))
+            .collect()
+    }
+
+    /// The row above or below the cursor, the first row when there is no cursor yet
+    fn next_row(&self, up: bool, cx: &App) -> Option<usize> {
+        let len = ShareList::global(cx).data.len();
+        if len == 0 {
+            return None;
+        }
+        let ix = match self.cursor {
+            Some(cursor) if up => cursor.saturating_sub(1),
+            Some(cursor) => cursor + 1,
+            None => 0,
+        };
+        Some(ix.min(len - 1))
+    }
+
+    fn move_cursor(&mut self, ix: usize, cx: &mut Context<Self>) {
+        self.cursor = Some(ix);
+        self.scroll_handle.scroll_to_item(ix, ScrollStrategy::Top);
+        cx.notify();
+    }
+
+    fn select_only(&mut self, ix: usize, cx: &mut Context<Self>) {
+        let Some(share_id) = ShareList::global(cx).data.get(ix).map(|item| item.id) else {
+            return;
+        };
+        self.selected = HashSet::from([share_id]);
+        self.anchor = Some(ix);
+        self.move_cursor(ix, cx);
+    }
+
+    /// Select the rows between the anchor and the row
+    fn select_range(&mut self, ix: usize, cx: &mut Context<Self>) {
+        let anchor = self.anchor.unwrap_or(ix);
+        let range = anchor.min(ix)..=anchor.max(ix);
+        self.selected = ShareList::global(cx)
+            .data
+            .iter()
+            .enumerate()
+            .filter(|(row, _)| range.contains(row))
+            .map(|(_, item)| item.id)
+            .collect();
+        self.anchor = Some(anchor);
+        self.move_cursor(ix, cx);
+    }
+
+    fn toggle_row(&mut self, ix: usize, cx: &mut Context<Self>) {
+        let Some(share_id) = ShareList::global(cx).data.get(ix).map(|item| item.id) else {
+            return;
+        };
+        if !self.selected.remove(&share_id) {
+            self.selected.insert(share_id);
+        }
+        self.anchor = Some(ix);
+        self.move_cursor(ix, cx);
+    }
+
+    /// Shift-click selects a range, ctrl-click (cmd-click on macOS) toggles a row
+    fn handle_row_click(
+        &mut self,
+        ix: usize,
+        event: &ClickEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.focus_handle.focus(window);
+        let modifiers = event.modifiers();
+        if modifiers.shift {
+            self.select_range(ix, cx);
+        } else if modifiers.secondary() {
+            self.toggle_row(ix, cx);
+        } else {
+            self.select_only(ix, cx);
+        }
+    }
+
+    fn select_previous(&mut self, _: &SelectPrevious, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some(ix) = self.next_row(true, cx) {
+            self.select_only(ix, cx);
+        }
+    }
+
+    fn select_next(&mut self, _: &SelectNext, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some(ix) = self.next_row(false, cx) {
+            self.select_only(ix, cx);
+        }
+    }
+
+    fn extend_selection_up(
+        &mut self,
+        _: &ExtendSelectionUp,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(ix) = self.next_row(true, cx) {
+            self.select_range(ix, cx);
+        }
+    }
+
+    fn extend_selection_down(
+        &mut self,
+        _: &ExtendSelectionDown,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(ix) = self.next_row(false, cx) {
+            self.select_range(ix, cx);
+        }
+    }
+
+    fn select_all(&mut self, _: &SelectAll, _: &mut Window, cx: &mut Context<Self>) {
+        self.selected = ShareList::global(cx)
+            .data
+            .iter()
+            .map(|item| item.id)
+            .collect();
+        cx.notify();
+    }
+
+    fn clear_selection(&mut self, _: &ClearSelection, _: &mut Window, cx: &mut Context<Self>) {
+        self.selected.clear();
+        self.anchor = None;
+        self.collection_picker = None;
+        cx.notify();
+    }
+
+    fn remove_selected(&mut self, _: &RemoveSelected, window: &mut Window, cx: &mut Context<Self>) {
+        let share_ids = self.selected_ids(cx);
+        if share_ids.is_empty() {
+            return;
+        }
+        Self::remove_items(share_ids, cx);
+        self.cursor = None;
+        self.clear_selection(&ClearSelection, window, cx);
+    }
+
+    fn set_filter(filter: ShareFilter, cx: &mut App) {
+        cx.default_global::<ShareList>().filter = filter;
+        Self::reload(cx);
+    }
+
+    fn handle_search(&self, cx: &mut Context<Self>) {
+        let keyword = self.keyword_input.read(cx).content.trim().to_string();
+        let mut filter = cx.default_global::<ShareList>().filter.clone();
+        filter.keyword = Some(keyword).filter(|keyword| !keyword.is_empty());
+        Self::set_filter(filter, cx);
+    }
+
+    /// Sort by the field, clicking the field already sorted by flips the order
+    fn handle_sort(sort_by: ShareSortBy, cx: &mut App) {
+        let mut filter = cx.default_global::<ShareList>().filter.clone();
+        if filter.sort_by == sort_by {
+            filter.descending = !filter.descending;
+        } else {
+            filter.sort_by = sort_by;
+            filter.descending = false;
+        }
+        Self::set_filter(filter, cx);
+    }
+
+    fn sort_button(
+        id: &'static str,
+        label: String,
+        sort_by: ShareSortBy,
+        cx: &Context<Self>,
+    ) -> Button {
+        let filter = &ShareList::global(cx).filter;
+        let (variant, label) = if filter.sort_by == sort_by {
+            let arrow = if filter.descending { "↓" } else { "↑" };
+            (ButtonVariant::Primary, format!("{label} {arrow}"))
+        } else {
+            (ButtonVariant::Ghost, label)
+        };
+        Button::new(id)
+            .with_variant(variant)
+            .child(label)
+            .on_click(move |_ev, _window, cx| {
+                Self::handle_sort(sort_by, cx);
+            })
+    }
+}
+
+impl FileListPage {
+    /// Actions on the selected shares, shown while the selection is not empty
+    fn render_bulk_toolbar(&self, share_ids: Vec<i64>, cx: &Context<Self>) -> impl IntoElement {
+        let theme = Theme::global(cx);
+        let expiry_options = [
+            (
+                "expire-in-hour",
+                t!("label.expire-in-hour"),
+                Some(Duration::hours(1)),
+            ),
+            (
+                "expire-in-day",
+                t!("label.expire-in-day"),
+                Some(Duration::days(1)),
+            ),
+            (
+                "expire-in-week",
+                t!("label.expire-in-week"),
+                Some(Duration::weeks(1)),
+            ),
+            ("expire-never", t!("label.expire-never"), None),
+        ];
+
+        v_flex()
+            .pl_4()
+            .pr_4()
+            .pb_2()
+            .gap_2()
+            .border_b_1()
+            .border_color(theme.colors.border)
+            .text_color(theme.colors.foreground)
+            .child(
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .child(
+                        div()
+                            .font_bold()
+                            .child(t!("label.selected-count", count = share_ids.len()).to_string()),
+                    )
+                    .child(div().flex_grow())
+                    .child(div().child(t!("label.expire-in").to_string()))
+                    .children(expiry_options.into_iter().map(|(id, label, expires_in)| {
+                        let share_ids = share_ids.clone();
+                        Button::new(id)
+                            .with_variant(ButtonVariant::Ghost)
+                            .child(label.to_string())
+                            .on_click(move |_ev, _window, cx| {
+                                Self::set_expiry(share_ids.clone(), expires_in, cx);
+                            })
+                    }))
+                    .child(
+                        Button::new("add-selected-to-collection")
+                            .icon(Icon::new(IconName::GalleryVerticalEnd).text_color(neutral_500()))
+                            .with_variant(ButtonVariant::Ghost)
+                            .tooltip(t!("tooltip.add-selected-to-collection"))
+                            .on_click(cx.listener(|_this, _ev, _window, cx| {
+                                Self::show_collection_picker(cx);
+                            })),
+                    )
+                    .child(
+                        Button::new("remove-selected")
+                            .icon(Icon::new(IconName::Delete).text_color(theme.colors.danger))
+                            .with_variant(ButtonVariant::Ghost)
+                            .tooltip(t!("tooltip.remove-selected"))
+                            .on_click(cx.listener(|this, _ev, window, cx| {
+                                this.remove_selected(&RemoveSelected, window, cx);
+                            })),
+                    )
+                    .child(
+                        Button::new("clear-selection")
+                            .icon(Icon::new(IconName::Close).text_color(neutral_500()))
+                            .with_variant(ButtonVariant::Ghost)
+                            .tooltip(t!("tooltip.clear-selection"))
+                            .on_click(cx.listener(|this, _ev, window, cx| {
+                                this.clear_selection(&ClearSelection, window, cx);
+                            })),
+                    ),
+            )
+            .when_some(self.collection_picker.as_ref(), |this, collections| {
+                this.child(
+                    h_flex()
+                        .gap_2()
+                        .items_center()
+                        .flex_wrap()
+                        .child(div().child(t!("label.add-to-collection").to_string()))
+                        .when(collections.is_empty(), |this| {
+                            this.child(
+                                div()
+                                    .text_color(neutral_500())
+                                    .child(t!("label.no-collections").to_string()),
+                            )
+                        })
+                        .children(collections.iter().map(|collection| {
+                            let collection_id = collection.id;
+                            let share_ids = share_ids.clone();
+                            Button::new(("collection", collection_id as u64))
+                                .with_variant(ButtonVariant::Secondary)
+                                .child(collection.name.clone())
+                                .on_click(cx.listener(move |this, _ev, _window, cx| {
+                                    Self::add_to_collection(collection_id, share_ids.clone(), cx);
+                                    this.collection_picker = None;
+                                    cx.notify();
+                                }))
+                        })),
+                )
+            })
+    }
+
+    fn render_link_dialog(&self, dialog: &ShareLinkDialog, cx: &Context<Self>) -> impl IntoElement {
+        let theme = Theme::global(cx);
+        let link = dialog.link();
+        let url = link.url.clone();
+        let show_preview = dialog.show_preview;
+        let link_button = |id: &'static str, label: String, is_preview: bool| {
+            let variant = if show_preview == is_preview {
+                ButtonVariant::Primary
+            } else {
+                ButtonVariant::Ghost
+            };
+            Button::new(id)
+                .with_variant(variant)
+                .child(label)
+                .on_click(cx.listener(move |this, _ev, _window, cx| {
+                    if let Some(dialog) = this.link_dialog.as_mut() {
+                        dialog.show_preview = is_preview;
+                    }
+                    cx.notify();
+                }))
+        };
+
+        div()
+            .id("share-link-dialog")
+            .absolute()
+            .size_full()
+            .flex()
+            .items_center()
+            .justify_center()
+            .bg(hsla(0., 0., 0., 0.4))
+            .occlude()
+            .on_click(cx.listener(|this, _ev, _window, cx| {
+                this.link_dialog = None;
+                cx.notify();
+            }))
+            .child(
+                v_flex()
+                    .id("share-link-card")
+                    .w(px(420.))
+                    .p_4()
+                    .gap_2()
+                    .items_center()
+                    .bg(theme.colors.background)
+                    .text_color(theme.colors.foreground)
+                    .rounded_2xl()
+                    .border_1()
+                    .border_color(theme.colors.border)
+                    // Clicks inside the card should not close the dialog
+                    .on_click(|_ev, _window, cx| cx.stop_propagation())
+                    .child(
+                        h_flex()
+                            .w_full()
+                            .gap_2()
+                            .justify_between()
+                            .child(
+                                div()
+                                    .min_w_0()
+                                    .font_bold()
+                                    .overflow_x_hidden()
+                                    .text_overflow(TextOverflow::Truncate("...".into()))
+                                    .child(dialog.file_name.clone()),
+                            )
+                            .child(
+                                Button::new("close-share-link-dialog")
+                                    .icon(Icon::new(IconName::Close).text_color(neutral_500()))
+                                    .with_variant(ButtonVariant::Ghost)
+                                    .on_click(cx.listener(|this, _ev, _window, cx| {
+                                        this.link_dialog = None;
+                                        cx.notify();
+                                    })),
+                            ),
+                    )
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .child(link_button(
+                                "download-link",
+                                t!("label.download").to_string(),
+                                false,
+                            ))
+                            .child(link_button(
+                                "preview-link",
+                                t!("label.preview").to_string(),
+                                true,
+                            )),
+                    )
+                    .child(img(ImageSource::Image(link.qr_code.clone())).size(px(240.)))
+                    .child(
+                        h_flex()
+                            .w_full()
+                            .gap_2()
+                            .items_center()
+                            .child(
+                                div()
+                                    .min_w_0()
+                                    .flex_grow()
+                                    .overflow_x_hidden()
+                                    .text_overflow(TextOverflow::Truncate("...".into()))
+                                    .child(url.clone()),
+                            )
+                            .child(
+                                Button::new("copy-share-link")
+                                    .icon(Icon::new(IconName::Copy).text_color(neutral_500()))
+                                    .with_variant(ButtonVariant::Ghost)
+                                    .on_click(move |_ev, _window, _cx| {
+                                        ServerInfoPage::copy_to_clipboard(url.clone());
+                                    }),
+                            ),
+                    ),
+            )
+    }
+
+    /// The shared files, rendered lazily since the list can be long
+    fn render_share_list(&self, share_list_size: usize, cx: &Context<Self>) -> impl IntoElement {
+        uniform_list(
+            "entries",
+            share_list_size,
+            cx.processor(|this, range, _window, cx| {
+                let mut items = Vec::new();
+                let theme = Theme::global(cx);
+                let share_list = ShareList::global(cx);
+                let thumbnails = cx.try_global::<Thumbnails>();
+
+                for ix in range {
+                    let item = share_list.data.get(ix);
+                    let selected = item.is_some_and(|item| this.selected.contains(&item.id));
+                    let background = if selected {
+                        theme.colors.list_active
+                    } else {
+                        theme.colors.list
+                    };
+                    items.push(
+                        div()
+                            .id(ix)
+                            .h_full()
+                            .pl(px(16.))
+                            .pr(px(16.))
+                            .h(px(48.))
+                            .bg(background)
+                            .when(this.cursor == Some(ix), |this| {
+                                this.border_l_2().border_color(theme.colors.primary)
+                            })
+                            .text_color(theme.colors.foreground)
+                            .hover(|mut style| {
+                                style.background = Some(Fill::from(theme.colors.list_hover));
+
+                                style
+                            })
+                            .active(|mut style| {
+                                style.background = Some(Fill::from(theme.colors.list_active));
+                                style
+                            })
+                            .on_click(cx.listener(move |this, event, window, cx| {
+                                this.handle_row_click(ix, event, window, cx);
+                            }))
+                            .when_some(item, |this, item: &Model| {
+                                let share_id = item.id;
+                                let file_name = item.file_name.clone();
+                                let link_file_name = item.file_name.clone();
+                                let access_token = item.access_token.clone();
+                                let file_path = item.file_path.clone();
+                                let expires_at = item.expires_at.map(|expires_at| {
+                                    t!(
+                                        "label.expires-at",
+                                        time = expires_at.format("%Y-%m-%d %H:%M")
+                                    )
+                                    .to_string()
+                                });
+                                let status = ShareStatus::from_str(&item.status)
+                                    .unwrap_or(ShareStatus::Available);
+                                let icon = match thumbnails
+                                    .and_then(|thumbnails| thumbnails.paths.get(&share_id))
+                                {
+                                    Some(thumbnail) => Resource::Path(thumbnail.clone()),
+                                    None => Resource::Embedded(
+                                        get_icon_for_mime(&item.mime_type).into(),
+                                    ),
+                                };
+                                this.child(
+                                    h_flex()
+                                        .h_full()
+                                        .gap_4()
+                                        .items_center()
+                                        .justify_between()
+                                        .flex_nowrap()
+                                        .child(
+                                            img(ImageSource::Resource(icon))
                                                 .size_8()
-                                                .flex_none(),
-                                            )
-                                            .child(
+                                                .flex_none()
+                                                .rounded_sm()
+                                                .object_fit(ObjectFit::Cover),
+                                        )
+                                        .child(
+                                            div()
+                                                .min_w_0()
+                                                .flex_grow()
+                                                .flex_shrink()
+                                                .overflow_x_hidden()
+                                                .text_overflow(TextOverflow::Truncate("...".into()))
+                                                .child(file_name),
+                                        )
+                                        .when_some(expires_at, |this, expires_at| {
+                                            this.child(
                                                 div()
-                                                    .min_w_0()
-                                                    .flex_grow()
-                                                    .flex_shrink()
-                                                    .overflow_x_hidden()
-                                                    .text_overflow(TextOverflow::Truncate(
-                                                        "...".into(),
-                                                    ))
-                                                    .child(file_name),
+                                                    .flex_none()
+                                                    .text_sm()
+                                                    .text_color(neutral_500())
+                                                    .child(expires_at),
                                             )
-                                            .child(
-                                                Button::new("remove-button")
+                                        })
+                                        .when(status != ShareStatus::Available, |this| {
+                                            let tooltip = match status {
+                                                ShareStatus::Missing => {
+                                                    t!("tooltip.share-missing")
+                                                }
+                                                _ => t!("tooltip.share-changed"),
+                                            };
+                                            this.child(
+                                                Button::new("share-status")
                                                     .icon(
-                                                        Icon::new(IconName::Delete)
-                                                            .text_color(theme.colors.danger),
+                                                        Icon::new(IconName::TriangleAlert)
+                                                            .text_color(theme.colors.warning),
                                                     )
-                                                    .with_variant(ButtonVariant::Custom(
-                                                        ButtonCustomVariant::new(cx)
-                                                            .hover(theme.colors.primary_hover)
-                                                            .active(theme.colors.primary_active),
-                                                    ))
-                                                    .tooltip(t!(
-                                                        "tooltip.remove-file-from-share-list"
-                                                    ))
-                                                    .on_click(move |_ev, _window, cx| {
-                                                        cx.stop_propagation();
-                                                        FileListPage::remove_item(share_id, cx);
-                                                    }),
+                                                    .with_variant(ButtonVariant::Ghost)
+                                                    .tooltip(tooltip),
                                             )
                                             .child(
-                                                Button::new("open-location-button")
+                                                Button::new("relocate-button")
                                                     .icon(
-                                                        Icon::new(IconName::Folder)
+                                                        Icon::new(IconName::FolderOpen)
                                                             .text_color(neutral_500()),
                                                     )
                                                     .with_variant(ButtonVariant::Custom(
@@ -176,22 +964,196 @@ impl Render for FileListPage {
                                                             .hover(theme.colors.primary_hover)
                                                             .active(theme.colors.primary_active),
                                                     ))
-                                                    .tooltip(t!(
-                                                        "tooltip.open-file-in-file-manager"
-                                                    ))
+                                                    .tooltip(t!("tooltip.relocate-share"))
                                                     .on_click(move |_, _, cx| {
                                                         cx.stop_propagation();
-                                                        open_file_in_file_manager(&file_path);
+                                                        FileListPage::relocate_item(share_id, cx);
                                                     }),
-                                            ),
-                                    )
-                                }),
-                        );
-                    }
-                    items
-                }),
-            )
-            .h_full(),
+                                            )
+                                        })
+                                        .child(
+                                            Button::new("share-link-button")
+                                                .icon(
+                                                    Icon::new(IconName::ExternalLink)
+                                                        .text_color(neutral_500()),
+                                                )
+                                                .with_variant(ButtonVariant::Custom(
+                                                    ButtonCustomVariant::new(cx)
+                                                        .hover(theme.colors.primary_hover)
+                                                        .active(theme.colors.primary_active),
+                                                ))
+                                                .tooltip(t!("tooltip.share-link"))
+                                                .on_click(cx.listener(move |this, _, _, cx| {
+                                                    cx.stop_propagation();
+                                                    this.link_dialog = Some(ShareLinkDialog::new(
+                                                        link_file_name.clone(),
+                                                        &access_token,
+                                                    ));
+                                                    cx.notify();
+                                                })),
+                                        )
+                                        .child(
+                                            Button::new("regenerate-link-button")
+                                                .icon(
+                                                    Icon::new(IconName::Redo)
+                                                        .text_color(neutral_500()),
+                                                )
+                                                .with_variant(ButtonVariant::Custom(
+                                                    ButtonCustomVariant::new(cx)
+                                                        .hover(theme.colors.primary_hover)
+                                                        .active(theme.colors.primary_active),
+                                                ))
+                                                .tooltip(t!("tooltip.regenerate-link"))
+                                                .on_click(move |_, _, cx| {
+                                                    cx.stop_propagation();
+                                                    FileListPage::regenerate_link(share_id, cx);
+                                                }),
+                                        )
+                                        .child(
+                                            Button::new("remove-button")
+                                                .icon(
+                                                    Icon::new(IconName::Delete)
+                                                        .text_color(theme.colors.danger),
+                                                )
+                                                .with_variant(ButtonVariant::Custom(
+                                                    ButtonCustomVariant::new(cx)
+                                                        .hover(theme.colors.primary_hover)
+                                                        .active(theme.colors.primary_active),
+                                                ))
+                                                .tooltip(t!("tooltip.remove-file-from-share-list"))
+                                                .on_click(move |_ev, _window, cx| {
+                                                    cx.stop_propagation();
+                                                    FileListPage::remove_items(vec![share_id], cx);
+                                                }),
+                                        )
+                                        .child(
+                                            Button::new("open-location-button")
+                                                .icon(
+                                                    Icon::new(IconName::Folder)
+                                                        .text_color(neutral_500()),
+                                                )
+                                                .with_variant(ButtonVariant::Custom(
+                                                    ButtonCustomVariant::new(cx)
+                                                        .hover(theme.colors.primary_hover)
+                                                        .active(theme.colors.primary_active),
+                                                ))
+                                                .tooltip(t!("tooltip.open-file-in-file-manager"))
+                                                .on_click(move |_, _, cx| {
+                                                    cx.stop_propagation();
+                                                    open_file_in_file_manager(&file_path);
+                                                }),
+                                        ),
+                                )
+                            }),
+                    );
+                }
+                items
+            }),
         )
+        .track_scroll(self.scroll_handle.clone())
+        .size_full()
+    }
+}
+
+impl Render for FileListPage {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let share_list_size = cx.default_global::<ShareList>().data.len();
+        let selected_ids = self.selected_ids(cx);
+        // Files dragged from the file manager are an active drag of `ExternalPaths`
+        let dragging_files = cx.has_active_drag();
+        let theme = Theme::global(cx);
+        let drop_target = theme.colors.drop_target;
+
+        v_flex()
+            .relative()
+            .size_full()
+            .bg(theme.background)
+            .drag_over::<ExternalPaths>(move |style, _, _, _| style.bg(drop_target))
+            .on_drop(|paths: &ExternalPaths, _window, cx| {
+                FileListPage::add_files(paths.paths().to_vec(), cx);
+            })
+            .child(
+                h_flex()
+                    .h_10()
+                    .pl_4()
+                    .pr_4()
+                    .gap_2()
+                    .child(
+                        div()
+                            .flex_grow()
+                            .border_b_1()
+                            .border_color(theme.colors.input)
+                            .text_color(theme.colors.foreground)
+                            .child(self.keyword_input.clone()),
+                    )
+                    .child(
+                        Button::new("search-shares")
+                            .with_variant(ButtonVariant::Secondary)
+                            .child(t!("label.search").to_string())
+                            .on_click(cx.listener(|this, _ev, _window, cx| {
+                                this.handle_search(cx);
+                            })),
+                    )
+                    .child(Self::sort_button(
+                        "sort-by-added",
+                        t!("label.sort-by-added").to_string(),
+                        ShareSortBy::Added,
+                        cx,
+                    ))
+                    .child(Self::sort_button(
+                        "sort-by-name",
+                        t!("label.sort-by-name").to_string(),
+                        ShareSortBy::Name,
+                        cx,
+                    ))
+                    .child(Self::sort_button(
+                        "sort-by-size",
+                        t!("label.sort-by-size").to_string(),
+                        ShareSortBy::Size,
+                        cx,
+                    ))
+                    .child(Self::sort_button(
+                        "sort-by-date",
+                        t!("label.sort-by-date").to_string(),
+                        ShareSortBy::Date,
+                        cx,
+                    )),
+            )
+            .when(!selected_ids.is_empty(), |this| {
+                this.child(self.render_bulk_toolbar(selected_ids, cx))
+            })
+            .child(
+                div()
+                    .flex_grow()
+                    .key_context("FileList")
+                    .track_focus(&self.focus_handle)
+                    .on_action(cx.listener(Self::select_previous))
+                    .on_action(cx.listener(Self::select_next))
+                    .on_action(cx.listener(Self::extend_selection_up))
+                    .on_action(cx.listener(Self::extend_selection_down))
+                    .on_action(cx.listener(Self::select_all))
+                    .on_action(cx.listener(Self::clear_selection))
+                    .on_action(cx.listener(Self::remove_selected))
+                    .child(self.render_share_list(share_list_size, cx)),
+            )
+            .when(dragging_files, |this| {
+                this.child(
+                    div()
+                        .absolute()
+                        .size_full()
+                        .flex()
+                        .items_center()
+                        .justify_center()
+                        .bg(theme.colors.drop_target)
+                        .border_2()
+                        .border_color(theme.colors.primary)
+                        .text_color(theme.colors.foreground)
+                        .font_bold()
+                        .child(t!("label.drop-files-to-share").to_string()),
+                )
+            })
+            .when_some(self.link_dialog.as_ref(), |this, dialog| {
+                this.child(self.render_link_dialog(dialog, cx))
+            })
     }
 }
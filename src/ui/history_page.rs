@@ -0,0 +1,350 @@
+use crate::backend::db::DatabaseSource;
+use crate::backend::db::repository::{Transfer, TransferFilter, get_devices, get_transfers};
+use crate::backend::web::transfers::TransferDirection;
+use crate::gpui_tokio::Tokio;
+use crate::ui::component::input::TextInput;
+use crate::ui::root_view::Pages;
+use crate::util::{format_bytes, to_csv};
+use gpui::prelude::FluentBuilder;
+use gpui::{
+    App, AppContext, AsyncApp, Context, Entity, Global, InteractiveElement, IntoElement,
+    ParentElement, ReadGlobal, Render, Styled, TextOverflow, UpdateGlobal, Window, black, div, px,
+    uniform_list,
+};
+use gpui_component::button::{Button, ButtonVariant, ButtonVariants};
+use gpui_component::{
+    Icon, IconName, StyledExt, Theme, ThemeMode, gray_100, h_flex, neutral_400, neutral_500, v_flex,
+};
+use log::{error, info};
+use rfd::AsyncFileDialog;
+use rust_i18n::t;
+use std::collections::HashMap;
+
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+#[derive(Default)]
+pub struct TransferHistory {
+    filter: TransferFilter,
+    data: Vec<Transfer>,
+    /// Device id to the name the web client registered
+    device_names: HashMap<String, String>,
+}
+
+impl TransferHistory {
+    fn device_name(&self, transfer: &Transfer) -> Option<&String> {
+        transfer
+            .device_id
+            .as_ref()
+            .and_then(|device_id| self.device_names.get(device_id))
+    }
+
+    /// The registered device name of the client, or its ip if the device is unknown
+    fn client_name(&self, transfer: &Transfer) -> String {
+        self.device_name(transfer)
+            .cloned()
+            .unwrap_or_else(|| transfer.client_ip.clone())
+    }
+}
+
+impl Global for TransferHistory {}
+
+pub struct HistoryPage {
+    keyword_input: Entity<TextInput>,
+}
+
+impl HistoryPage {
+    pub fn new(cx: &mut App) -> Entity<HistoryPage> {
+        let keyword_input = TextInput::new(
+            None,
+            Some("Search by file name, device or client ip...".into()),
+            None,
+            cx,
+        );
+        cx.new(|_cx| HistoryPage { keyword_input })
+    }
+
+    /// Reload the transfer history with the current filter
+    pub fn reload(cx: &mut App) {
+        let db = DatabaseSource::global(cx).instance.clone();
+        let filter = cx.default_global::<TransferHistory>().filter.clone();
+        cx.spawn(async move |cx: &mut AsyncApp| {
+            let query_filter = filter.clone();
+            let (data, devices) = Tokio::spawn(cx, async move {
+                let connection = db.connection().await.unwrap();
+                let data = get_transfers(&connection, &query_filter).await.unwrap();
+                let devices = get_devices(&connection).await.unwrap();
+                (data, devices)
+            })
+            .unwrap()
+            .await
+            .unwrap();
+
+            let device_names = devices
+                .into_iter()
+                .map(|device| (device.id, device.name))
+                .collect();
+            let transfer_history = TransferHistory {
+                filter,
+                data,
+                device_names,
+            };
+            cx.update(move |cx: &mut App| {
+                cx.set_global::<TransferHistory>(transfer_history);
+                cx.refresh_windows();
+            })
+        })
+        .detach();
+    }
+
+    fn set_filter(filter: TransferFilter, cx: &mut App) {
+        cx.default_global::<TransferHistory>().filter = filter;
+        Self::reload(cx);
+    }
+
+    fn handle_select_direction(direction: Option<TransferDirection>, cx: &mut App) {
+        let mut filter = cx.default_global::<TransferHistory>().filter.clone();
+        filter.direction = direction.map(|direction| direction.to_string());
+        Self::set_filter(filter, cx);
+    }
+
+    fn handle_search(&self, cx: &mut Context<Self>) {
+        let keyword = self.keyword_input.read(cx).content.trim().to_string();
+        let mut filter = cx.default_global::<TransferHistory>().filter.clone();
+        filter.keyword = Some(keyword).filter(|keyword| !keyword.is_empty());
+        Self::set_filter(filter, cx);
+    }
+
+    /// Export the transfers currently shown to a CSV file chosen by the user
+    fn handle_export(cx: &mut App) {
+        let mut rows = vec![
+            [
+                "direction",
+                "file_name",
+                "device",
+                "client_ip",
+                "user_agent",
+                "bytes",
+                "started_at",
+                "finished_at",
+                "result",
+            ]
+            .map(|header| header.to_string())
+            .to_vec(),
+        ];
+        let transfer_history = cx.default_global::<TransferHistory>();
+        for transfer in &transfer_history.data {
+            let device = transfer_history
+                .device_name(transfer)
+                .cloned()
+                .unwrap_or_default();
+            rows.push(vec![
+                transfer.direction.clone(),
+                transfer.file_name.clone(),
+                device,
+                transfer.client_ip.clone(),
+                transfer.user_agent.clone().unwrap_or_default(),
+                transfer.bytes.to_string(),
+                transfer.started_at.format(TIME_FORMAT).to_string(),
+                transfer
+                    .finished_at
+                    .map(|finished_at| finished_at.format(TIME_FORMAT).to_string())
+                    .unwrap_or_default(),
+                transfer.result.clone(),
+            ]);
+        }
+        let csv = to_csv(rows);
+
+        cx.spawn(async move |cx: &mut AsyncApp| {
+            Tokio::spawn(cx, async move {
+                let file_handle = AsyncFileDialog::new()
+                    .set_file_name("transfers.csv")
+                    .add_filter("CSV", &["csv"])
+                    .save_file()
+                    .await;
+                if let Some(file_handle) = file_handle {
+                    let path = file_handle.path().to_path_buf();
+                    match async_fs::write(&path, csv).await {
+                        Ok(_) => info!("Exported transfer history to {:?}", path),
+                        Err(e) => error!("Failed to export transfer history, {e}"),
+                    }
+                }
+            })
+            .unwrap()
+            .await
+            .unwrap();
+        })
+        .detach();
+    }
+
+    fn direction_button(
+        id: &'static str,
+        label: String,
+        direction: Option<TransferDirection>,
+        cx: &Context<Self>,
+    ) -> Button {
+        let selected = TransferHistory::global(cx).filter.direction
+            == direction.map(|direction| direction.to_string());
+        let variant = if selected {
+            ButtonVariant::Primary
+        } else {
+            ButtonVariant::Ghost
+        };
+        Button::new(id)
+            .with_variant(variant)
+            .child(label)
+            .on_click(move |_ev, _window, cx| {
+                Self::handle_select_direction(direction, cx);
+            })
+    }
+}
+
+impl Render for HistoryPage {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let transfer_count = cx.default_global::<TransferHistory>().data.len();
+        let theme = Theme::global(cx);
+
+        v_flex()
+            .size_full()
+            .bg(theme.colors.background)
+            .text_color(neutral_400())
+            .child(
+                h_flex()
+                    .h_8()
+                    .pl_4()
+                    .pr_4()
+                    .justify_between()
+                    .child(
+                        div()
+                            .when(theme.mode == ThemeMode::Dark, |this| {
+                                this.text_color(gray_100())
+                            })
+                            .when(theme.mode == ThemeMode::Light, |this| {
+                                this.text_color(black())
+                            })
+                            .font_bold()
+                            .child(t!("label.transfer-history").to_string()),
+                    )
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .child(
+                                Button::new("export-transfer-history")
+                                    .with_variant(ButtonVariant::Secondary)
+                                    .child(t!("label.export-csv").to_string())
+                                    .on_click(|_ev, _window, cx| {
+                                        Self::handle_export(cx);
+                                    }),
+                            )
+                            .child(
+                                Button::new("close-history-page")
+                                    .icon(Icon::new(IconName::Close).text_color(neutral_500()))
+                                    .with_variant(ButtonVariant::Ghost)
+                                    .on_click(|_ev, _window, cx| {
+                                        Pages::set_global(cx, Pages::FileListPage);
+                                    }),
+                            ),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .h_10()
+                    .pl_4()
+                    .pr_4()
+                    .gap_2()
+                    .child(Self::direction_button(
+                        "history-direction-all",
+                        t!("label.all").to_string(),
+                        None,
+                        cx,
+                    ))
+                    .child(Self::direction_button(
+                        "history-direction-download",
+                        t!("label.download").to_string(),
+                        Some(TransferDirection::Download),
+                        cx,
+                    ))
+                    .child(Self::direction_button(
+                        "history-direction-upload",
+                        t!("label.upload").to_string(),
+                        Some(TransferDirection::Upload),
+                        cx,
+                    ))
+                    .child(
+                        div()
+                            .flex_grow()
+                            .border_b_1()
+                            .border_color(theme.colors.input)
+                            .text_color(theme.colors.foreground)
+                            .child(self.keyword_input.clone()),
+                    )
+                    .child(
+                        Button::new("search-transfer-history")
+                            .with_variant(ButtonVariant::Secondary)
+                            .child(t!("label.search").to_string())
+                            .on_click(cx.listener(|this, _ev, _window, cx| {
+                                this.handle_search(cx);
+                            })),
+                    ),
+            )
+            .child(
+                uniform_list(
+                    "transfer-history",
+                    transfer_count,
+                    cx.processor(|_this, range, _window, cx| {
+                        let mut items = Vec::new();
+                        let theme = Theme::global(cx);
+                        let transfer_history = TransferHistory::global(cx);
+
+                        for ix in range {
+                            let Some(item) = transfer_history.data.get(ix) else {
+                                continue;
+                            };
+                            items.push(
+                                h_flex()
+                                    .id(ix)
+                                    .h(px(48.))
+                                    .pl(px(16.))
+                                    .pr(px(16.))
+                                    .gap_4()
+                                    .items_center()
+                                    .bg(theme.colors.list)
+                                    .text_color(theme.colors.foreground)
+                                    .child(
+                                        div().w(px(80.)).flex_none().child(item.direction.clone()),
+                                    )
+                                    .child(
+                                        div()
+                                            .min_w_0()
+                                            .flex_grow()
+                                            .overflow_x_hidden()
+                                            .text_overflow(TextOverflow::Truncate("...".into()))
+                                            .child(item.file_name.clone()),
+                                    )
+                                    .child(
+                                        div()
+                                            .w(px(160.))
+                                            .flex_none()
+                                            .child(transfer_history.client_name(item)),
+                                    )
+                                    .child(
+                                        div()
+                                            .w(px(80.))
+                                            .flex_none()
+                                            .child(format_bytes(item.bytes)),
+                                    )
+                                    .child(div().w(px(90.)).flex_none().child(item.result.clone()))
+                                    .child(
+                                        div()
+                                            .flex_none()
+                                            .text_color(neutral_500())
+                                            .child(item.started_at.format(TIME_FORMAT).to_string()),
+                                    ),
+                            );
+                        }
+                        items
+                    }),
+                )
+                .flex_grow(),
+            )
+    }
+}
@@ -0,0 +1,419 @@
+use crate::backend::db::DatabaseSource;
+use crate::backend::web::server::{ServerState, ShareServer};
+use crate::gpui_tokio::Tokio;
+use crate::network::{find_free_port, parse_bind_address};
+use crate::profile::{
+    DEFAULT_PROFILE, active_profile, create_profile, is_valid_profile_name, list_profiles,
+    runs_simultaneously, set_active_profile, set_run_simultaneously,
+};
+use crate::setting::{Settings, database_file};
+use crate::tray::TrayIcon;
+use crate::ui::blocked_requests_page::BlockedRequestsPage;
+use crate::ui::collections_page::{CollectionList, CollectionsPage};
+use crate::ui::component::input::TextInput;
+use crate::ui::file_list_page::FileListPage;
+use crate::ui::history_page::HistoryPage;
+use crate::ui::root_view::Pages;
+use crate::ui::settings_page::SettingsPage;
+use crate::ui::title_bar::{start_server_error_message, switch_server};
+use crate::ui::toast::Toast;
+use gpui::prelude::FluentBuilder;
+use gpui::{
+    App, AppContext, Context, Entity, Global, InteractiveElement, IntoElement, ParentElement,
+    ReadGlobal, Render, StatefulInteractiveElement, Styled, TextOverflow, UpdateGlobal, Window,
+    black, div, px,
+};
+use gpui_component::button::{Button, ButtonVariant, ButtonVariants};
+use gpui_component::switch::Switch;
+use gpui_component::{
+    Icon, IconName, StyledExt, Theme, ThemeMode, gray_100, h_flex, neutral_400, neutral_500, v_flex,
+};
+use log::{error, info};
+use rust_i18n::t;
+use std::collections::HashMap;
+
+/// Servers of the profiles which are not active. They are kept running after switching
+/// profiles when running simultaneously is enabled.
+#[derive(Default)]
+pub struct BackgroundServers {
+    servers: HashMap<String, ShareServer>,
+}
+
+impl Global for BackgroundServers {}
+
+struct ProfileItem {
+    name: String,
+    port: u16,
+    storage_folder: String,
+}
+
+#[derive(Default)]
+pub struct ProfileList {
+    data: Vec<ProfileItem>,
+    run_simultaneously: bool,
+}
+
+impl Global for ProfileList {}
+
+pub struct ProfilesPage {
+    name_input: Entity<TextInput>,
+    error: Option<String>,
+    /// Shows the settings of the active profile, it is reset after switching profiles
+    settings_page: Entity<SettingsPage>,
+}
+
+/// The default profile is named by the language of the UI
+pub fn profile_display_name(profile: &str) -> String {
+    if profile == DEFAULT_PROFILE {
+        t!("label.default-profile").into_owned()
+    } else {
+        profile.to_string()
+    }
+}
+
+impl ProfilesPage {
+    pub fn new(settings_page: Entity<SettingsPage>, cx: &mut App) -> Entity<ProfilesPage> {
+        let name_input = TextInput::new(
+            None,
+            Some(t!("label.profile-name-placeholder").into_owned().into()),
+            None,
+            cx,
+        );
+        cx.new(|_cx| ProfilesPage {
+            name_input,
+            error: None,
+            settings_page,
+        })
+    }
+
+    pub fn reload(cx: &mut App) {
+        let data = list_profiles()
+            .into_iter()
+            .map(|name| {
+                let settings = if name == active_profile() {
+                    Settings::clone()
+                } else {
+                    Settings::load(&name)
+                };
+                ProfileItem {
+                    name,
+                    port: settings.port,
+                    storage_folder: settings.storage_folder,
+                }
+            })
+            .collect();
+        cx.set_global(ProfileList {
+            data,
+            run_simultaneously: runs_simultaneously(),
+        });
+        cx.refresh_windows();
+    }
+
+    fn handle_create(&mut self, cx: &mut Context<Self>) {
+        let name = self.name_input.read(cx).content.trim().to_string();
+        let profile_list = cx.default_global::<ProfileList>();
+        let exists = profile_list.data.iter().any(|profile| profile.name == name);
+        // A free port after the ports of the other profiles, so they can run simultaneously
+        let max_port = profile_list
+            .data
+            .iter()
+            .map(|profile| profile.port)
+            .max()
+            .unwrap_or(Settings::default().port);
+        let bind_address = parse_bind_address(&Settings::clone().bind_address);
+        let port =
+            find_free_port(bind_address, max_port).unwrap_or_else(|| max_port.saturating_add(1));
+
+        self.error = if !is_valid_profile_name(&name) {
+            Some(t!("error.invalid-profile-name").into_owned())
+        } else if exists {
+            Some(t!("error.profile-exists", name = name).into_owned())
+        } else {
+            match Self::create(&name, port) {
+                Ok(_) => {
+                    self.name_input
+                        .update(cx, |input, _cx| input.content = "".into());
+                    None
+                }
+                Err(e) => {
+                    error!("Failed to create profile {name}, {e}");
+                    Some(t!("error.create-profile-failed", reason = e).into_owned())
+                }
+            }
+        };
+        Self::reload(cx);
+        cx.notify();
+    }
+
+    fn create(name: &str, port: u16) -> anyhow::Result<()> {
+        create_profile(name)?;
+        Settings::save(name, &Settings::clone().for_new_profile(name, port))
+    }
+
+    /// Show the share list and the settings of another profile. The server of the previous
+    /// profile is stopped, unless running simultaneously is enabled.
+    fn switch(&mut self, profile: String, cx: &mut Context<Self>) {
+        let previous = active_profile();
+        if profile == previous {
+            return;
+        }
+        if let Err(e) = set_active_profile(&profile) {
+            error!("Failed to switch to profile {profile}, {e}");
+            Toast::show(
+                t!("toast.switch-profile-failed", reason = e).into_owned(),
+                None,
+                cx,
+            );
+            return;
+        }
+        info!("Switch profile from {previous} to {profile}");
+
+        let server = cx
+            .default_global::<BackgroundServers>()
+            .servers
+            .remove(&profile)
+            .unwrap_or_else(|| ShareServer::new(Tokio::handle(cx)));
+        let mut previous_server = std::mem::replace(cx.global_mut::<ShareServer>(), server);
+        if previous_server.state() == ServerState::On {
+            if runs_simultaneously() {
+                cx.default_global::<BackgroundServers>()
+                    .servers
+                    .insert(previous, previous_server);
+            } else {
+                previous_server.stop();
+            }
+        }
+
+        Settings::reload();
+        let db_file = database_file(&profile);
+        cx.set_global(DatabaseSource::new(db_file.to_str().unwrap()));
+        // Every page showing data of the database shows the data of the new profile, the
+        // selected collection belongs to the previous database
+        cx.set_global(CollectionList::default());
+        FileListPage::check_and_reload(cx);
+        CollectionsPage::reload(cx);
+        HistoryPage::reload(cx);
+        BlockedRequestsPage::reload(cx);
+        self.settings_page
+            .update(cx, |settings_page, cx| settings_page.reset(cx));
+        TrayIcon::update_server_state(cx);
+        Self::reload(cx);
+    }
+
+    /// Start or stop the server of a profile, the server of the active profile is the one
+    /// of the title bar
+    fn switch_profile_server(profile: &str, is_on: bool, cx: &mut App) {
+        if profile == active_profile() {
+            switch_server(is_on, cx);
+            return;
+        }
+        if !is_on {
+            if let Some(mut server) = cx
+                .default_global::<BackgroundServers>()
+                .servers
+                .remove(profile)
+            {
+                server.stop();
+            }
+            cx.refresh_windows();
+            return;
+        }
+
+        let settings = Settings::load(profile);
+        let db_file = database_file(profile);
+        let datasource = DatabaseSource::new(db_file.to_str().unwrap()).instance;
+        let mut server = ShareServer::new(Tokio::handle(cx));
+        match server.start(settings, datasource) {
+            Ok(_) => {
+                cx.default_global::<BackgroundServers>()
+                    .servers
+                    .insert(profile.to_string(), server);
+            }
+            Err(e) => {
+                error!("Failed to start server of profile {profile}, {e}");
+                Toast::show(start_server_error_message(&e), None, cx);
+            }
+        }
+        cx.refresh_windows();
+    }
+
+    fn server_state(profile: &str, cx: &App) -> ServerState {
+        if profile == active_profile() {
+            return ShareServer::global(cx).state();
+        }
+        cx.try_global::<BackgroundServers>()
+            .and_then(|background| background.servers.get(profile))
+            .map_or(ServerState::Off, |server| server.state())
+    }
+
+    fn handle_run_simultaneously(is_on: bool, cx: &mut App) {
+        if let Err(e) = set_run_simultaneously(is_on) {
+            error!("Failed to save profile options, {e}");
+        }
+        Self::reload(cx);
+    }
+}
+
+impl Render for ProfilesPage {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        cx.default_global::<ProfileList>();
+        let theme = Theme::global(cx);
+        let profile_list = ProfileList::global(cx);
+        let active = active_profile();
+
+        v_flex()
+            .size_full()
+            .bg(theme.colors.background)
+            .text_color(neutral_400())
+            .child(
+                h_flex()
+                    .h_8()
+                    .pl_4()
+                    .pr_4()
+                    .justify_between()
+                    .child(
+                        div()
+                            .when(theme.mode == ThemeMode::Dark, |this| {
+                                this.text_color(gray_100())
+                            })
+                            .when(theme.mode == ThemeMode::Light, |this| {
+                                this.text_color(black())
+                            })
+                            .font_bold()
+                            .child(t!("label.profiles").to_string()),
+                    )
+                    .child(
+                        Button::new("close-profiles-page")
+                            .icon(Icon::new(IconName::Close).text_color(neutral_500()))
+                            .with_variant(ButtonVariant::Ghost)
+                            .on_click(|_ev, _window, cx| {
+                                Pages::set_global(cx, Pages::FileListPage);
+                            }),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .h_10()
+                    .pl_4()
+                    .pr_4()
+                    .gap_2()
+                    .child(
+                        div()
+                            .flex_grow()
+                            .border_b_1()
+                            .border_color(theme.colors.input)
+                            .text_color(theme.colors.foreground)
+                            .child(self.name_input.clone()),
+                    )
+                    .child(
+                        Button::new("create-profile")
+                            .with_variant(ButtonVariant::Primary)
+                            .child(t!("label.create-profile").to_string())
+                            .on_click(cx.listener(|this, _ev, _window, cx| {
+                                this.handle_create(cx);
+                            })),
+                    ),
+            )
+            .when_some(self.error.clone(), |this, error| {
+                this.child(
+                    div()
+                        .pl_4()
+                        .pr_4()
+                        .text_sm()
+                        .text_color(theme.colors.danger)
+                        .child(error),
+                )
+            })
+            .child(
+                h_flex()
+                    .h_10()
+                    .pl_4()
+                    .pr_4()
+                    .gap_4()
+                    .justify_between()
+                    .text_color(theme.colors.foreground)
+                    .child(t!("label.run-profiles-simultaneously").to_string())
+                    .child(
+                        Switch::new("run-profiles-simultaneously-switch")
+                            .checked(profile_list.run_simultaneously)
+                            .on_click(|is_on: &bool, _window, cx| {
+                                Self::handle_run_simultaneously(*is_on, cx);
+                            }),
+                    ),
+            )
+            .child(
+                v_flex()
+                    .id("profiles")
+                    .flex_grow()
+                    .min_h_0()
+                    .overflow_y_scroll()
+                    .children(profile_list.data.iter().enumerate().map(|(ix, profile)| {
+                        let is_active = profile.name == active;
+                        let server_state = Self::server_state(&profile.name, cx);
+                        let switch_name = profile.name.clone();
+                        let server_name = profile.name.clone();
+                        h_flex()
+                            .id(("profile", ix))
+                            .h(px(48.))
+                            .pl_4()
+                            .pr_4()
+                            .gap_4()
+                            .items_center()
+                            .border_b_1()
+                            .border_color(theme.colors.border)
+                            .text_color(theme.colors.foreground)
+                            .when(is_active, |this| this.bg(theme.colors.list_active))
+                            .child(
+                                v_flex()
+                                    .min_w_0()
+                                    .flex_grow()
+                                    .child(
+                                        div()
+                                            .when(is_active, |this| this.font_bold())
+                                            .child(profile_display_name(&profile.name)),
+                                    )
+                                    .child(
+                                        div()
+                                            .text_sm()
+                                            .text_color(neutral_500())
+                                            .overflow_x_hidden()
+                                            .text_overflow(TextOverflow::Truncate("...".into()))
+                                            .child(format!(
+                                                "{} · {}",
+                                                t!("label.profile-port", port = profile.port),
+                                                profile.storage_folder
+                                            )),
+                                    ),
+                            )
+                            .when(
+                                is_active
+                                    || profile_list.run_simultaneously
+                                    || server_state == ServerState::On,
+                                |this| {
+                                    this.child(
+                                        Switch::new(("profile-server-switch", ix))
+                                            .checked(server_state == ServerState::On)
+                                            .on_click(move |is_on: &bool, _window, cx| {
+                                                Self::switch_profile_server(
+                                                    &server_name,
+                                                    *is_on,
+                                                    cx,
+                                                );
+                                            }),
+                                    )
+                                },
+                            )
+                            .when(!is_active, |this| {
+                                this.child(
+                                    Button::new(("switch-profile", ix))
+                                        .with_variant(ButtonVariant::Ghost)
+                                        .child(t!("label.switch-profile").to_string())
+                                        .on_click(cx.listener(move |this, _ev, _window, cx| {
+                                            this.switch(switch_name.clone(), cx);
+                                        })),
+                                )
+                            })
+                    })),
+            )
+    }
+}
@@ -1,14 +1,23 @@
+use crate::setting::Settings;
+use crate::ui::blocked_requests_page::BlockedRequestsPage;
+use crate::ui::collections_page::CollectionsPage;
 use crate::ui::file_list_page::FileListPage;
+use crate::ui::history_page::HistoryPage;
+use crate::ui::profiles_page::ProfilesPage;
 use crate::ui::server_info_page::ServerInfoPage;
 use crate::ui::settings_page::SettingsPage;
-use crate::ui::title_bar::TitleBar;
+use crate::ui::title_bar::{TitleBar, switch_server};
+use crate::ui::toast::Toast;
 use gpui::prelude::FluentBuilder;
 use gpui::{
     Bounds, Context, CursorStyle, Decorations, Entity, Global, HitboxBehavior, Hsla,
     InteractiveElement, IntoElement, MouseButton, ParentElement, Pixels, Point, ReadGlobal, Render,
     ResizeEdge, Size, Styled, Window, canvas, div, point, px, transparent_black,
 };
-use gpui_component::Theme;
+use gpui_component::button::{Button, ButtonVariant, ButtonVariants};
+use gpui_component::{Icon, IconName, Theme, h_flex, neutral_500};
+use log::error;
+use rust_i18n::t;
 use std::cmp::PartialEq;
 
 #[derive(Debug, Eq, PartialEq)]
@@ -16,6 +25,10 @@ pub enum Pages {
     FileListPage,
     SettingsPage,
     ServerInfoPage,
+    BlockedRequestsPage,
+    HistoryPage,
+    CollectionsPage,
+    ProfilesPage,
 }
 
 impl Default for Pages {
@@ -31,10 +44,81 @@ pub struct WindowRootView {
     pub file_list_page: Entity<FileListPage>,
     pub settings_page: Entity<SettingsPage>,
     pub server_info_page: Entity<ServerInfoPage>,
+    pub blocked_requests_page: Entity<BlockedRequestsPage>,
+    pub history_page: Entity<HistoryPage>,
+    pub collections_page: Entity<CollectionsPage>,
+    pub profiles_page: Entity<ProfilesPage>,
+}
+
+impl WindowRootView {
+    fn render_toast(
+        &self,
+        message: String,
+        suggested_port: Option<u16>,
+        cx: &Context<Self>,
+    ) -> impl IntoElement {
+        let theme = Theme::global(cx);
+        div()
+            .absolute()
+            .bottom_4()
+            .left_0()
+            .right_0()
+            .flex()
+            .justify_center()
+            .child(
+                h_flex()
+                    .id("toast")
+                    .max_w(px(560.))
+                    .px_4()
+                    .py_2()
+                    .gap_2()
+                    .items_center()
+                    .bg(theme.colors.background)
+                    .text_color(theme.colors.foreground)
+                    .rounded_lg()
+                    .border_1()
+                    .border_color(theme.colors.danger)
+                    .shadow_lg()
+                    .child(div().flex_grow().child(message))
+                    .when_some(suggested_port, |this, port| {
+                        this.child(
+                            Button::new("use-suggested-port")
+                                .with_variant(ButtonVariant::Primary)
+                                .child(t!("label.use-port", port = port).to_string())
+                                .on_click(cx.listener(move |this, _ev, _window, cx| {
+                                    this.use_port(port, cx);
+                                })),
+                        )
+                    })
+                    .child(
+                        Button::new("dismiss-toast")
+                            .icon(Icon::new(IconName::Close).text_color(neutral_500()))
+                            .with_variant(ButtonVariant::Ghost)
+                            .on_click(|_ev, _window, cx| {
+                                Toast::dismiss(cx);
+                            }),
+                    ),
+            )
+    }
+
+    /// Save the suggested port and start the server with it
+    fn use_port(&mut self, port: u16, cx: &mut Context<Self>) {
+        Toast::dismiss(cx);
+        if let Err(e) = Settings::update(Box::new(move |settings| settings.port = port)) {
+            error!("Failed to save settings: {e}");
+            return;
+        }
+        self.settings_page
+            .update(cx, |settings_page, cx| settings_page.set_port(port, cx));
+        switch_server(true, cx);
+    }
 }
 
 impl Render for WindowRootView {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let toast = cx.default_global::<Toast>();
+        let toast_message = toast.message.clone();
+        let suggested_port = toast.suggested_port;
         let decorations = window.window_decorations();
         let rounding = px(12.0);
         let shadow_size = px(10.0);
@@ -152,6 +236,7 @@ impl Render for WindowRootView {
                     })
                     .bg(theme.colors.background)
                     // .bg(gpui::rgb(0xFF0000))
+                    .relative()
                     .size_full()
                     .flex()
                     .flex_col()
@@ -165,6 +250,21 @@ impl Render for WindowRootView {
                     })
                     .when(Pages::global(cx) == &Pages::ServerInfoPage, |this| {
                         this.child(self.server_info_page.clone())
+                    })
+                    .when(Pages::global(cx) == &Pages::BlockedRequestsPage, |this| {
+                        this.child(self.blocked_requests_page.clone())
+                    })
+                    .when(Pages::global(cx) == &Pages::HistoryPage, |this| {
+                        this.child(self.history_page.clone())
+                    })
+                    .when(Pages::global(cx) == &Pages::CollectionsPage, |this| {
+                        this.child(self.collections_page.clone())
+                    })
+                    .when(Pages::global(cx) == &Pages::ProfilesPage, |this| {
+                        this.child(self.profiles_page.clone())
+                    })
+                    .when_some(toast_message, |this, message| {
+                        this.child(self.render_toast(message, suggested_port, cx))
                     }),
             )
     }
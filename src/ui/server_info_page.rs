@@ -1,3 +1,6 @@
+use crate::network::{
+    NetworkInterface, list_network_interfaces, resolve_advertise_address, server_url,
+};
 use crate::setting::Settings;
 use crate::ui::root_view::Pages;
 use clipboard_rs::{Clipboard, ClipboardContext};
@@ -12,8 +15,7 @@ use gpui_component::{
     Icon, IconName, StyledExt, Theme, ThemeMode, gray_100, h_flex, neutral_400, neutral_500, v_flex,
 };
 use image::Luma;
-use local_ip_address::local_ip;
-use log::{error, info};
+use log::error;
 use qrcode::render::svg;
 use qrcode::{EcLevel, QrCode, Version};
 use rust_i18n::t;
@@ -21,22 +23,39 @@ use std::io::{BufWriter, Cursor};
 use std::sync::Arc;
 
 pub struct ServerInfoPage {
-    local_ip: SharedString,
+    interfaces: Vec<NetworkInterface>,
 }
 
 impl ServerInfoPage {
     pub fn new(_window: &mut Window, cx: &mut App) -> Entity<Self> {
-        let local_ip = local_ip().unwrap();
-
-        info!("My local IP address: {:?}", local_ip);
-
         cx.new(move |_cx| ServerInfoPage {
-            local_ip: local_ip.to_string().into(),
+            interfaces: list_network_interfaces(),
         })
     }
 
-    fn create_qr_code(url: &String) -> Img {
-        let code = QrCode::new(url.clone().into_bytes()).unwrap();
+    /// Enumerate the network interfaces again, e.g. after joining a VPN
+    fn refresh_interfaces(&mut self, cx: &mut Context<Self>) {
+        self.interfaces = list_network_interfaces();
+        cx.notify();
+    }
+
+    fn handle_select_advertise_address(addr: String, cx: &mut Context<Self>) {
+        let update_fn = move |settings: &mut Settings| {
+            settings.advertise_address = Some(addr);
+        };
+        if let Err(e) = Settings::update(Box::new(update_fn)) {
+            error!("Failed to save advertise address, {e}");
+        }
+        cx.notify();
+    }
+
+    pub fn create_qr_code(url: &String) -> Img {
+        img(ImageSource::Image(Self::create_qr_image(url)))
+    }
+
+    /// The QR code as an image, for views which keep it instead of encoding it on every render
+    pub fn create_qr_image(url: &str) -> Arc<Image> {
+        let code = QrCode::new(url.as_bytes()).unwrap();
         let mut buffer = Vec::new();
         let mut writer = Cursor::new(&mut buffer);
         code.render::<Luma<u8>>()
@@ -44,8 +63,20 @@ impl ServerInfoPage {
             .build()
             .write_to(&mut writer, image::ImageFormat::Png)
             .unwrap();
-        let image = Image::from_bytes(ImageFormat::Png, buffer);
-        img(ImageSource::Image(image.into()))
+        Arc::new(Image::from_bytes(ImageFormat::Png, buffer))
+    }
+
+    pub fn copy_to_clipboard(text: String) {
+        match ClipboardContext::new() {
+            Ok(ctx) => {
+                if let Err(e) = ctx.set_text(text) {
+                    error!("Failed to write url to clipboard, {e}");
+                }
+            }
+            Err(e) => {
+                error!("Failed to write url to clipboard, {e}");
+            }
+        }
     }
 }
 
@@ -53,7 +84,12 @@ impl Render for ServerInfoPage {
     fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let theme = Theme::global(cx);
         let settings = Settings::clone();
-        let url = format!("http://{}:{}/web/index.html", self.local_ip, settings.port);
+        let advertise_address = resolve_advertise_address(
+            &settings.bind_address,
+            settings.advertise_address.as_deref(),
+            &self.interfaces,
+        );
+        let url = server_url(advertise_address, settings.port, "web/index.html");
         let image = Self::create_qr_code(&url);
         let url = SharedString::from(url);
         h_flex()
@@ -102,6 +138,50 @@ impl Render for ServerInfoPage {
                             .items_center()
                             .child(image),
                     )
+                    .child(
+                        h_flex()
+                            .w_full()
+                            .px_4()
+                            .gap_2()
+                            .flex_wrap()
+                            .justify_center()
+                            .items_center()
+                            .children(self.interfaces.iter().enumerate().filter_map(
+                                |(ix, interface)| {
+                                    if interface.addr.is_loopback() {
+                                        return None;
+                                    }
+                                    let addr = interface.addr.to_string();
+                                    let variant = if interface.addr == advertise_address {
+                                        ButtonVariant::Primary
+                                    } else {
+                                        ButtonVariant::Ghost
+                                    };
+                                    Some(
+                                        Button::new(("advertise-address", ix))
+                                            .with_variant(variant)
+                                            .child(interface.label())
+                                            .on_click(cx.listener(
+                                                move |_this, _ev, _window, cx| {
+                                                    Self::handle_select_advertise_address(
+                                                        addr.clone(),
+                                                        cx,
+                                                    );
+                                                },
+                                            )),
+                                    )
+                                },
+                            ))
+                            .child(
+                                Button::new("refresh-interfaces-button")
+                                    .icon(Icon::new(IconName::Redo).text_color(neutral_500()))
+                                    .with_variant(ButtonVariant::Ghost)
+                                    .tooltip(t!("tooltip.refresh-network-interfaces"))
+                                    .on_click(cx.listener(|this, _ev, _window, cx| {
+                                        this.refresh_interfaces(cx);
+                                    })),
+                            ),
+                    )
                     .child(
                         h_flex()
                             .w_full()
@@ -114,19 +194,8 @@ impl Render for ServerInfoPage {
                                 Button::new("copy-url-button")
                                     .icon(Icon::new(IconName::Copy).text_color(neutral_500()))
                                     .with_variant(ButtonVariant::Ghost)
-                                    .on_click(cx.listener(move |_this, _ev, _window, cx| {
-                                        match ClipboardContext::new() {
-                                            Ok(ctx) => {
-                                                if let Err(e) =
-                                                    ctx.set_text(url.clone().to_string())
-                                                {
-                                                    error!("Failed to write url to clipboard, {e}");
-                                                }
-                                            }
-                                            Err(e) => {
-                                                error!("Failed to write url to clipboard, {e}");
-                                            }
-                                        }
+                                    .on_click(cx.listener(move |_this, _ev, _window, _cx| {
+                                        Self::copy_to_clipboard(url.to_string());
                                     })),
                             ),
                     ),
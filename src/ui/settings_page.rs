@@ -1,29 +1,85 @@
+use crate::autostart::{is_autostart_enabled, set_autostart};
+use crate::backend::web::access_control::IpNetwork;
+use crate::backend::web::server::ShareServer;
 use crate::gpui_tokio::Tokio;
+use crate::network::{ANY_ADDRESS, NetworkInterface, list_network_interfaces};
+use crate::overrides::Overrides;
+use crate::profile::{active_profile, launch_profile};
 use crate::setting::Settings;
 use crate::ui::component::input::{
     Backspace, Copy, Cut, Delete, End, Home, Left, Paste, Right, SelectAll, SelectLeft,
     SelectRight, ShowCharacterPalette, TextInput, TextInputStyle,
 };
 use crate::ui::root_view::Pages;
+use crate::ui::title_bar::restart_server;
+use crate::ui::toast::Toast;
+use crate::util::check_folder_writable;
+use anyhow::anyhow;
 use gpui::prelude::FluentBuilder;
 use gpui::{
     AbsoluteLength, App, AppContext, AsyncApp, Context, Edges, Entity, EntityInputHandler,
     FocusHandle, Focusable, Hsla, InteractiveElement, IntoElement, KeyBinding, ParentElement,
-    Pixels, Render, SharedString, Styled, UpdateGlobal, WeakEntity, Window, black, div, px, red,
-    rgb, transparent_black, white,
+    Pixels, Render, SharedString, StatefulInteractiveElement, Styled, UpdateGlobal, WeakEntity,
+    Window, black, div, px, red, rgb, transparent_black, white,
 };
 use gpui_component::button::{Button, ButtonVariant, ButtonVariants};
+use gpui_component::switch::Switch;
 use gpui_component::{
-    Icon, IconName, StyledExt, Theme, ThemeMode, gray_50, gray_100, h_flex, neutral_400,
-    neutral_500, v_flex,
+    Disableable, Icon, IconName, StyledExt, Theme, ThemeMode, gray_50, gray_100, h_flex,
+    neutral_400, neutral_500, v_flex,
 };
 use log::{error, info};
 use rfd::AsyncFileDialog;
 use rust_i18n::t;
+use std::collections::HashMap;
+
+/// The inputs which are validated, the errors are shown below them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum SettingField {
+    Port,
+    StorageFolder,
+    AllowList,
+    DenyList,
+    MaxRequests,
+    MaxDownloadSpeed,
+    MaxDownloadSpeedPerConnection,
+    MaxUploadSpeed,
+    MaxUploadSpeedPerConnection,
+}
+
+/// Keep the parsed value, or remember the error of the field and use a placeholder
+fn check<T: Default>(
+    errors: &mut HashMap<SettingField, String>,
+    field: SettingField,
+    result: Result<T, String>,
+) -> T {
+    result.unwrap_or_else(|e| {
+        errors.insert(field, e);
+        T::default()
+    })
+}
 
 pub struct SettingsPage {
     port_input: Entity<TextInput>,
     upload_folder_input: Entity<TextInput>,
+    allow_list_input: Entity<TextInput>,
+    deny_list_input: Entity<TextInput>,
+    max_requests_input: Entity<TextInput>,
+    max_download_speed_input: Entity<TextInput>,
+    max_download_speed_per_connection_input: Entity<TextInput>,
+    max_upload_speed_input: Entity<TextInput>,
+    max_upload_speed_per_connection_input: Entity<TextInput>,
+    interfaces: Vec<NetworkInterface>,
+    bind_address: String,
+    advertise_address: Option<String>,
+    minimize_on_close: bool,
+    notify_upload_started: bool,
+    notify_upload_completed: bool,
+    notify_download: bool,
+    start_server_on_launch: bool,
+    /// Whether the XDG autostart entry exists
+    launch_at_login: bool,
+    errors: HashMap<SettingField, String>,
     focus_handle: FocusHandle,
 }
 
@@ -32,6 +88,21 @@ impl SettingsPage {
         let Settings {
             port,
             storage_folder,
+            bind_address,
+            advertise_address,
+            allow_list,
+            deny_list,
+            max_requests_per_minute,
+            max_download_speed,
+            max_download_speed_per_connection,
+            max_upload_speed,
+            max_upload_speed_per_connection,
+            minimize_on_close,
+            notify_upload_started,
+            notify_upload_completed,
+            notify_download,
+            start_server_on_launch,
+            ..
         } = Settings::clone();
         let port = port.to_string();
         let port_input = TextInput::new(
@@ -40,6 +111,9 @@ impl SettingsPage {
             None,
             cx,
         );
+        port_input.update(cx, |input, _cx| {
+            input.read_only = Self::is_overridden("port")
+        });
 
         let style = TextInputStyle {
             padding: Edges {
@@ -53,9 +127,45 @@ impl SettingsPage {
         let upload_folder_input = TextInput::new(
             Some(storage_folder.into()),
             Some("Enter upload folder...".into()),
-            Some(style),
+            Some(style.clone()),
             cx,
         );
+        upload_folder_input.update(cx, |input, _cx| {
+            input.read_only = Self::is_overridden("storage_folder")
+        });
+
+        let allow_list_input = TextInput::new(
+            Some(allow_list.join(", ").into()),
+            Some("e.g. 192.168.1.0/24, fe80::/10 (empty allows all)".into()),
+            Some(style.clone()),
+            cx,
+        );
+        let deny_list_input = TextInput::new(
+            Some(deny_list.join(", ").into()),
+            Some("e.g. 192.168.1.13".into()),
+            Some(style.clone()),
+            cx,
+        );
+        let max_requests_input = TextInput::new(
+            Some(max_requests_per_minute.to_string().into()),
+            Some("0 means unlimited".into()),
+            Some(style.clone()),
+            cx,
+        );
+        let speed_input = |speed: u32, cx: &mut App| {
+            TextInput::new(
+                Some(speed.to_string().into()),
+                Some("KiB/s, 0 means unlimited".into()),
+                Some(style.clone()),
+                cx,
+            )
+        };
+        let max_download_speed_input = speed_input(max_download_speed, cx);
+        let max_download_speed_per_connection_input =
+            speed_input(max_download_speed_per_connection, cx);
+        let max_upload_speed_input = speed_input(max_upload_speed, cx);
+        let max_upload_speed_per_connection_input =
+            speed_input(max_upload_speed_per_connection, cx);
 
         cx.bind_keys([
             KeyBinding::new("backspace", Backspace, None),
@@ -76,10 +186,86 @@ impl SettingsPage {
         cx.new(|cx| SettingsPage {
             port_input,
             upload_folder_input,
+            allow_list_input,
+            deny_list_input,
+            max_requests_input,
+            max_download_speed_input,
+            max_download_speed_per_connection_input,
+            max_upload_speed_input,
+            max_upload_speed_per_connection_input,
+            interfaces: list_network_interfaces(),
+            bind_address,
+            advertise_address,
+            minimize_on_close,
+            notify_upload_started,
+            notify_upload_completed,
+            notify_download,
+            start_server_on_launch,
+            launch_at_login: is_autostart_enabled(),
+            errors: HashMap::new(),
             focus_handle: cx.focus_handle(),
         })
     }
 
+    /// Show the port changed outside the settings page, e.g. from a toast
+    pub fn set_port(&mut self, port: u16, cx: &mut Context<Self>) {
+        self.port_input.update(cx, |input, _cx| {
+            input.content = port.to_string().into();
+        });
+        cx.notify();
+    }
+
+    /// Show the settings of the active profile after switching profiles, unsaved changes
+    /// are dropped
+    pub fn reset(&mut self, cx: &mut Context<Self>) {
+        let settings = Settings::clone();
+        let inputs = [
+            (&self.port_input, settings.port.to_string()),
+            (&self.upload_folder_input, settings.storage_folder),
+            (&self.allow_list_input, settings.allow_list.join(", ")),
+            (&self.deny_list_input, settings.deny_list.join(", ")),
+            (
+                &self.max_requests_input,
+                settings.max_requests_per_minute.to_string(),
+            ),
+            (
+                &self.max_download_speed_input,
+                settings.max_download_speed.to_string(),
+            ),
+            (
+                &self.max_download_speed_per_connection_input,
+                settings.max_download_speed_per_connection.to_string(),
+            ),
+            (
+                &self.max_upload_speed_input,
+                settings.max_upload_speed.to_string(),
+            ),
+            (
+                &self.max_upload_speed_per_connection_input,
+                settings.max_upload_speed_per_connection.to_string(),
+            ),
+        ];
+        for (input, content) in inputs {
+            input.update(cx, |input, _cx| input.content = content.into());
+        }
+        // The overrides only apply to the profile used on launch
+        self.port_input.update(cx, |input, _cx| {
+            input.read_only = Self::is_overridden("port")
+        });
+        self.upload_folder_input.update(cx, |input, _cx| {
+            input.read_only = Self::is_overridden("storage_folder")
+        });
+        self.bind_address = settings.bind_address;
+        self.advertise_address = settings.advertise_address;
+        self.minimize_on_close = settings.minimize_on_close;
+        self.notify_upload_started = settings.notify_upload_started;
+        self.notify_upload_completed = settings.notify_upload_completed;
+        self.notify_download = settings.notify_download;
+        self.start_server_on_launch = settings.start_server_on_launch;
+        self.errors.clear();
+        cx.notify();
+    }
+
     pub fn handle_select_upload_folder(cx: &mut Context<Self>) {
         cx.spawn(async |this: WeakEntity<SettingsPage>, cx: &mut AsyncApp| {
             let result = Tokio::spawn(cx, async {
@@ -111,21 +297,304 @@ impl SettingsPage {
         .detach();
     }
 
-    fn handle_save_settings(&self, cx: &mut Context<Self>) -> anyhow::Result<()> {
-        let port_input = self.port_input.read(cx);
-        let port = port_input.content.to_string().parse::<u16>()?;
-
-        let folder_input = self.upload_folder_input.read(cx);
-        let upload_folder = folder_input.content.clone();
+    /// Validate and save the settings. The settings which need a rebind restart the running
+    /// server, the others are applied to it directly.
+    fn handle_save_settings(&mut self, cx: &mut Context<Self>) -> anyhow::Result<()> {
+        let mut errors = HashMap::new();
+        let port = check(
+            &mut errors,
+            SettingField::Port,
+            Self::parse_port(&self.port_input.read(cx).content),
+        );
+        let upload_folder = self.upload_folder_input.read(cx).content.trim().to_string();
+        check(
+            &mut errors,
+            SettingField::StorageFolder,
+            Self::check_storage_folder(&upload_folder),
+        );
+        let allow_list = check(
+            &mut errors,
+            SettingField::AllowList,
+            Self::parse_network_list(&self.allow_list_input.read(cx).content),
+        );
+        let deny_list = check(
+            &mut errors,
+            SettingField::DenyList,
+            Self::parse_network_list(&self.deny_list_input.read(cx).content),
+        );
+        let max_requests_per_minute = check(
+            &mut errors,
+            SettingField::MaxRequests,
+            Self::parse_number(&self.max_requests_input, cx),
+        );
+        let max_download_speed = check(
+            &mut errors,
+            SettingField::MaxDownloadSpeed,
+            Self::parse_number(&self.max_download_speed_input, cx),
+        );
+        let max_download_speed_per_connection = check(
+            &mut errors,
+            SettingField::MaxDownloadSpeedPerConnection,
+            Self::parse_number(&self.max_download_speed_per_connection_input, cx),
+        );
+        let max_upload_speed = check(
+            &mut errors,
+            SettingField::MaxUploadSpeed,
+            Self::parse_number(&self.max_upload_speed_input, cx),
+        );
+        let max_upload_speed_per_connection = check(
+            &mut errors,
+            SettingField::MaxUploadSpeedPerConnection,
+            Self::parse_number(&self.max_upload_speed_per_connection_input, cx),
+        );
+        self.errors = errors;
+        cx.notify();
+        if !self.errors.is_empty() {
+            return Err(anyhow!("Invalid settings {:?}", self.errors));
+        }
 
+        let bind_address = self.bind_address.clone();
+        let advertise_address = self.advertise_address.clone();
+        let minimize_on_close = self.minimize_on_close;
+        let notify_upload_started = self.notify_upload_started;
+        let notify_upload_completed = self.notify_upload_completed;
+        let notify_download = self.notify_download;
+        let start_server_on_launch = self.start_server_on_launch;
+        if self.launch_at_login != is_autostart_enabled() {
+            set_autostart(self.launch_at_login)?;
+        }
         let update_fn = move |settings: &mut Settings| {
             settings.port = port;
-            settings.storage_folder = upload_folder.clone().to_string();
+            settings.storage_folder = upload_folder;
+            settings.bind_address = bind_address;
+            settings.advertise_address = advertise_address;
+            settings.allow_list = allow_list;
+            settings.deny_list = deny_list;
+            settings.max_requests_per_minute = max_requests_per_minute;
+            settings.max_download_speed = max_download_speed;
+            settings.max_download_speed_per_connection = max_download_speed_per_connection;
+            settings.max_upload_speed = max_upload_speed;
+            settings.max_upload_speed_per_connection = max_upload_speed_per_connection;
+            settings.minimize_on_close = minimize_on_close;
+            settings.notify_upload_started = notify_upload_started;
+            settings.notify_upload_completed = notify_upload_completed;
+            settings.notify_download = notify_download;
+            settings.start_server_on_launch = start_server_on_launch;
         };
+        let old_settings = Settings::clone();
         Settings::update(Box::new(update_fn))?;
+        let settings = Settings::clone();
+        cx.global::<ShareServer>().reload_settings(&settings);
+        // The server binds the port on start and keeps its own copy of the storage folder
+        if settings.port != old_settings.port
+            || settings.bind_address != old_settings.bind_address
+            || settings.storage_folder != old_settings.storage_folder
+        {
+            restart_server(cx);
+        }
         Ok(())
     }
 
+    fn parse_port(content: &str) -> Result<u16, String> {
+        match content.trim().parse::<u16>() {
+            Ok(port) if port > 0 => Ok(port),
+            _ => Err(t!("error.invalid-port").into_owned()),
+        }
+    }
+
+    /// The storage folder is created if it does not exist, uploads fail if it is not writable
+    fn check_storage_folder(folder: &str) -> Result<(), String> {
+        if folder.is_empty() {
+            return Err(t!("error.empty-folder").into_owned());
+        }
+        check_folder_writable(folder)
+            .map_err(|e| t!("error.folder-not-writable", reason = e).into_owned())
+    }
+
+    fn parse_number(input: &Entity<TextInput>, cx: &App) -> Result<u32, String> {
+        input
+            .read(cx)
+            .content
+            .trim()
+            .parse::<u32>()
+            .map_err(|_| t!("error.invalid-number").into_owned())
+    }
+
+    /// Parse a comma separated list of networks, every entry must be a valid CIDR or address
+    fn parse_network_list(content: &str) -> Result<Vec<String>, String> {
+        content
+            .split(',')
+            .map(|network| network.trim())
+            .filter(|network| !network.is_empty())
+            .map(|network| match network.parse::<IpNetwork>() {
+                Ok(_) => Ok(network.to_string()),
+                Err(_) => Err(t!("error.invalid-network", network = network).into_owned()),
+            })
+            .collect()
+    }
+
+    fn render_error(&self, field: SettingField, cx: &Context<Self>) -> Option<impl IntoElement> {
+        let error = self.errors.get(&field)?;
+        let theme = Theme::global(cx);
+        Some(
+            div()
+                .pl_8()
+                .pr_8()
+                .pb_2()
+                .text_sm()
+                .text_color(theme.colors.danger)
+                .child(error.clone()),
+        )
+    }
+
+    /// Fields given on the command line or by environment variables can not be changed,
+    /// they apply to the profile used on launch
+    fn is_overridden(field: &str) -> bool {
+        Overrides::global().is_overridden(field) && active_profile() == launch_profile()
+    }
+
+    fn render_overridden_hint(field: &str) -> Option<impl IntoElement> {
+        if !Self::is_overridden(field) {
+            return None;
+        }
+        Some(
+            div()
+                .pl_8()
+                .pr_8()
+                .pb_2()
+                .text_sm()
+                .text_color(neutral_500())
+                .child(t!("label.overridden-setting").to_string()),
+        )
+    }
+
+    fn render_input_row(
+        label: String,
+        input: Entity<TextInput>,
+        cx: &Context<Self>,
+    ) -> impl IntoElement {
+        let theme = Theme::global(cx);
+        h_flex()
+            .justify_between()
+            .h_16()
+            .pl_8()
+            .pr_8()
+            .gap_4()
+            .child(label)
+            .child(
+                div()
+                    .flex_grow()
+                    .border_b_1()
+                    .border_color(theme.colors.input)
+                    .child(input),
+            )
+    }
+
+    fn render_switch_row(
+        id: &'static str,
+        label: String,
+        checked: bool,
+        on_click: impl Fn(&mut Self, bool) + 'static,
+        cx: &Context<Self>,
+    ) -> impl IntoElement {
+        h_flex()
+            .justify_between()
+            .h_16()
+            .pl_8()
+            .pr_8()
+            .gap_4()
+            .child(label)
+            .child(Switch::new(id).checked(checked).on_click(cx.listener(
+                move |this, is_on: &bool, _window, cx| {
+                    on_click(this, *is_on);
+                    cx.notify();
+                },
+            )))
+    }
+
+    fn address_button(id: (&'static str, usize), label: String, selected: bool) -> Button {
+        let variant = if selected {
+            ButtonVariant::Primary
+        } else {
+            ButtonVariant::Ghost
+        };
+        Button::new(id).with_variant(variant).child(label)
+    }
+
+    fn render_bind_address_options(&self, cx: &Context<Self>) -> impl IntoElement {
+        let disabled = Self::is_overridden("bind_address");
+        let all_interfaces = Self::address_button(
+            ("bind-address", 0),
+            t!("label.all-interfaces").to_string(),
+            self.bind_address == ANY_ADDRESS,
+        )
+        .disabled(disabled)
+        .on_click(cx.listener(|this, _ev, _window, cx| {
+            this.bind_address = ANY_ADDRESS.to_string();
+            cx.notify();
+        }));
+
+        h_flex()
+            .flex_grow()
+            .flex_wrap()
+            .gap_1()
+            .child(all_interfaces)
+            .children(self.interfaces.iter().enumerate().map(|(ix, interface)| {
+                let addr = interface.addr.to_string();
+                Self::address_button(
+                    ("bind-address", ix + 1),
+                    interface.label(),
+                    self.bind_address == addr,
+                )
+                .disabled(disabled)
+                .on_click(cx.listener(move |this, _ev, _window, cx| {
+                    this.bind_address = addr.clone();
+                    cx.notify();
+                }))
+            }))
+    }
+
+    fn render_advertise_address_options(&self, cx: &Context<Self>) -> impl IntoElement {
+        let disabled = Self::is_overridden("advertise_address");
+        let auto_detect = Self::address_button(
+            ("advertise-address", 0),
+            t!("label.auto-detect").to_string(),
+            self.advertise_address.is_none(),
+        )
+        .disabled(disabled)
+        .on_click(cx.listener(|this, _ev, _window, cx| {
+            this.advertise_address = None;
+            cx.notify();
+        }));
+
+        h_flex()
+            .flex_grow()
+            .flex_wrap()
+            .gap_1()
+            .child(auto_detect)
+            .children(
+                self.interfaces
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, interface)| !interface.addr.is_loopback())
+                    .map(|(ix, interface)| {
+                        let addr = interface.addr.to_string();
+                        Self::address_button(
+                            ("advertise-address", ix + 1),
+                            interface.label(),
+                            self.advertise_address.as_deref() == Some(addr.as_str()),
+                        )
+                        .disabled(disabled)
+                        .on_click(cx.listener(
+                            move |this, _ev, _window, cx| {
+                                this.advertise_address = Some(addr.clone());
+                                cx.notify();
+                            },
+                        ))
+                    }),
+            )
+    }
+
     fn close_settings_page(cx: &mut Context<SettingsPage>) {
         Pages::set_global(cx, Pages::FileListPage);
     }
@@ -142,7 +611,7 @@ impl Render for SettingsPage {
             .child(
                 v_flex()
                     .w(px(580.))
-                    .h(px(320.))
+                    .h(px(520.))
                     .bg(theme.colors.background)
                     .text_color(neutral_400())
                     .rounded_2xl()
@@ -176,9 +645,10 @@ impl Render for SettingsPage {
                     )
                     .child(
                         v_flex()
+                            .id("settings-items")
                             .flex_grow()
-                            .justify_center()
-                            .content_center()
+                            .min_h(px(0.))
+                            .overflow_y_scroll()
                             .child(
                                 h_flex()
                                     .justify_between()
@@ -195,6 +665,8 @@ impl Render for SettingsPage {
                                             .child(self.port_input.clone()),
                                     ),
                             )
+                            .children(self.render_error(SettingField::Port, cx))
+                            .children(Self::render_overridden_hint("port"))
                             .child(
                                 h_flex()
                                     .justify_between()
@@ -220,6 +692,7 @@ impl Render for SettingsPage {
                                                     .flex_none()
                                                     .font_bold()
                                                     .child(Icon::new(IconName::Folder))
+                                                    .disabled(Self::is_overridden("storage_folder"))
                                                     .on_click(cx.listener(
                                                         |_this, _ev, _window, cx| {
                                                             Self::handle_select_upload_folder(cx);
@@ -227,7 +700,123 @@ impl Render for SettingsPage {
                                                     )),
                                             ),
                                     ),
-                            ),
+                            )
+                            .children(self.render_error(SettingField::StorageFolder, cx))
+                            .children(Self::render_overridden_hint("storage_folder"))
+                            .child(
+                                h_flex()
+                                    .justify_between()
+                                    .items_start()
+                                    .py_2()
+                                    .pl_8()
+                                    .pr_8()
+                                    .gap_4()
+                                    .child(t!("label.bind-address").to_string())
+                                    .child(self.render_bind_address_options(cx)),
+                            )
+                            .children(Self::render_overridden_hint("bind_address"))
+                            .child(
+                                h_flex()
+                                    .justify_between()
+                                    .items_start()
+                                    .py_2()
+                                    .pl_8()
+                                    .pr_8()
+                                    .gap_4()
+                                    .child(t!("label.advertise-address").to_string())
+                                    .child(self.render_advertise_address_options(cx)),
+                            )
+                            .children(Self::render_overridden_hint("advertise_address"))
+                            .child(Self::render_input_row(
+                                t!("label.allow-list").to_string(),
+                                self.allow_list_input.clone(),
+                                cx,
+                            ))
+                            .children(self.render_error(SettingField::AllowList, cx))
+                            .child(Self::render_input_row(
+                                t!("label.deny-list").to_string(),
+                                self.deny_list_input.clone(),
+                                cx,
+                            ))
+                            .children(self.render_error(SettingField::DenyList, cx))
+                            .child(Self::render_input_row(
+                                t!("label.max-requests-per-minute").to_string(),
+                                self.max_requests_input.clone(),
+                                cx,
+                            ))
+                            .children(self.render_error(SettingField::MaxRequests, cx))
+                            .child(Self::render_input_row(
+                                t!("label.max-download-speed").to_string(),
+                                self.max_download_speed_input.clone(),
+                                cx,
+                            ))
+                            .children(self.render_error(SettingField::MaxDownloadSpeed, cx))
+                            .child(Self::render_input_row(
+                                t!("label.max-download-speed-per-connection").to_string(),
+                                self.max_download_speed_per_connection_input.clone(),
+                                cx,
+                            ))
+                            .children(
+                                self.render_error(SettingField::MaxDownloadSpeedPerConnection, cx),
+                            )
+                            .child(Self::render_input_row(
+                                t!("label.max-upload-speed").to_string(),
+                                self.max_upload_speed_input.clone(),
+                                cx,
+                            ))
+                            .children(self.render_error(SettingField::MaxUploadSpeed, cx))
+                            .child(Self::render_input_row(
+                                t!("label.max-upload-speed-per-connection").to_string(),
+                                self.max_upload_speed_per_connection_input.clone(),
+                                cx,
+                            ))
+                            .children(
+                                self.render_error(SettingField::MaxUploadSpeedPerConnection, cx),
+                            )
+                            .child(Self::render_switch_row(
+                                "start-server-on-launch-switch",
+                                t!("label.start-server-on-launch").to_string(),
+                                self.start_server_on_launch,
+                                |this, is_on| this.start_server_on_launch = is_on,
+                                cx,
+                            ))
+                            .when(cfg!(target_os = "linux"), |this| {
+                                this.child(Self::render_switch_row(
+                                    "launch-at-login-switch",
+                                    t!("label.launch-at-login").to_string(),
+                                    self.launch_at_login,
+                                    |this, is_on| this.launch_at_login = is_on,
+                                    cx,
+                                ))
+                            })
+                            .child(Self::render_switch_row(
+                                "minimize-on-close-switch",
+                                t!("label.minimize-on-close").to_string(),
+                                self.minimize_on_close,
+                                |this, is_on| this.minimize_on_close = is_on,
+                                cx,
+                            ))
+                            .child(Self::render_switch_row(
+                                "notify-upload-started-switch",
+                                t!("label.notify-upload-started").to_string(),
+                                self.notify_upload_started,
+                                |this, is_on| this.notify_upload_started = is_on,
+                                cx,
+                            ))
+                            .child(Self::render_switch_row(
+                                "notify-upload-completed-switch",
+                                t!("label.notify-upload-completed").to_string(),
+                                self.notify_upload_completed,
+                                |this, is_on| this.notify_upload_completed = is_on,
+                                cx,
+                            ))
+                            .child(Self::render_switch_row(
+                                "notify-download-switch",
+                                t!("label.notify-download").to_string(),
+                                self.notify_download,
+                                |this, is_on| this.notify_download = is_on,
+                                cx,
+                            )),
                     )
                     .child(
                         h_flex()
@@ -256,7 +845,18 @@ impl Render for SettingsPage {
                                             }
                                             Err(e) => {
                                                 error!("Failed to save settings: {}", e);
-                                                // TODO 报错提示
+                                                // Invalid inputs are shown below them
+                                                if this.errors.is_empty() {
+                                                    Toast::show(
+                                                        t!(
+                                                            "toast.save-settings-failed",
+                                                            reason = e
+                                                        )
+                                                        .into_owned(),
+                                                        None,
+                                                        cx,
+                                                    );
+                                                }
                                             }
                                         }
                                     })),
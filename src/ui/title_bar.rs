@@ -1,13 +1,19 @@
 use gpui::prelude::FluentBuilder;
 use crate::backend::db::DatabaseSource;
-use crate::backend::entities::shares;
-use crate::backend::web::server::{ServerState, ShareServer};
+use crate::backend::web::server::{ServerState, ShareServer, StartServerError};
 use crate::gpui_tokio::Tokio;
-use crate::mimes::detect_mime_type;
+use crate::network::{find_free_port, parse_bind_address};
+use crate::profile::active_profile;
 use crate::setting;
+use crate::tray::TrayIcon;
+use crate::ui::blocked_requests_page::BlockedRequestsPage;
+use crate::ui::collections_page::CollectionsPage;
 use crate::ui::file_list_page::FileListPage;
+use crate::ui::history_page::HistoryPage;
+use crate::ui::profiles_page::{ProfilesPage, profile_display_name};
 use crate::ui::root_view::Pages;
 use crate::ui::settings_page::SettingsPage;
+use crate::ui::toast::Toast;
 use gpui::{
     App, AppContext, AsyncApp, Context, Entity, Global, ImageSource, InteractiveElement,
     IntoElement, MouseButton, MouseDownEvent, ParentElement, ReadGlobal, Render, RenderOnce,
@@ -20,9 +26,7 @@ use gpui_component::{Icon, IconName, Theme, h_flex, neutral_500, v_flex};
 use log::{error, info};
 use rfd::AsyncFileDialog;
 use rust_i18n::t;
-use sea_orm::{ActiveModelTrait, IntoActiveModel};
 use sea_query::Iden;
-use snowflaked::sync::Generator;
 use std::sync::Arc;
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
@@ -138,7 +142,7 @@ impl Render for WindowControl {
                         window.zoom_window();
                     }
                     WindowControlType::Close => {
-                        window.remove_window();
+                        TrayIcon::close_window(window, cx);
                     }
                 }
             }))
@@ -147,6 +151,71 @@ impl Render for WindowControl {
 
 impl Global for ShareServer {}
 
+/// Start or stop the server, used by the title bar switch and the tray menu.
+/// A toast explains why the server could not be started.
+pub fn switch_server(is_on: bool, cx: &mut App) {
+    let datasource = DatabaseSource::global(cx).instance.clone();
+    let share_server = cx.global_mut::<ShareServer>();
+    if is_on {
+        let settings = setting::Settings::clone();
+        let bind_address = parse_bind_address(&settings.bind_address);
+        let port = settings.port;
+        if let Err(e) = share_server.start(settings, datasource) {
+            error!("Failed to start server, {e}");
+            let suggested_port = match e {
+                StartServerError::PortInUse(_) | StartServerError::PermissionDenied(_) => {
+                    find_free_port(bind_address, port)
+                }
+                _ => None,
+            };
+            Toast::show(start_server_error_message(&e), suggested_port, cx);
+        }
+    } else {
+        share_server.stop();
+    }
+    TrayIcon::update_server_state(cx);
+    cx.refresh_windows();
+}
+
+/// Restart the running server to apply the settings which need a rebind.
+/// The port is bound again once the old server has shut down.
+pub fn restart_server(cx: &mut App) {
+    if ShareServer::global(cx).state() == ServerState::Off {
+        return;
+    }
+    let server_thread = cx.global_mut::<ShareServer>().stop();
+    TrayIcon::update_server_state(cx);
+    cx.refresh_windows();
+    cx.spawn(async move |cx: &mut AsyncApp| {
+        if let Some(server_thread) = server_thread {
+            Tokio::spawn(cx, async move {
+                tokio::task::spawn_blocking(move || server_thread.join())
+                    .await
+                    .ok();
+            })
+            .unwrap()
+            .await
+            .unwrap();
+        }
+        info!("Restarting server");
+        cx.update(|cx: &mut App| switch_server(true, cx))
+    })
+    .detach();
+}
+
+pub fn start_server_error_message(e: &StartServerError) -> String {
+    match e {
+        StartServerError::PortInUse(port) => t!("toast.port-in-use", port = port).into_owned(),
+        StartServerError::PermissionDenied(port) => {
+            t!("toast.port-permission-denied", port = port).into_owned()
+        }
+        StartServerError::AddressUnavailable(addr) => {
+            t!("toast.address-unavailable", address = addr).into_owned()
+        }
+        StartServerError::Other(msg) => t!("toast.start-server-failed", reason = msg).into_owned(),
+    }
+}
+
 #[derive(IntoElement)]
 struct ServerControl {}
 
@@ -159,40 +228,20 @@ impl ServerControl {
     }
 
     fn handle_add_files_to_share_list(cx: &mut App) {
-        let db = DatabaseSource::global(cx);
-        let sqlite = db.instance.clone();
         cx.spawn(async move |cx: &mut AsyncApp| {
-            Tokio::spawn(cx, async move {
-                let files = AsyncFileDialog::pick_files(Default::default()).await;
-
-                if let Some(files) = files {
-                    let connection = sqlite.clone().connection().await.unwrap();
-                    let generator = Generator::new(0);
-
-                    for file in files {
-                        let file_path = file.path().to_str().unwrap().to_string();
-                        let file_name = file.file_name();
-                        let model: shares::ActiveModel = shares::Model {
-                            id: generator.generate(),
-                            file_name,
-                            mime_type: detect_mime_type(&file_path),
-                            file_path,
-                        }
-                        .into_active_model();
-                        match model.insert(&connection).await {
-                            Ok(_) => (),
-                            Err(e) => {
-                                error!("Failed to insert share file: {}", e);
-                            }
-                        }
-                    }
-                }
+            let files = Tokio::spawn(cx, async move {
+                AsyncFileDialog::pick_files(Default::default()).await
             })
             .unwrap()
             .await
             .unwrap();
+
+            let Some(files) = files else {
+                return Ok(());
+            };
+            let paths = files.iter().map(|file| file.path().to_path_buf()).collect();
             cx.update(|cx| {
-                FileListPage::reload(cx);
+                FileListPage::add_files(paths, cx);
             })
         })
         .detach();
@@ -216,13 +265,19 @@ impl RenderOnce for ServerControl {
                         ServerState::Off => false,
                     })
                     .on_click(|is_on, _, cx| {
-                        let datasource = DatabaseSource::global(cx).instance.clone();
-                        let share_server = cx.global_mut::<ShareServer>();
-                        if *is_on {
-                            share_server.start(setting::Settings::clone(), datasource);
-                        } else {
-                            share_server.stop();
-                        }
+                        switch_server(*is_on, cx);
+                    }),
+            )
+            .child(
+                Button::new("profiles-button")
+                    .with_variant(ButtonVariant::Ghost)
+                    .tooltip(t!("tooltip.profiles-button"))
+                    .child(profile_display_name(&active_profile()))
+                    .on_click(|_, _, cx| {
+                        cx.stop_propagation();
+                        info!("profiles-button clicked");
+                        ProfilesPage::reload(cx);
+                        Pages::set_global(cx, Pages::ProfilesPage);
                     }),
             )
             .child(
@@ -241,6 +296,42 @@ impl RenderOnce for ServerControl {
                                 Self::handle_add_files_to_share_list(cx);
                             }),
                     )
+                    .child(
+                        Button::new("collections-button")
+                            .icon(Icon::new(IconName::GalleryVerticalEnd).text_color(neutral_500()))
+                            .tooltip(t!("tooltip.collections-button"))
+                            .with_variant(ButtonVariant::Ghost)
+                            .on_click(|_, _, cx| {
+                                cx.stop_propagation();
+                                info!("collections-button clicked");
+                                CollectionsPage::reload(cx);
+                                Pages::set_global(cx, Pages::CollectionsPage);
+                            }),
+                    )
+                    .child(
+                        Button::new("history-button")
+                            .icon(Icon::new(IconName::Inbox).text_color(neutral_500()))
+                            .tooltip(t!("tooltip.history-button"))
+                            .with_variant(ButtonVariant::Ghost)
+                            .on_click(|_, _, cx| {
+                                cx.stop_propagation();
+                                info!("history-button clicked");
+                                HistoryPage::reload(cx);
+                                Pages::set_global(cx, Pages::HistoryPage);
+                            }),
+                    )
+                    .child(
+                        Button::new("blocked-requests-button")
+                            .icon(Icon::new(IconName::TriangleAlert).text_color(neutral_500()))
+                            .tooltip(t!("tooltip.blocked-requests-button"))
+                            .with_variant(ButtonVariant::Ghost)
+                            .on_click(|_, _, cx| {
+                                cx.stop_propagation();
+                                info!("blocked-requests-button clicked");
+                                BlockedRequestsPage::reload(cx);
+                                Pages::set_global(cx, Pages::BlockedRequestsPage);
+                            }),
+                    )
                     .child(
                         Button::new("server-info-button")
                             .icon(Icon::new(IconName::Info).text_color(neutral_500()))
@@ -0,0 +1,26 @@
+use gpui::{App, Global};
+
+/// A message shown at the bottom of the window until it is closed
+#[derive(Default)]
+pub struct Toast {
+    pub message: Option<String>,
+    /// A free port offered when the server could not bind the configured port
+    pub suggested_port: Option<u16>,
+}
+
+impl Global for Toast {}
+
+impl Toast {
+    pub fn show(message: String, suggested_port: Option<u16>, cx: &mut App) {
+        cx.set_global(Toast {
+            message: Some(message),
+            suggested_port,
+        });
+        cx.refresh_windows();
+    }
+
+    pub fn dismiss(cx: &mut App) {
+        cx.set_global(Toast::default());
+        cx.refresh_windows();
+    }
+}
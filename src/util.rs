@@ -1,6 +1,7 @@
 use crate::util::linux::LinuxFileManagers;
 use anyhow::{Context, anyhow};
 use async_fs::File;
+use chrono::{DateTime, Local, NaiveDateTime};
 use futures_util::{AsyncReadExt, io};
 use log::error;
 use sha2::{Digest, Sha256};
@@ -108,6 +109,69 @@ pub async fn get_available_filename<P: AsRef<Path>>(path: P) -> io::Result<PathB
     }
 }
 
+/// 展开文件夹，返回文件夹以及子文件夹中所有的文件，文件原样返回。
+/// 子文件夹的符号链接不会被展开，避免循环链接
+pub fn expand_folders(paths: &[PathBuf]) -> Vec<PathBuf> {
+    fn visit(dir: &Path, files: &mut Vec<PathBuf>) {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                error!("Failed to read folder {:?}, {e}", dir);
+                return;
+            }
+        };
+        let mut entries: Vec<_> = entries.filter_map(|entry| entry.ok()).collect();
+        entries.sort_by_key(|entry| entry.file_name());
+        for entry in entries {
+            let path = entry.path();
+            match entry.file_type() {
+                Ok(file_type) if file_type.is_dir() => visit(&path, files),
+                _ if path.is_file() => files.push(path),
+                _ => {}
+            }
+        }
+    }
+
+    let mut files = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            visit(path, &mut files);
+        } else {
+            files.push(path.clone());
+        }
+    }
+    files
+}
+
+#[test]
+fn test_expand_folders() {
+    let project_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let cargo_file = project_dir.join("Cargo.toml");
+    let files = expand_folders(&[cargo_file.clone(), project_dir.join("src")]);
+    assert_eq!(Some(&cargo_file), files.first());
+    assert!(files.contains(&project_dir.join("src").join("migrator").join("mod.rs")));
+    assert!(files.iter().all(|file| file.is_file()));
+}
+
+/// 检查文件夹是否可写，文件夹不存在时会被创建
+pub fn check_folder_writable<P: AsRef<Path>>(folder: P) -> std::io::Result<()> {
+    let folder = folder.as_ref();
+    std::fs::create_dir_all(folder)?;
+    let test_file = folder.join(".share-rs-write-test");
+    std::fs::write(&test_file, b"")?;
+    std::fs::remove_file(&test_file)
+}
+
+#[test]
+fn test_check_folder_writable() {
+    let folder = std::env::temp_dir().join("share-rs-writable-test");
+    assert!(check_folder_writable(&folder).is_ok());
+    assert!(!folder.join(".share-rs-write-test").exists());
+    // A folder can not be created inside a file
+    let cargo_file = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml");
+    assert!(check_folder_writable(cargo_file.join("Uploads")).is_err());
+}
+
 // 测试示例
 #[test]
 fn test_get_available_filename() {
@@ -235,6 +299,20 @@ pub fn open_folder_and_select_file(folder_path: &str, file_name: &str) -> anyhow
     }
 }
 
+/// Open the file with the default application
+pub fn open_file(full_path: &str) -> anyhow::Result<()> {
+    if cfg!(target_os = "windows") {
+        Command::new("cmd")
+            .args(&["/C", "start", "", full_path])
+            .spawn()?;
+    } else if cfg!(target_os = "macos") {
+        Command::new("open").arg(full_path).spawn()?;
+    } else {
+        Command::new("xdg-open").arg(full_path).spawn()?;
+    }
+    Ok(())
+}
+
 pub fn open_file_in_file_manager(full_path: &str) {
     let path = PathBuf::new().join(full_path);
     let folder_path = path.parent();
@@ -286,6 +364,30 @@ pub async fn compute_file_hash<P: AsRef<Path>>(path: P) -> io::Result<String> {
     Ok(format!("{:x}", result))
 }
 
+/// 读取文件的大小和修改时间
+pub async fn read_file_metadata<P: AsRef<Path>>(
+    file_path: P,
+) -> anyhow::Result<(i64, NaiveDateTime)> {
+    let metadata = async_fs::metadata(file_path.as_ref())
+        .await
+        .context(format!(
+            "Failed to read metadata of {:?}",
+            file_path.as_ref()
+        ))?;
+    let modified = metadata
+        .modified()
+        .context("Failed to read modified time")?;
+    let modified_at = DateTime::<Local>::from(modified).naive_local();
+    Ok((metadata.len() as i64, modified_at))
+}
+
+/// 生成随机的访问令牌，128位随机数的十六进制表示，无法根据其他令牌推测
+pub fn generate_access_token() -> String {
+    let mut bytes = [0u8; 16];
+    getrandom::fill(&mut bytes).expect("Failed to generate random bytes");
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
 pub async fn check_file_hash<P: AsRef<Path>>(path: P, expected_hash: &str) -> io::Result<bool> {
     let file_hash = compute_file_hash(path).await?;
     Ok(file_hash == expected_hash)
@@ -320,3 +422,147 @@ pub async fn check_file<P: AsRef<Path>>(
         )))
     }
 }
+
+/// PIN哈希的迭代次数。PIN通常只有几位数字，哈希必须足够慢，
+/// 才能防止拿到数据库文件的人离线暴力破解
+const PIN_HASH_ITERATIONS: u32 = 600_000;
+
+const PIN_HASH_ALGORITHM: &str = "pbkdf2-sha256";
+
+/// 计算PIN的哈希值，数据库中不保存PIN的明文。
+/// 结果为`pbkdf2-sha256$迭代次数$盐值$哈希值`，盐值是随机生成的
+pub fn hash_pin(pin: &str) -> String {
+    hash_pin_with_iterations(pin, PIN_HASH_ITERATIONS)
+}
+
+fn hash_pin_with_iterations(pin: &str, iterations: u32) -> String {
+    let mut salt = [0u8; 16];
+    getrandom::fill(&mut salt).expect("Failed to generate random bytes");
+    let salt: String = salt.iter().map(|byte| format!("{:02x}", byte)).collect();
+    let hash = pbkdf2_pin_hash(pin, &salt, iterations);
+    format!("{PIN_HASH_ALGORITHM}${iterations}${salt}${hash}")
+}
+
+fn pbkdf2_pin_hash(pin: &str, salt: &str, iterations: u32) -> String {
+    let mut hash = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(pin.as_bytes(), salt.as_bytes(), iterations, &mut hash);
+    hash.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// 校验PIN是否与`hash_pin`生成的哈希值一致，按固定时间比较，避免通过响应时间猜测哈希值。
+/// 计算哈希比较耗时，不要在异步任务中直接调用
+pub fn verify_pin_hash(pin: &str, pin_hash: &str) -> bool {
+    let parts: Vec<&str> = pin_hash.split('$').collect();
+    let [PIN_HASH_ALGORITHM, iterations, salt, expected] = parts[..] else {
+        return false;
+    };
+    let Ok(iterations) = iterations.parse::<u32>() else {
+        return false;
+    };
+    let actual = pbkdf2_pin_hash(pin, salt, iterations);
+    actual.len() == expected.len()
+        && actual
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+#[test]
+fn test_hash_pin() {
+    let pin_hash = hash_pin_with_iterations("1234", 1000);
+    let parts: Vec<&str> = pin_hash.split('$').collect();
+    assert_eq!(vec![PIN_HASH_ALGORITHM, "1000"], parts[..2]);
+    assert_eq!((32, 64), (parts[2].len(), parts[3].len()));
+    assert_ne!(pin_hash, hash_pin_with_iterations("1234", 1000));
+    assert!(verify_pin_hash("1234", &pin_hash));
+    assert!(!verify_pin_hash("4321", &pin_hash));
+    assert!(!verify_pin_hash("1234", "1234"));
+    assert!(!verify_pin_hash(
+        "1234",
+        &pin_hash.replacen("1000", "1001", 1)
+    ));
+}
+
+/// 按照RFC 4180转义CSV字段，包含逗号、引号或换行的字段会被引号包裹，
+/// 以公式字符开头的字段加上单引号前缀，防止在表格软件中被当作公式执行
+pub fn escape_csv_field(field: &str) -> String {
+    let field = if field.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{field}")
+    } else {
+        field.to_string()
+    };
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field
+    }
+}
+
+/// 将多行数据转换成CSV文本
+pub fn to_csv<R, F>(rows: R) -> String
+where
+    R: IntoIterator<Item = Vec<F>>,
+    F: AsRef<str>,
+{
+    let mut csv = String::new();
+    for row in rows {
+        let row: Vec<String> = row
+            .iter()
+            .map(|field| escape_csv_field(field.as_ref()))
+            .collect();
+        csv.push_str(&row.join(","));
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+#[test]
+fn test_to_csv() {
+    assert_eq!("abc", escape_csv_field("abc"));
+    assert_eq!("\"a,b\"", escape_csv_field("a,b"));
+    assert_eq!("\"say \"\"hi\"\"\"", escape_csv_field("say \"hi\""));
+    assert_eq!("\"a\nb\"", escape_csv_field("a\nb"));
+    assert_eq!("'=1+2", escape_csv_field("=1+2"));
+    assert_eq!("'@SUM(A1)", escape_csv_field("@SUM(A1)"));
+    assert_eq!("'-2", escape_csv_field("-2"));
+    assert_eq!(
+        "\"'=HYPERLINK(\"\"x\"\")\"",
+        escape_csv_field("=HYPERLINK(\"x\")")
+    );
+    assert_eq!("\"'\r1\"", escape_csv_field("\r1"));
+
+    let csv = to_csv(vec![vec!["name", "size"], vec!["a,b.txt", "42"]]);
+    assert_eq!("name,size\r\n\"a,b.txt\",42\r\n", csv);
+}
+
+/// 将字节数格式化为便于阅读的文本，例如`1.5 MiB`
+pub fn format_bytes(bytes: i64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024. && unit < UNITS.len() - 1 {
+        size /= 1024.;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[unit])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+#[test]
+fn test_format_bytes() {
+    assert_eq!("512 B", format_bytes(512));
+    assert_eq!("1.5 KiB", format_bytes(1536));
+    assert_eq!("2.0 GiB", format_bytes(2 * 1024 * 1024 * 1024));
+}
+
+#[test]
+fn test_generate_access_token() {
+    let token = generate_access_token();
+    assert_eq!(32, token.len());
+    assert!(token.chars().all(|c| c.is_ascii_hexdigit()));
+    assert_ne!(token, generate_access_token());
+}
@@ -0,0 +1,48 @@
+import {API_BASE, ApiError, readApiError, ShareList} from './request';
+
+const PIN_HEADER = 'X-Collection-Pin';
+
+// 分享集合的基本信息，不需要PIN即可获取
+export interface CollectionInfo {
+    id: string,
+    name: string,
+    pin_required: boolean,
+}
+
+// PIN同时保存在cookie中，下载请求无法设置请求头
+function pinCookie(collectionId: string): string {
+    return `share_rs_collection_pin_${collectionId}`;
+}
+
+export function savePin(collectionId: string, pin: string) {
+    document.cookie = `${pinCookie(collectionId)}=${encodeURIComponent(pin)}; path=/; SameSite=Lax`;
+}
+
+// 集合通过访问令牌而不是集合id访问
+export async function getCollectionInfo(accessToken: string): Promise<CollectionInfo> {
+    const response = await fetch(`${API_BASE}/collections/${accessToken}`);
+    if (!response.ok) {
+        const error = await readApiError(response);
+        throw new Error(error.message);
+    }
+    return await response.json() as CollectionInfo;
+}
+
+// PIN不正确时抛出的错误码为pin_required
+export async function getCollectionShares(accessToken: string, pin?: string): Promise<ShareList> {
+    const response = await fetch(`${API_BASE}/collections/${accessToken}/shares`, {
+        headers: pin ? {[PIN_HEADER]: pin} : undefined,
+    });
+    if (!response.ok) {
+        throw await readApiError(response);
+    }
+    return await response.json() as ShareList;
+}
+
+export function isPinError(error: unknown): error is ApiError {
+    return (error as ApiError)?.code === 'pin_required';
+}
+
+export function collectionDownloadUrl(accessToken: string, shareId: string): string {
+    return `${API_BASE}/collections/${accessToken}/stream/${shareId}?force_download=true`;
+}
@@ -0,0 +1,49 @@
+import {API_BASE} from './request';
+
+const DEVICE_ID_KEY = 'share_rs_device_id';
+const DEVICE_NAME_KEY = 'share_rs_device_name';
+const DEVICE_ID_HEADER = 'X-Device-Id';
+
+// 随机生成设备id，局域网内通过http访问时crypto.randomUUID不可用
+function generateDeviceId(): string {
+    const bytes = new Uint8Array(16);
+    crypto.getRandomValues(bytes);
+    return Array.from(bytes, byte => byte.toString(16).padStart(2, '0')).join('');
+}
+
+// 获取当前浏览器的设备id，同时写入cookie，使下载请求也能携带设备id
+export function getDeviceId(): string {
+    let deviceId = localStorage.getItem(DEVICE_ID_KEY);
+    if (!deviceId) {
+        deviceId = generateDeviceId();
+        localStorage.setItem(DEVICE_ID_KEY, deviceId);
+    }
+    document.cookie = `${DEVICE_ID_KEY}=${deviceId}; path=/; max-age=31536000; SameSite=Lax`;
+    return deviceId;
+}
+
+export function getDeviceName(): string | null {
+    return localStorage.getItem(DEVICE_NAME_KEY);
+}
+
+// 上传请求需要携带的设备信息请求头
+export function deviceHeaders(): Record<string, string> {
+    return {[DEVICE_ID_HEADER]: getDeviceId()};
+}
+
+// 注册设备名称，例如 "Alice's Pixel"
+export async function registerDevice(name: string): Promise<string> {
+    const response = await fetch(`${API_BASE}/devices/register`, {
+        method: 'POST',
+        headers: {
+            'Content-Type': 'application/json',
+        },
+        body: JSON.stringify({device_id: getDeviceId(), name}),
+    });
+    if (!response.ok) {
+        throw new Error(`HTTP error! status: ${response.status}`);
+    }
+    const device = await response.json() as { device_id: string, name: string };
+    localStorage.setItem(DEVICE_NAME_KEY, device.name);
+    return device.name;
+}
@@ -1,14 +1,43 @@
+// 接口统一挂载在/api/v1下
+export const API_BASE = '/api/v1';
+
+// 服务端统一的错误响应
+export interface ApiError {
+    code: string,
+    message: string,
+    details: unknown,
+}
+
+// 读取错误响应，响应不是JSON时把响应文本作为错误信息
+export async function readApiError(response: Response): Promise<ApiError> {
+    const text = await response.text();
+    try {
+        return JSON.parse(text) as ApiError;
+    } catch {
+        return {code: 'unknown', message: text || response.statusText, details: null};
+    }
+}
 
 export interface ShareItem {
     id: string,
     file_name: string,
     mime_type: string,
+    file_size: number,
+    modified_at: string | null,
+    // 只有获取过分享详情之后才会有值
+    sha256: string | null,
+    // Available、Missing或者Changed
+    status: string,
+    // 下载、预览以及获取缩略图时使用的访问令牌
+    access_token: string,
+    // 过期之后分享项会被移除，为null时永不过期
+    expires_at: string | null,
 }
 
 export type ShareList = Array<ShareItem>;
 
 export async function getShareList(): Promise<ShareList> {
-    const response = await fetch('/shares', {
+    const response = await fetch(`${API_BASE}/shares`, {
         method: 'POST',
         headers: {
             'Content-Type': 'application/json',
@@ -20,3 +49,22 @@ export async function getShareList(): Promise<ShareList> {
     return await response.json() as ShareList;
 }
 
+
+// 获取分享详情，第一次获取时服务端会计算文件的SHA-256
+export async function getShareDetail(accessToken: string): Promise<ShareItem> {
+    const response = await fetch(`${API_BASE}/shares/${accessToken}`);
+    if (!response.ok) {
+        throw new Error(`HTTP error! status: ${response.status}`);
+    }
+    return await response.json() as ShareItem;
+}
+
+export function formatFileSize(size: number): string {
+    const units = ['B', 'KiB', 'MiB', 'GiB', 'TiB'];
+    let unit = 0;
+    while (size >= 1024 && unit < units.length - 1) {
+        size /= 1024;
+        unit++;
+    }
+    return unit === 0 ? `${size} ${units[unit]}` : `${size.toFixed(1)} ${units[unit]}`;
+}
@@ -1,5 +1,7 @@
 // UploadManager.ts
 import {HashWorkerManager} from './hash-worker-manager';
+import {deviceHeaders} from './device';
+import {API_BASE, readApiError} from './request';
 
 interface Chunk {
     status: 'waiting' | 'uploading' | 'completed';
@@ -279,17 +281,17 @@ export class UploadManager {
 
     // 初始化上传
     private async initUpload(file: File, fileHash: string): Promise<InitUploadResponse> {
-        const response = await fetch(`/upload/init`, {
+        const response = await fetch(`${API_BASE}/upload/init`, {
             method: 'POST', headers: {
-                'Content-Type': 'application/json',
+                'Content-Type': 'application/json', ...deviceHeaders(),
             }, body: JSON.stringify({
                 file_name: file.name, file_size: file.size, file_hash: fileHash,
             }),
         });
 
         if (!response.ok) {
-            const responseText = await response.text();
-            throw new Error(`初始化上传失败, statusText: ${response.statusText}, responseText: ${responseText}`);
+            const error = await readApiError(response);
+            throw new Error(`初始化上传失败, code: ${error.code}, message: ${error.message}`);
         }
 
         return await response.json();
@@ -311,17 +313,17 @@ export class UploadManager {
         this.abortControllers.set(chunkNumber, controller);
 
         try {
-            const response = await fetch(`/upload/chunk`, {
-                method: 'POST', body: formData, signal: controller.signal,
+            const response = await fetch(`${API_BASE}/upload/chunk`, {
+                method: 'POST', headers: deviceHeaders(), body: formData, signal: controller.signal,
             });
 
             if (!response.ok) {
-                const responseText = await response.text();
-                if (responseText === 'File already completed') {
+                const error = await readApiError(response);
+                if (error.code === 'upload_completed') {
                     console.warn('File already completed');
                     this.status!.status = UploadState.Completed;
                 } else {
-                    throw new Error(`分片上传失败: ${responseText}`);
+                    throw new Error(`分片上传失败: ${error.message}`);
                 }
             }
 
@@ -339,16 +341,17 @@ export class UploadManager {
 
     // 完成上传
     private async completeUpload(fileId: string): Promise<void> {
-        const response = await fetch(`/upload/complete`, {
+        const response = await fetch(`${API_BASE}/upload/complete`, {
             method: 'POST', headers: {
-                'Content-Type': 'application/json',
+                'Content-Type': 'application/json', ...deviceHeaders(),
             }, body: JSON.stringify({
                 file_id: fileId,
             }),
         });
 
         if (!response.ok) {
-            throw new Error(`完成上传失败: ${response.statusText}`);
+            const error = await readApiError(response);
+            throw new Error(`完成上传失败: ${error.message}`);
         }
 
         this.updateStatus({...this.status!, status: UploadState.Completed});
@@ -2,11 +2,16 @@
 
 import {useEffect, useState, MouseEvent} from "react";
 
-import {getShareList, ShareList} from "@/app/api/request";
+import {API_BASE, formatFileSize, getShareDetail, getShareList, ShareItem, ShareList} from "@/app/api/request";
 import {ThemeProvider} from "next-themes";
 import DownloadIcon from '@/app/icon/download.svg';
 import HeaderBar from "@/component/header-bar";
 
+const iconUrl = (shareItem: ShareItem) => `${API_BASE}/icons?mime_type=` + encodeURIComponent(shareItem.mime_type);
+
+const thumbnailUrl = (shareItem: ShareItem) =>
+    shareItem.mime_type.startsWith("image/") ? `${API_BASE}/thumbnails/${shareItem.access_token}` : iconUrl(shareItem);
+
 export default function Home() {
 
     const emptyList: ShareList = []
@@ -18,12 +23,19 @@ export default function Home() {
         }).catch(console.error)
     }, []);
 
-    const preview = (fileId: string) => {
-        window.open(`/stream/${fileId}?force_download=false`, "_blank");
+    const preview = (accessToken: string) => {
+        window.open(`${API_BASE}/preview/${accessToken}`, "_blank");
     };
-    const download = (event: MouseEvent, fileId: string) => {
+    const download = (event: MouseEvent, accessToken: string) => {
         event.nativeEvent.stopPropagation();
-        window.open(`/stream/${fileId}?force_download=true`, "_blank");
+        window.open(`${API_BASE}/stream/${accessToken}?force_download=true`, "_blank");
+    };
+    const showChecksum = (event: MouseEvent, accessToken: string) => {
+        event.nativeEvent.stopPropagation();
+        event.stopPropagation();
+        getShareDetail(accessToken).then(detail => {
+            window.prompt(`SHA-256 of ${detail.file_name}`, detail.sha256 ?? '');
+        }).catch(console.error);
     };
     return (
         <ThemeProvider>
@@ -35,11 +47,31 @@ export default function Home() {
                             return (
                                 <div
                                     className="h-12 pl-4 pr-4 flex flex-row justify-start items-center gap-4 hover:bg-blue-100 dark:hover:bg-custom-gray"
-                                    key={index} onClick={() => preview(shareItem.id)}>
-                                    <img className="size-8" src={"/icons?mime_type=" + encodeURIComponent(shareItem.mime_type)}
+                                    key={index} onClick={() => preview(shareItem.access_token)}>
+                                    <img className="size-8 object-cover rounded"
+                                         src={thumbnailUrl(shareItem)}
+                                         onError={(event) => {
+                                             // 无法生成缩略图时退回到文件类型图标
+                                             const icon = iconUrl(shareItem);
+                                             if (!event.currentTarget.src.endsWith(icon)) {
+                                                 event.currentTarget.src = icon;
+                                             }
+                                         }}
                                          alt="File Icon"/>
-                                    <div className="grow shrink truncate">{shareItem.file_name}</div>
-                                    <div className="size-8" onClick={(event) => download(event, shareItem.id)}>
+                                    <div className="grow shrink min-w-0 flex flex-col">
+                                        <div className="truncate">{shareItem.file_name}</div>
+                                        <div className="text-xs opacity-70 truncate">
+                                            {shareItem.status === 'Missing' && <span className="text-amber-500">Missing · </span>}
+                                            {formatFileSize(shareItem.file_size)}
+                                            {shareItem.modified_at && ` · ${shareItem.modified_at.replace('T', ' ')}`}
+                                            {' · '}
+                                            <span className="hover:text-blue-400 cursor-pointer"
+                                                  onClick={(event) => showChecksum(event, shareItem.access_token)}>
+                                                SHA-256
+                                            </span>
+                                        </div>
+                                    </div>
+                                    <div className="size-8" onClick={(event) => download(event, shareItem.access_token)}>
                                         <DownloadIcon className="dark:fill-white hover:fill-blue-400 cursor-pointer active:fill-blue-200"/>
                                     </div>
 
@@ -1,13 +1,30 @@
+import {useEffect, useState} from "react";
 import ShareIcon from "@/app/icon/share.svg";
 import UploadIcon from "@/app/icon/upload.svg";
+import {getDeviceId, getDeviceName, registerDevice} from "@/app/api/device";
 
 export default function HeaderBar(props: {pageTitle: string}){
+    const [deviceName, setDeviceName] = useState<string | null>(null);
+
+    useEffect(() => {
+        // 确保设备id已经生成并写入cookie
+        getDeviceId();
+        setDeviceName(getDeviceName());
+    }, []);
+
     const goToSharePage = () => {
         window.location.href = '/web/index.html';
     }
     const goToUploadPage = () => {
         window.location.href = '/web/upload.html';
     }
+    const renameDevice = () => {
+        const name = window.prompt("Name this device, e.g. Alice's Pixel", deviceName ?? "");
+        if (name === null || name.trim() === "") {
+            return;
+        }
+        registerDevice(name.trim()).then(setDeviceName).catch(console.error);
+    }
 
     return (
         <div
@@ -16,11 +33,17 @@ export default function HeaderBar(props: {pageTitle: string}){
                 <ShareIcon
                     className="w-8 h-8 fill-white active:fill-custom-gray hover:fill-blue-400 cursor-pointer"/>
             </div>
-            <div>{props.pageTitle}</div>
+            <div className="flex flex-col items-center">
+                <div>{props.pageTitle}</div>
+                <div className="text-xs opacity-70 hover:text-blue-400 cursor-pointer" onClick={renameDevice}
+                     title={"Rename this device"}>
+                    {deviceName ?? "Name this device"}
+                </div>
+            </div>
             <div onClick={goToUploadPage} title={"To upload page"}>
                 <UploadIcon
                     className="w-8 h-8 fill-white active:fill-custom-gray hover:fill-blue-400 cursor-pointer"/>
             </div>
         </div>
     )
-}
\ No newline at end of file
+}
@@ -0,0 +1,169 @@
+'use client'
+import "@/app/globals.css";
+import HeaderBar from "@/component/header-bar";
+import {ThemeProvider} from "next-themes";
+import {useEffect, useState} from "react";
+import {API_BASE} from "@/app/api/request";
+
+interface Parameter {
+    name: string,
+    in: 'path' | 'query' | 'header' | 'cookie',
+    required?: boolean,
+    description?: string,
+}
+
+interface Operation {
+    summary?: string,
+    description?: string,
+    tags?: string[],
+    parameters?: Parameter[],
+    requestBody?: { content: Record<string, unknown> },
+    responses: Record<string, { description: string }>,
+}
+
+interface OpenApiDocument {
+    info: { title: string, version: string, description?: string },
+    paths: Record<string, Record<string, Operation>>,
+}
+
+interface Endpoint {
+    method: string,
+    path: string,
+    operation: Operation,
+}
+
+interface Result {
+    status: number,
+    body: string,
+}
+
+function listEndpoints(openApiDocument: OpenApiDocument): Endpoint[] {
+    return Object.entries(openApiDocument.paths).flatMap(([path, operations]) =>
+        Object.entries(operations).map(([method, operation]) => ({method, path, operation}))
+    );
+}
+
+// 用参数替换路径中的占位符，并拼接查询参数
+function buildUrl(endpoint: Endpoint, values: Record<string, string>): string {
+    let path = endpoint.path;
+    const query = new URLSearchParams();
+    for (const parameter of endpoint.operation.parameters ?? []) {
+        const value = values[parameter.name] ?? '';
+        if (parameter.in === 'path') {
+            path = path.replace(`{${parameter.name}}`, encodeURIComponent(value));
+        } else if (parameter.in === 'query' && value !== '') {
+            query.append(parameter.name, value);
+        }
+    }
+    const search = query.toString();
+    return `${API_BASE}${path}${search ? `?${search}` : ''}`;
+}
+
+function EndpointPanel(props: { endpoint: Endpoint }) {
+    const {endpoint} = props;
+    const [open, setOpen] = useState(false);
+    const [values, setValues] = useState<Record<string, string>>({});
+    const [body, setBody] = useState('');
+    const [result, setResult] = useState<Result | null>(null);
+
+    const content = endpoint.operation.requestBody?.content ?? {};
+    const jsonBody = 'application/json' in content;
+    const multipart = 'multipart/form-data' in content;
+
+    const send = async () => {
+        const response = await fetch(buildUrl(endpoint, values), {
+            method: endpoint.method.toUpperCase(),
+            headers: jsonBody ? {'Content-Type': 'application/json'} : undefined,
+            body: jsonBody ? body : undefined,
+        });
+        const text = await response.text();
+        let formatted = text;
+        try {
+            formatted = JSON.stringify(JSON.parse(text), null, 2);
+        } catch {
+            // 不是JSON时原样显示
+        }
+        setResult({status: response.status, body: formatted});
+    };
+
+    return (
+        <div className="border-b border-header-bar-border">
+            <div className="h-12 pl-4 pr-4 flex flex-row items-center gap-4 cursor-pointer hover:bg-blue-100 dark:hover:bg-custom-gray"
+                 onClick={() => setOpen(!open)}>
+                <div className="w-16 flex-none font-bold uppercase">{endpoint.method}</div>
+                <div className="flex-none font-mono">{endpoint.path}</div>
+                <div className="grow shrink truncate text-sm opacity-70">{endpoint.operation.summary}</div>
+            </div>
+            {open ? (
+                <div className="pl-4 pr-4 pb-4 flex flex-col gap-2 text-sm">
+                    {endpoint.operation.description ? <div>{endpoint.operation.description}</div> : null}
+                    {(endpoint.operation.parameters ?? []).map(parameter => (
+                        <label key={parameter.name} className="flex flex-row items-center gap-2">
+                            <span className="w-32 flex-none font-mono">
+                                {parameter.name}{parameter.required ? '*' : ''}
+                            </span>
+                            <input className="grow border rounded pl-2 pr-2 h-8 bg-transparent"
+                                   placeholder={`${parameter.in}${parameter.description ? ` · ${parameter.description}` : ''}`}
+                                   value={values[parameter.name] ?? ''}
+                                   onChange={event => setValues({...values, [parameter.name]: event.target.value})}/>
+                        </label>
+                    ))}
+                    {jsonBody ? (
+                        <textarea className="border rounded p-2 h-32 font-mono bg-transparent"
+                                  placeholder="JSON request body"
+                                  value={body}
+                                  onChange={event => setBody(event.target.value)}/>
+                    ) : null}
+                    {multipart ? (
+                        <div className="opacity-70">This endpoint takes a multipart form, use the upload page to try it.</div>
+                    ) : (
+                        <button className="self-start border rounded pl-4 pr-4 h-8 hover:text-blue-400 hover:border-blue-400"
+                                onClick={() => send().catch(console.error)}>
+                            Send
+                        </button>
+                    )}
+                    <div className="flex flex-col gap-1">
+                        {Object.entries(endpoint.operation.responses).map(([status, response]) => (
+                            <div key={status} className="opacity-70">{status}: {response.description}</div>
+                        ))}
+                    </div>
+                    {result ? (
+                        <div className="flex flex-col gap-1">
+                            <div className="font-bold">Status {result.status}</div>
+                            <pre className="border rounded p-2 overflow-x-auto max-h-96">{result.body}</pre>
+                        </div>
+                    ) : null}
+                </div>
+            ) : null}
+        </div>
+    );
+}
+
+export default function ApiExplorer() {
+    const [apiDocument, setApiDocument] = useState<OpenApiDocument | null>(null);
+
+    useEffect(() => {
+        fetch(`${API_BASE}/openapi.json`)
+            .then(response => response.json() as Promise<OpenApiDocument>)
+            .then(setApiDocument)
+            .catch(console.error);
+    }, []);
+
+    return (<ThemeProvider>
+        <div className="flex flex-col h-full justify-start">
+            <HeaderBar pageTitle={"API"}/>
+            {apiDocument ? (
+                <div className="flex flex-col">
+                    <div className="p-4 flex flex-col gap-2">
+                        <div className="font-bold">{apiDocument.info.title} {apiDocument.info.version}</div>
+                        <div className="text-sm opacity-70">{apiDocument.info.description}</div>
+                        <a className="text-sm hover:text-blue-400" href={`${API_BASE}/openapi.json`}>openapi.json</a>
+                    </div>
+                    {listEndpoints(apiDocument).map(endpoint => (
+                        <EndpointPanel key={`${endpoint.method} ${endpoint.path}`} endpoint={endpoint}/>
+                    ))}
+                </div>
+            ) : null}
+        </div>
+    </ThemeProvider>);
+}
@@ -0,0 +1,96 @@
+'use client'
+import "@/app/globals.css";
+import HeaderBar from "@/component/header-bar";
+import {ThemeProvider} from "next-themes";
+import {FormEvent, useEffect, useState} from "react";
+import {API_BASE, formatFileSize, ShareItem, ShareList} from "@/app/api/request";
+import {
+    CollectionInfo,
+    collectionDownloadUrl,
+    getCollectionInfo,
+    getCollectionShares,
+    isPinError,
+    savePin,
+} from "@/app/api/collection";
+import DownloadIcon from '@/app/icon/download.svg';
+
+const iconUrl = (shareItem: ShareItem) => `${API_BASE}/icons?mime_type=` + encodeURIComponent(shareItem.mime_type);
+
+export default function Collection() {
+    const [accessToken, setAccessToken] = useState<string | null>(null);
+    const [collection, setCollection] = useState<CollectionInfo | null>(null);
+    const [shareList, setShareList] = useState<ShareList | null>(null);
+    const [pin, setPin] = useState('');
+    const [error, setError] = useState<string | null>(null);
+
+    useEffect(() => {
+        // 集合的访问令牌通过查询参数传递，例如 /web/collection.html?token=...
+        const token = new URLSearchParams(window.location.search).get('token');
+        if (!token) {
+            setError('Collection not found');
+            return;
+        }
+        setAccessToken(token);
+        getCollectionInfo(token).then(info => {
+            setCollection(info);
+            if (!info.pin_required) {
+                return getCollectionShares(token).then(setShareList);
+            }
+        }).catch(e => setError(e.message ?? String(e)));
+    }, []);
+
+    const unlock = (event: FormEvent) => {
+        event.preventDefault();
+        if (!accessToken || !collection) {
+            return;
+        }
+        getCollectionShares(accessToken, pin).then(data => {
+            savePin(collection.id, pin);
+            setError(null);
+            setShareList(data);
+        }).catch(e => setError(isPinError(e) ? 'Invalid PIN' : e.message ?? String(e)));
+    };
+
+    const download = (shareId: string) => {
+        if (accessToken) {
+            window.open(collectionDownloadUrl(accessToken, shareId), "_blank");
+        }
+    };
+
+    return (<ThemeProvider>
+        <div className="flex flex-col h-full justify-start">
+            <HeaderBar pageTitle={collection?.name ?? "Collection"}/>
+            {error ? <div className="p-4 text-red-500">{error}</div> : null}
+            {collection?.pin_required && !shareList ? (
+                <form className="p-4 flex flex-row items-center gap-2" onSubmit={unlock}>
+                    <input className="border rounded pl-2 pr-2 h-8 bg-transparent"
+                           type="password"
+                           inputMode="numeric"
+                           placeholder="PIN"
+                           value={pin}
+                           onChange={event => setPin(event.target.value)}/>
+                    <button className="border rounded pl-4 pr-4 h-8 hover:text-blue-400 hover:border-blue-400"
+                            type="submit">
+                        Open
+                    </button>
+                </form>
+            ) : null}
+            <div className="flex flex-col justify-start">
+                {(shareList ?? []).map(shareItem => (
+                    <div key={shareItem.id}
+                         className="h-12 pl-4 pr-4 flex flex-row justify-start items-center gap-4 hover:bg-blue-100 dark:hover:bg-custom-gray"
+                         onClick={() => download(shareItem.id)}>
+                        <img className="size-8" src={iconUrl(shareItem)} alt="File Icon"/>
+                        <div className="grow shrink min-w-0 flex flex-col">
+                            <div className="truncate">{shareItem.file_name}</div>
+                            <div className="text-xs opacity-70 truncate">{formatFileSize(shareItem.file_size)}</div>
+                        </div>
+                        <div className="size-8">
+                            <DownloadIcon className="dark:fill-white hover:fill-blue-400 cursor-pointer active:fill-blue-200"/>
+                        </div>
+                    </div>
+                ))}
+            </div>
+        </div>
+    </ThemeProvider>);
+}
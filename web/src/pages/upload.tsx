@@ -8,6 +8,7 @@ import AbortIcon from "@/app/icon/abort.svg";
 import {useEffect, useRef, useState} from "react";
 import {BatchUploadManager} from "@/app/api/batch-upload-manager";
 import {UploadState, UploadStatus} from "@/app/api/upload-manager";
+import {API_BASE} from "@/app/api/request";
 import {LinearProgress} from "@mui/material";
 
 enum UploadItemStatus {
@@ -187,7 +188,7 @@ export default function Upload() {
                                 className="h-12 pl-4 pr-4 flex flex-row justify-start items-center gap-4 "
                             >
                                 <img className="size-8"
-                                     src={"/icons?mime_type=" + encodeURIComponent(uploadItem.mimeType)}
+                                     src={`${API_BASE}/icons?mime_type=` + encodeURIComponent(uploadItem.mimeType)}
                                      alt="File Icon"/>
                                 <div className="grow shrink truncate">{uploadItem.fileName}</div>
 